@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::keccak;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer},
 };
 
 declare_id!("9Hd5Nv7MYPeFbSntrdEg92uojcWGuGGH2Mkmyrm7eMGd");
@@ -13,12 +15,68 @@ pub const XNT_DECIMALS: u8 = 9;
 pub const DEFAULT_HALVING_INTERVAL: u64 = 100_000_000 * 10_u64.pow(GAME_DECIMALS as u32);
 pub const DEFAULT_STAKING_SHARE_BPS: u16 = 3000;
 pub const MAX_ACTIVE_BOOSTS: usize = 8;
+pub const MAX_BOOST_KINDS: usize = 8;
+pub const MAX_INVENTORY_ITEMS: usize = 16;
+pub const LEADERBOARD_SIZE: usize = 10;
+/// Keeps a ranking epoch's claimed bitmap within the 10 KiB init limit.
+pub const MAX_RANKING_LEAVES: u32 = 65_536;
+/// Lock lengths (days) accepted by `stake` and their multipliers (bps),
+/// shortest first.
+pub const LOCK_TIERS: [(u16, u16); 3] = [(7, 10_500), (14, 11_000), (30, 12_000)];
+/// Shortest lock a `LockReductionBps` boost can produce.
+pub const MIN_LOCK_SECS: i64 = 86_400;
+pub const DEFAULT_REWARD_DURATION_SECS: i64 = 7 * 86_400;
+pub const MAX_REWARD_DURATION_SECS: i64 = 90 * 86_400;
+pub const MAX_UNBONDING_PERIOD_SECS: i64 = 30 * 86_400;
+pub const MAX_WARMUP_PERIOD_SECS: i64 = 30 * 86_400;
+/// Shortest treasury timelock; also the delay before a queued policy change
+/// can apply when the current timelock is shorter.
+pub const MIN_TREASURY_TIMELOCK_SECS: i64 = 86_400;
+/// Day boundaries may shift from UTC-14:00 to UTC+14:00.
+pub const MAX_DAY_OFFSET_SECS: i64 = 14 * 3_600;
+/// Checkpoints keep one entry per interval of this many slots (about four
+/// hours at 400 ms slots); voting power is sampled at the start of an
+/// interval.
+pub const CHECKPOINT_INTERVAL_SLOTS: u64 = 36_000;
+/// Pool history kept for governance: one entry per interval with a stake
+/// change, at most 64.
+pub const POOL_CHECKPOINTS: usize = 64;
+/// Per-user history; as long as the pool's so both cover the same window.
+pub const USER_CHECKPOINTS: usize = POOL_CHECKPOINTS;
+/// Intervals `get_voting_power` looks back, counting the current one: 63,
+/// about ten and a half days. A ring of N entries can always answer for the
+/// last N - 1 intervals however often its owner's stake changes, since
+/// dropping the entry a snapshot needs takes N newer intervals. Older slots
+/// fail with `CheckpointUnavailable`.
+pub const SNAPSHOT_WINDOW_INTERVALS: u64 = POOL_CHECKPOINTS as u64 - 1;
+/// Entries a new `UserPositions` index has room for; it grows past this as
+/// positions are added.
+pub const INITIAL_USER_POSITIONS: usize = 4;
+/// sGAME locked (counted in the share supply but never minted) by the first
+/// liquid deposit, so an emptied vault cannot be re-priced by a dust deposit.
+pub const MINIMUM_LIQUID_SHARES: u64 = 1_000;
+/// Layout version written in `UserAccount::version`. Accounts from before
+/// the version byte are recognised by their size.
+pub const USER_ACCOUNT_VERSION: u8 = 1;
 
 const GLOBAL_CONFIG_SEED: &[u8] = b"global-config";
 const STAKING_POOL_SEED: &[u8] = b"staking-pool";
 const USER_STAKE_SEED: &[u8] = b"user-stake";
 const USER_ACCOUNT_SEED: &[u8] = b"user-account";
 const BOOST_CONFIG_SEED: &[u8] = b"boost-config";
+const BOOST_RECEIPT_SEED: &[u8] = b"boost-receipt";
+const BOOST_INVENTORY_SEED: &[u8] = b"boost-inventory";
+const TREASURY_POLICY_SEED: &[u8] = b"treasury-policy";
+const TREASURY_WITHDRAWAL_SEED: &[u8] = b"treasury-withdrawal";
+const TREASURY_POLICY_CHANGE_SEED: &[u8] = b"treasury-policy-change";
+const USER_POSITIONS_SEED: &[u8] = b"user-positions";
+const LIQUID_VAULT_SEED: &[u8] = b"liquid-vault";
+const LIQUID_SHARE_MINT_SEED: &[u8] = b"sgame-mint";
+const LIQUID_XNT_VAULT_SEED: &[u8] = b"liquid-xnt";
+const LIQUID_UNBOND_SEED: &[u8] = b"liquid-unbond";
+const LIQUID_DEPOSIT_SEED: &[u8] = b"liquid-deposit";
+const LEADERBOARD_SEED: &[u8] = b"leaderboard";
+const RANKING_EPOCH_SEED: &[u8] = b"ranking-epoch";
 
 const MINING_POINTS: [u32; 4] = [1, 3, 7, 12];
 
@@ -57,9 +115,7 @@ pub mod x1_mining_arena {
         Ok(())
     }
 
-    pub fn initialize_treasury_vault(
-        ctx: Context<InitializeTreasuryVault>,
-    ) -> Result<()> {
+    pub fn initialize_treasury_vault(ctx: Context<InitializeTreasuryVault>) -> Result<()> {
         require_keys_eq!(
             ctx.accounts.admin.key(),
             ctx.accounts.global_config.admin,
@@ -88,7 +144,58 @@ pub mod x1_mining_arena {
         staking_pool.treasury_xnt_vault = ctx.accounts.global_config.treasury_xnt_vault;
         staking_pool.total_effective_stake = 0;
         staking_pool.acc_reward_per_share = 0;
+        staking_pool.pending_undistributed = 0;
+        staking_pool.reward_duration_secs = DEFAULT_REWARD_DURATION_SECS;
+        staking_pool.reward_rate = 0;
+        staking_pool.period_finish = Clock::get()?.unix_timestamp;
+        staking_pool.last_update_ts = staking_pool.period_finish;
+
+        Ok(())
+    }
 
+    pub fn configure_staking_pool(
+        ctx: Context<ConfigureStakingPool>,
+        args: StakingPoolConfigArgs,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        update_reward_stream(staking_pool, now)?;
+        if let Some(duration) = args.reward_duration_secs {
+            require!(
+                (0..=MAX_REWARD_DURATION_SECS).contains(&duration),
+                ArenaError::InvalidDuration
+            );
+            staking_pool.reward_duration_secs = duration;
+        }
+        if let Some(period) = args.unbonding_period_secs {
+            require!(
+                (0..=MAX_UNBONDING_PERIOD_SECS).contains(&period),
+                ArenaError::InvalidDuration
+            );
+            staking_pool.unbonding_period_secs = period;
+        }
+        if let Some(period) = args.warmup_period_secs {
+            require!(
+                (0..=MAX_WARMUP_PERIOD_SECS).contains(&period),
+                ArenaError::InvalidDuration
+            );
+            staking_pool.warmup_period_secs = period;
+        }
+        if let Some(max_amount) = args.max_position_amount {
+            staking_pool.max_position_amount = max_amount;
+        }
+        if let Some(max_effective) = args.max_user_effective_stake {
+            staking_pool.max_user_effective_stake = max_effective;
+        }
+        if let Some(bps) = args.max_user_share_bps {
+            require!(bps <= 10_000, ArenaError::InvalidBps);
+            staking_pool.max_user_share_bps = bps;
+        }
         Ok(())
     }
 
@@ -104,9 +211,15 @@ pub mod x1_mining_arena {
     }
 
     pub fn mine_with_rig(ctx: Context<MineWithRig>, rig_id: u8) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
         let clock = Clock::get()?;
         let user_account = &mut ctx.accounts.user_account;
         user_account.purge_expired(clock.unix_timestamp);
+        user_account.roll_over_day(ctx.accounts.global_config.day_id(clock.unix_timestamp));
         if user_account.owner == Pubkey::default() {
             user_account.owner = ctx.accounts.payer.key();
         }
@@ -124,6 +237,7 @@ pub mod x1_mining_arena {
 
         let mut deposit_xnt = rig.base_cost_xnt;
         let mut used_free_rig = false;
+        let mut charges_used: Vec<BoostChargeUsed> = Vec::new();
 
         // Apply free rig ticket boost if present.
         if let Some(idx) = user_account.active_boosts.iter().position(|b| {
@@ -133,8 +247,31 @@ pub mod x1_mining_arena {
         }) {
             deposit_xnt = 0;
             used_free_rig = true;
-            user_account.active_boosts.remove(idx);
+            let boost_id = user_account.active_boosts[idx].boost_id;
+            let charges_remaining = user_account.consume_boost_charge(idx);
+            charges_used.push(BoostChargeUsed {
+                boost_id,
+                charges_remaining,
+            });
+        } else {
+            let discount_bps = boost_bonus_bps(
+                global,
+                user_account,
+                BoostKind::RigCostDiscountBps,
+                Some(rig_id),
+                clock.unix_timestamp,
+            )
+            .min(10_000);
+            deposit_xnt = ((deposit_xnt as u128) * (10_000 - discount_bps) / 10_000u128) as u64;
         }
+        let crit_bonus_bps = boost_bonus_bps(
+            global,
+            user_account,
+            BoostKind::CritChanceBps,
+            Some(rig_id),
+            clock.unix_timestamp,
+        );
+        let prob_high_bps = (rig.prob_high_bps as u128 + crit_bonus_bps).min(10_000) as u16;
 
         if deposit_xnt > 0 {
             // Transfer XNT to treasury vault.
@@ -153,26 +290,27 @@ pub mod x1_mining_arena {
                 .ok_or(ArenaError::MathOverflow)?
                 / 10_000u128;
 
-            if to_stakers > 0 && staking_pool.total_effective_stake > 0 {
-                staking_pool.acc_reward_per_share = staking_pool
-                    .acc_reward_per_share
-                    .checked_add(
-                        (to_stakers)
-                            .checked_mul(PRECISION)
-                            .ok_or(ArenaError::MathOverflow)?
-                            .checked_div(staking_pool.total_effective_stake)
-                            .ok_or(ArenaError::MathOverflow)?,
-                    )
-                    .ok_or(ArenaError::MathOverflow)?;
-            }
+            fund_staker_rewards(staking_pool, to_stakers, clock.unix_timestamp)?;
 
             user_account.mining_xnt_spent =
                 user_account.mining_xnt_spent.saturating_add(deposit_xnt);
         }
 
-        let reward =
-            compute_reward_amount(&rig, halving_level, &ctx.accounts.payer, &clock, rig_id)?;
-        let boosted_reward = apply_mining_reward_boost(user_account, reward, clock.unix_timestamp);
+        let reward = compute_reward_amount(
+            &rig,
+            halving_level,
+            prob_high_bps,
+            &ctx.accounts.payer,
+            &clock,
+            rig_id,
+        )?;
+        let boosted_reward = apply_mining_reward_boost(
+            &ctx.accounts.global_config,
+            user_account,
+            reward,
+            rig_id,
+            clock.unix_timestamp,
+        );
         let reward_u64 = boosted_reward.min(u64::MAX as u128) as u64;
 
         let signer_seeds: &[&[u8]] = &[GLOBAL_CONFIG_SEED, &[ctx.bumps.global_config]];
@@ -197,8 +335,21 @@ pub mod x1_mining_arena {
 
         // Update points.
         let mut points = MINING_POINTS[rig_id as usize] as u128;
-        points = apply_mining_point_boost(user_account, points, clock.unix_timestamp);
+        points = apply_mining_point_boost(
+            &ctx.accounts.global_config,
+            user_account,
+            points,
+            rig_id,
+            clock.unix_timestamp,
+        );
         let points_u64 = points.min(u64::MAX as u128) as u64;
+        charges_used.extend(consume_mining_charges(
+            &ctx.accounts.global_config,
+            user_account,
+            rig_id,
+            !used_free_rig,
+            clock.unix_timestamp,
+        ));
 
         user_account.daily_points = user_account.daily_points.saturating_add(points_u64 as u32);
         user_account.lifetime_points = user_account.lifetime_points.saturating_add(points_u64);
@@ -212,21 +363,39 @@ pub mod x1_mining_arena {
             user_account.mining_crit_count = user_account.mining_crit_count.saturating_add(1);
         }
 
+        record_leaderboard_points(
+            &ctx.accounts.global_config,
+            ctx.accounts
+                .leaderboard
+                .as_deref_mut()
+                .map(|leaderboard| &mut **leaderboard),
+            clock.unix_timestamp,
+            ctx.accounts.payer.key(),
+            user_account.daily_points,
+        )?;
+
         emit!(MiningEvent {
             user: ctx.accounts.payer.key(),
             rig_id,
             deposit_xnt,
             reward_game: reward_u64,
             used_free_rig,
+            charges_used,
         });
 
         Ok(())
     }
 
     pub fn stake(ctx: Context<Stake>, amount: u64, lock_days: u16) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
         let clock = Clock::get()?;
         let user_account = &mut ctx.accounts.user_account;
         user_account.purge_expired(clock.unix_timestamp);
+        user_account.roll_over_day(ctx.accounts.global_config.day_id(clock.unix_timestamp));
         if user_account.owner == Pubkey::default() {
             user_account.owner = ctx.accounts.owner.key();
         }
@@ -238,13 +407,28 @@ pub mod x1_mining_arena {
 
         let position_id = user_account.next_position_id.max(1);
         user_account.next_position_id = position_id;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
 
+        // A shortened lock keeps the multiplier of the tier chosen.
         let lock_multiplier_bps = lock_multiplier(lock_days)?;
-        let boost_multiplier_bps = resolve_staking_multiplier(user_account, clock.unix_timestamp);
+        let lock_reduction_bps = boost_bonus_bps(
+            &ctx.accounts.global_config,
+            user_account,
+            BoostKind::LockReductionBps,
+            None,
+            clock.unix_timestamp,
+        );
+        let lock_secs = reduced_lock_secs(lock_days, lock_reduction_bps);
+        let boost_multiplier_bps = resolve_staking_multiplier(
+            &ctx.accounts.global_config,
+            user_account,
+            clock.unix_timestamp,
+        );
 
         let stake_position = &mut ctx.accounts.user_stake_position;
         if stake_position.owner == Pubkey::default() {
             stake_position.owner = ctx.accounts.owner.key();
+            stake_position.seed_owner = ctx.accounts.owner.key();
         }
         stake_position.position_id = position_id;
         require_keys_eq!(
@@ -265,26 +449,51 @@ pub mod x1_mining_arena {
         token::transfer(cpi_ctx, amount)?;
 
         let effective = calculate_effective(amount, lock_multiplier_bps, boost_multiplier_bps)?;
+        enforce_stake_caps(
+            &ctx.accounts.staking_pool,
+            user_account.target_effective_stake(),
+            amount,
+            effective,
+            effective,
+        )?;
 
         stake_position.amount_staked = amount;
         stake_position.lock_multiplier_bps = lock_multiplier_bps;
         stake_position.boost_multiplier_bps = boost_multiplier_bps;
-        stake_position.effective_stake = effective;
-        stake_position.lock_until_ts = clock.unix_timestamp + (lock_days as i64) * 86_400;
-        stake_position.reward_debt = stake_position
-            .effective_stake
-            .checked_mul(ctx.accounts.staking_pool.acc_reward_per_share)
-            .ok_or(ArenaError::MathOverflow)?
-            / PRECISION;
-
-        ctx.accounts.staking_pool.total_effective_stake = ctx
-            .accounts
-            .staking_pool
-            .total_effective_stake
-            .saturating_add(effective);
+        stake_position.lock_until_ts = clock.unix_timestamp + lock_secs;
+        let warmup_period = ctx.accounts.staking_pool.warmup_period_secs;
+        if warmup_period > 0 {
+            stake_position.warmup_start_ts = clock.unix_timestamp;
+            stake_position.warmup_end_ts = clock.unix_timestamp.saturating_add(warmup_period);
+        }
+        let warmed = stake_position.warmed_effective_stake(effective, clock.unix_timestamp);
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            user_account,
+            stake_position,
+            warmed,
+            effective,
+            clock.slot,
+        )?;
+        // The first staker after an empty stretch picks up whatever revenue
+        // was buffered while nobody was staked.
+        fold_pending_undistributed(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
 
         user_account.next_position_id = position_id.saturating_add(1);
 
+        reserve_position_entries(
+            &ctx.accounts.user_positions,
+            1,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let user_positions = &mut ctx.accounts.user_positions;
+        user_positions.init_owner(ctx.accounts.owner.key())?;
+        user_positions.upsert(
+            ctx.accounts.user_stake_position.key(),
+            &ctx.accounts.user_stake_position,
+        )?;
+
         emit!(StakeEvent {
             owner: ctx.accounts.owner.key(),
             position_id,
@@ -297,9 +506,15 @@ pub mod x1_mining_arena {
     }
 
     pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
         let position_id = assert_valid_user_stake_pda(
             ctx.program_id,
-            &ctx.accounts.owner.key(),
+            &ctx.accounts.user_stake_position.seed_owner(),
             Some(ctx.accounts.user_stake_position.position_id),
             &ctx.accounts.user_stake_position.key(),
         )?;
@@ -308,6 +523,7 @@ pub mod x1_mining_arena {
             ctx.accounts.owner.key(),
             ArenaError::Unauthorized
         );
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
         let claimed = settle_rewards(
             &mut ctx.accounts.staking_pool,
             &mut ctx.accounts.user_stake_position,
@@ -317,6 +533,17 @@ pub mod x1_mining_arena {
             &ctx.accounts.token_program,
             ctx.bumps.global_config,
         )?;
+        require_keys_eq!(
+            ctx.accounts.user_account.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
+        sync_warmup(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_account,
+            &mut ctx.accounts.user_stake_position,
+            &clock,
+        )?;
 
         emit!(ClaimEvent {
             owner: ctx.accounts.owner.key(),
@@ -326,11 +553,95 @@ pub mod x1_mining_arena {
         Ok(())
     }
 
+    pub fn claim_all<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimAll<'info>>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        require!(
+            !ctx.remaining_accounts.is_empty(),
+            ArenaError::NoPositionsProvided
+        );
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+
+        let owner = ctx.accounts.owner.key();
+        require_keys_eq!(
+            ctx.accounts.user_account.owner,
+            owner,
+            ArenaError::Unauthorized
+        );
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut total_claimed: u64 = 0;
+        for account_info in ctx.remaining_accounts.iter() {
+            require!(account_info.is_writable, ArenaError::InvalidStakePda);
+            require!(
+                !seen.contains(account_info.key),
+                ArenaError::DuplicatePosition
+            );
+            seen.push(*account_info.key);
+
+            grow_account(
+                account_info,
+                &ctx.accounts.owner,
+                &ctx.accounts.system_program,
+                &UserStakePosition::DISCRIMINATOR,
+                8 + UserStakePosition::LEN,
+            )?;
+            let mut position: Account<'info, UserStakePosition> = Account::try_from(account_info)?;
+            assert_valid_user_stake_pda(
+                ctx.program_id,
+                &position.seed_owner(),
+                Some(position.position_id),
+                account_info.key,
+            )?;
+            require_keys_eq!(position.owner, owner, ArenaError::Unauthorized);
+
+            let claimed = harvest_position(&ctx.accounts.staking_pool, &mut position)?;
+            total_claimed = total_claimed
+                .checked_add(claimed)
+                .ok_or(ArenaError::MathOverflow)?;
+            sync_warmup(
+                &mut ctx.accounts.staking_pool,
+                &mut ctx.accounts.user_account,
+                &mut position,
+                &clock,
+            )?;
+            position.exit(ctx.program_id)?;
+        }
+
+        pay_staker_rewards(
+            &mut ctx.accounts.staking_pool,
+            total_claimed,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
+
+        emit!(ClaimAllEvent {
+            owner,
+            positions: seen.len() as u16,
+            rewards_claimed: total_claimed,
+        });
+        Ok(())
+    }
+
+    /// Withdraws an unlocked position. When the pool has an unbonding period
+    /// the first call starts the cooldown like `begin_unbond`, and a call
+    /// after it has elapsed withdraws like `withdraw_unbonded`.
     pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
         let clock = Clock::get()?;
         let position_id = assert_valid_user_stake_pda(
             ctx.program_id,
-            &ctx.accounts.owner.key(),
+            &ctx.accounts.user_stake_position.seed_owner(),
             Some(ctx.accounts.user_stake_position.position_id),
             &ctx.accounts.user_stake_position.key(),
         )?;
@@ -339,20 +650,51 @@ pub mod x1_mining_arena {
             ctx.accounts.owner.key(),
             ArenaError::Unauthorized
         );
+        require_keys_eq!(
+            ctx.accounts.user_account.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
         require!(
             ctx.accounts.user_stake_position.lock_until_ts <= clock.unix_timestamp,
             ArenaError::StakeLocked
         );
 
-        settle_rewards(
-            &mut ctx.accounts.staking_pool,
-            &mut ctx.accounts.user_stake_position,
-            &ctx.accounts.global_config,
-            &ctx.accounts.treasury_xnt_vault,
-            &ctx.accounts.user_xnt_account,
-            &ctx.accounts.token_program,
-            ctx.bumps.global_config,
+        let action = ctx.accounts.user_stake_position.unstake_action(
+            ctx.accounts.staking_pool.unbonding_period_secs,
+            clock.unix_timestamp,
         )?;
+        if action != UnstakeAction::WithdrawUnbonded {
+            update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+            settle_rewards(
+                &mut ctx.accounts.staking_pool,
+                &mut ctx.accounts.user_stake_position,
+                &ctx.accounts.global_config,
+                &ctx.accounts.treasury_xnt_vault,
+                &ctx.accounts.user_xnt_account,
+                &ctx.accounts.token_program,
+                ctx.bumps.global_config,
+            )?;
+            set_effective_stake(
+                &mut ctx.accounts.staking_pool,
+                &mut ctx.accounts.user_account,
+                &mut ctx.accounts.user_stake_position,
+                0,
+                0,
+                clock.slot,
+            )?;
+
+            if let UnstakeAction::BeginUnbond { unbonding_until_ts } = action {
+                ctx.accounts.user_stake_position.unbonding_until_ts = unbonding_until_ts;
+                emit!(UnbondStartedEvent {
+                    owner: ctx.accounts.owner.key(),
+                    position_id,
+                    amount: ctx.accounts.user_stake_position.amount_staked,
+                    unbonding_until_ts,
+                });
+                return Ok(());
+            }
+        }
 
         let amount = ctx.accounts.user_stake_position.amount_staked;
         let stake_seeds: &[&[u8]] = &[STAKING_POOL_SEED, &[ctx.bumps.staking_pool]];
@@ -367,17 +709,16 @@ pub mod x1_mining_arena {
             stake_signer,
         );
         token::transfer(cpi_ctx, amount)?;
-
-        let prev_effective = ctx.accounts.user_stake_position.effective_stake;
-        ctx.accounts.staking_pool.total_effective_stake = ctx
-            .accounts
-            .staking_pool
-            .total_effective_stake
-            .saturating_sub(prev_effective);
-
         ctx.accounts.user_stake_position.amount_staked = 0;
-        ctx.accounts.user_stake_position.effective_stake = 0;
-        ctx.accounts.user_stake_position.reward_debt = 0;
+        // Closed here rather than with `close = owner` because a position
+        // that only started unbonding above has to stay open.
+        ctx.accounts
+            .user_stake_position
+            .close(ctx.accounts.owner.to_account_info())?;
+
+        let user_positions = &mut ctx.accounts.user_positions;
+        user_positions.init_owner(ctx.accounts.owner.key())?;
+        user_positions.remove(&ctx.accounts.user_stake_position.key());
 
         emit!(UnstakeEvent {
             owner: ctx.accounts.owner.key(),
@@ -387,567 +728,4949 @@ pub mod x1_mining_arena {
         Ok(())
     }
 
-    pub fn apply_ranking_results(
-        ctx: Context<ApplyRankingResults>,
-        boost_points_to_add: u64,
-    ) -> Result<()> {
+    /// Starts the cooldown for an unlocked position: rewards are settled and
+    /// the position stops counting towards `total_effective_stake`.
+    pub fn begin_unbond(ctx: Context<BeginUnbond>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let position_id = assert_valid_user_stake_pda(
+            ctx.program_id,
+            &ctx.accounts.user_stake_position.seed_owner(),
+            Some(ctx.accounts.user_stake_position.position_id),
+            &ctx.accounts.user_stake_position.key(),
+        )?;
         require_keys_eq!(
-            ctx.accounts.admin.key(),
-            ctx.accounts.global_config.admin,
+            ctx.accounts.user_stake_position.owner,
+            ctx.accounts.owner.key(),
             ArenaError::Unauthorized
         );
-        let user_account = &mut ctx.accounts.user_account;
-        if user_account.owner == Pubkey::default() {
-            user_account.owner = ctx.accounts.user.key();
-        }
         require_keys_eq!(
-            user_account.owner,
-            ctx.accounts.user.key(),
+            ctx.accounts.user_account.owner,
+            ctx.accounts.owner.key(),
             ArenaError::Unauthorized
         );
-        user_account.boost_points = user_account
-            .boost_points
-            .saturating_add(boost_points_to_add);
-        emit!(RankingAppliedEvent {
-            user: ctx.accounts.user.key(),
-            added_boost_points: boost_points_to_add,
+        require!(
+            ctx.accounts.user_stake_position.lock_until_ts <= clock.unix_timestamp,
+            ArenaError::StakeLocked
+        );
+        require!(
+            !ctx.accounts.user_stake_position.is_unbonding(),
+            ArenaError::AlreadyUnbonding
+        );
+
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        settle_rewards(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_stake_position,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_account,
+            &mut ctx.accounts.user_stake_position,
+            0,
+            0,
+            clock.slot,
+        )?;
+
+        let unbonding_until_ts = clock
+            .unix_timestamp
+            .saturating_add(ctx.accounts.staking_pool.unbonding_period_secs);
+        ctx.accounts.user_stake_position.unbonding_until_ts = unbonding_until_ts;
+
+        emit!(UnbondStartedEvent {
+            owner: ctx.accounts.owner.key(),
+            position_id,
+            amount: ctx.accounts.user_stake_position.amount_staked,
+            unbonding_until_ts,
         });
         Ok(())
     }
 
-    pub fn reset_daily_points(ctx: Context<ResetDailyPoints>, day_id: i64) -> Result<()> {
+    pub fn withdraw_unbonded(ctx: Context<WithdrawUnbonded>) -> Result<()> {
+        let clock = Clock::get()?;
+        let position_id = assert_valid_user_stake_pda(
+            ctx.program_id,
+            &ctx.accounts.user_stake_position.seed_owner(),
+            Some(ctx.accounts.user_stake_position.position_id),
+            &ctx.accounts.user_stake_position.key(),
+        )?;
         require_keys_eq!(
-            ctx.accounts.admin.key(),
-            ctx.accounts.global_config.admin,
+            ctx.accounts.user_stake_position.owner,
+            ctx.accounts.owner.key(),
             ArenaError::Unauthorized
         );
-        let user_account = &mut ctx.accounts.user_account;
-        if user_account.owner == Pubkey::default() {
-            user_account.owner = ctx.accounts.user.key();
-        }
-        require_keys_eq!(
-            user_account.owner,
-            ctx.accounts.user.key(),
-            ArenaError::Unauthorized
+        require!(
+            ctx.accounts.user_stake_position.is_unbonding(),
+            ArenaError::NotUnbonding
+        );
+        require!(
+            ctx.accounts.user_stake_position.unbonding_until_ts <= clock.unix_timestamp,
+            ArenaError::UnbondingInProgress
         );
-        user_account.daily_points = 0;
-        user_account.last_day_id = day_id;
-        Ok(())
-    }
 
-    pub fn upsert_boost_config(
-        ctx: Context<UpsertBoostConfig>,
-        args: BoostConfigArgs,
-    ) -> Result<()> {
-        require_keys_eq!(
-            ctx.accounts.admin.key(),
-            ctx.accounts.global_config.admin,
-            ArenaError::Unauthorized
+        let amount = ctx.accounts.user_stake_position.amount_staked;
+        let stake_seeds: &[&[u8]] = &[STAKING_POOL_SEED, &[ctx.bumps.staking_pool]];
+        let stake_signer: &[&[&[u8]]] = &[stake_seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.staking_vault.to_account_info(),
+                to: ctx.accounts.user_game_account.to_account_info(),
+                authority: ctx.accounts.staking_pool.to_account_info(),
+            },
+            stake_signer,
         );
-        let boost_config = &mut ctx.accounts.boost_config;
-        boost_config.id = args.id;
-        boost_config.kind = args.kind;
-        boost_config.cost_boost_points = args.cost_boost_points;
-        boost_config.value_bps = args.value_bps;
-        boost_config.duration_seconds = args.duration_seconds;
-        boost_config.rig_id = args.rig_id;
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.user_stake_position.amount_staked = 0;
+
+        let user_positions = &mut ctx.accounts.user_positions;
+        user_positions.init_owner(ctx.accounts.owner.key())?;
+        user_positions.remove(&ctx.accounts.user_stake_position.key());
+
+        emit!(UnstakeEvent {
+            owner: ctx.accounts.owner.key(),
+            position_id,
+            amount,
+        });
         Ok(())
     }
 
-    pub fn activate_boost(ctx: Context<ActivateBoost>) -> Result<()> {
+    /// Folds `source_position` into `target_position`, keeping the later
+    /// unlock time and re-deriving multipliers for the combined amount; the
+    /// lock multiplier is the amount-weighted average of the two.
+    pub fn merge_positions(ctx: Context<MergePositions>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
         let clock = Clock::get()?;
-        let user_account = &mut ctx.accounts.user_account;
-        user_account.purge_expired(clock.unix_timestamp);
-        if user_account.owner == Pubkey::default() {
-            user_account.owner = ctx.accounts.user.key();
-        }
+        let owner = ctx.accounts.owner.key();
+        require_keys_neq!(
+            ctx.accounts.target_position.key(),
+            ctx.accounts.source_position.key(),
+            ArenaError::DuplicatePosition
+        );
+        let target_id = assert_valid_user_stake_pda(
+            ctx.program_id,
+            &ctx.accounts.target_position.seed_owner(),
+            Some(ctx.accounts.target_position.position_id),
+            &ctx.accounts.target_position.key(),
+        )?;
+        let source_id = assert_valid_user_stake_pda(
+            ctx.program_id,
+            &ctx.accounts.source_position.seed_owner(),
+            Some(ctx.accounts.source_position.position_id),
+            &ctx.accounts.source_position.key(),
+        )?;
         require_keys_eq!(
-            user_account.owner,
-            ctx.accounts.user.key(),
+            ctx.accounts.target_position.owner,
+            owner,
             ArenaError::Unauthorized
         );
-
-        require!(
-            user_account.active_boosts.len() < MAX_ACTIVE_BOOSTS,
-            ArenaError::TooManyActiveBoosts
+        require_keys_eq!(
+            ctx.accounts.source_position.owner,
+            owner,
+            ArenaError::Unauthorized
+        );
+        require_keys_eq!(
+            ctx.accounts.user_account.owner,
+            owner,
+            ArenaError::Unauthorized
         );
         require!(
-            user_account.boost_points >= ctx.accounts.boost_config.cost_boost_points,
-            ArenaError::InsufficientBoostPoints
+            !ctx.accounts.target_position.is_unbonding()
+                && !ctx.accounts.source_position.is_unbonding(),
+            ArenaError::AlreadyUnbonding
         );
 
-        user_account.boost_points = user_account
-            .boost_points
-            .saturating_sub(ctx.accounts.boost_config.cost_boost_points);
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        let claimed = harvest_position(
+            &ctx.accounts.staking_pool,
+            &mut ctx.accounts.target_position,
+        )?
+        .checked_add(harvest_position(
+            &ctx.accounts.staking_pool,
+            &mut ctx.accounts.source_position,
+        )?)
+        .ok_or(ArenaError::MathOverflow)?;
+        pay_staker_rewards(
+            &mut ctx.accounts.staking_pool,
+            claimed,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
 
-        let expires_at = if ctx.accounts.boost_config.duration_seconds > 0 {
-            clock
-                .unix_timestamp
-                .saturating_add(ctx.accounts.boost_config.duration_seconds)
-        } else {
-            0
-        };
+        let target = &ctx.accounts.target_position;
+        let source = &ctx.accounts.source_position;
+        let amount = target
+            .amount_staked
+            .checked_add(source.amount_staked)
+            .ok_or(ArenaError::MathOverflow)?;
+        let lock_until_ts = target.lock_until_ts.max(source.lock_until_ts);
+        let lock_multiplier_bps = merged_lock_multiplier(
+            target.amount_staked,
+            target.lock_multiplier_bps,
+            source.amount_staked,
+            source.lock_multiplier_bps,
+        )?;
 
-        user_account.active_boosts.push(UserBoost {
-            boost_id: ctx.accounts.boost_config.id,
-            kind: ctx.accounts.boost_config.kind.clone(),
-            value_bps: ctx.accounts.boost_config.value_bps,
-            expires_at_ts: expires_at,
-            rig_id: ctx.accounts.boost_config.rig_id,
-            applied_to_mining: matches!(
-                ctx.accounts.boost_config.kind,
-                BoostKind::MiningRewardBps | BoostKind::MiningPointsBps | BoostKind::FreeRigTicket
-            ),
-            applied_to_staking: matches!(
-                ctx.accounts.boost_config.kind,
-                BoostKind::StakingMultiplierBps
-            ),
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.purge_expired(clock.unix_timestamp);
+        let boost_multiplier_bps = resolve_staking_multiplier(
+            &ctx.accounts.global_config,
+            user_account,
+            clock.unix_timestamp,
+        );
+        let effective = calculate_effective(amount, lock_multiplier_bps, boost_multiplier_bps)?;
+        let added = effective.saturating_sub(
+            ctx.accounts
+                .target_position
+                .target_stake()
+                .saturating_add(ctx.accounts.source_position.target_stake()),
+        );
+        enforce_stake_caps(
+            &ctx.accounts.staking_pool,
+            user_account.target_effective_stake(),
+            amount,
+            added,
+            added,
+        )?;
+        // The merged position keeps the less advanced warmup of the two.
+        let (warmup_start_ts, warmup_end_ts) = if ctx.accounts.source_position.warmup_end_ts
+            > ctx.accounts.target_position.warmup_end_ts
+        {
+            (
+                ctx.accounts.source_position.warmup_start_ts,
+                ctx.accounts.source_position.warmup_end_ts,
+            )
+        } else {
+            (
+                ctx.accounts.target_position.warmup_start_ts,
+                ctx.accounts.target_position.warmup_end_ts,
+            )
+        };
+
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            user_account,
+            &mut ctx.accounts.source_position,
+            0,
+            0,
+            clock.slot,
+        )?;
+        ctx.accounts.source_position.amount_staked = 0;
+
+        let target = &mut ctx.accounts.target_position;
+        target.amount_staked = amount;
+        target.lock_until_ts = lock_until_ts;
+        target.lock_multiplier_bps = lock_multiplier_bps;
+        target.boost_multiplier_bps = boost_multiplier_bps;
+        target.warmup_start_ts = warmup_start_ts;
+        target.warmup_end_ts = warmup_end_ts;
+        let warmed = target.warmed_effective_stake(effective, clock.unix_timestamp);
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            user_account,
+            target,
+            warmed,
+            effective,
+            clock.slot,
+        )?;
+
+        // The target gains an entry if it predates the index.
+        reserve_position_entries(
+            &ctx.accounts.user_positions,
+            1,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let user_positions = &mut ctx.accounts.user_positions;
+        user_positions.init_owner(owner)?;
+        user_positions.remove(&ctx.accounts.source_position.key());
+        user_positions.upsert(
+            ctx.accounts.target_position.key(),
+            &ctx.accounts.target_position,
+        )?;
+
+        emit!(PositionsMergedEvent {
+            owner,
+            target_position_id: target_id,
+            source_position_id: source_id,
+            amount,
+            lock_until_ts,
+            effective,
+            rewards_claimed: claimed,
         });
+        Ok(())
+    }
 
-        emit!(BoostActivatedEvent {
-            user: ctx.accounts.user.key(),
-            boost_id: ctx.accounts.boost_config.id,
-            expires_at,
+    /// Carves `amount` out of an existing position into a new position with
+    /// the same unlock time and multipliers.
+    pub fn split_position(ctx: Context<SplitPosition>, amount: u64) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let owner = ctx.accounts.owner.key();
+        let source_id = assert_valid_user_stake_pda(
+            ctx.program_id,
+            &ctx.accounts.source_position.seed_owner(),
+            Some(ctx.accounts.source_position.position_id),
+            &ctx.accounts.source_position.key(),
+        )?;
+        require_keys_eq!(
+            ctx.accounts.source_position.owner,
+            owner,
+            ArenaError::Unauthorized
+        );
+        require_keys_eq!(
+            ctx.accounts.user_account.owner,
+            owner,
+            ArenaError::Unauthorized
+        );
+        require!(
+            !ctx.accounts.source_position.is_unbonding(),
+            ArenaError::AlreadyUnbonding
+        );
+        require!(
+            amount > 0 && amount < ctx.accounts.source_position.amount_staked,
+            ArenaError::InvalidSplitAmount
+        );
+
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        let claimed = settle_rewards(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.source_position,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
+
+        let user_account = &mut ctx.accounts.user_account;
+        let new_position_id = user_account.next_position_id.max(1);
+        user_account.next_position_id = new_position_id.saturating_add(1);
+
+        let source = &mut ctx.accounts.source_position;
+        let remaining = source.amount_staked - amount;
+        let source_effective = calculate_effective(
+            remaining,
+            source.lock_multiplier_bps,
+            source.boost_multiplier_bps,
+        )?;
+        let new_effective = calculate_effective(
+            amount,
+            source.lock_multiplier_bps,
+            source.boost_multiplier_bps,
+        )?;
+        source.amount_staked = remaining;
+        let source_warmed = source.warmed_effective_stake(source_effective, clock.unix_timestamp);
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            user_account,
+            source,
+            source_warmed,
+            source_effective,
+            clock.slot,
+        )?;
+
+        let new_position = &mut ctx.accounts.new_position;
+        new_position.owner = owner;
+        new_position.seed_owner = owner;
+        new_position.position_id = new_position_id;
+        new_position.amount_staked = amount;
+        new_position.lock_multiplier_bps = ctx.accounts.source_position.lock_multiplier_bps;
+        new_position.boost_multiplier_bps = ctx.accounts.source_position.boost_multiplier_bps;
+        new_position.lock_until_ts = ctx.accounts.source_position.lock_until_ts;
+        new_position.warmup_start_ts = ctx.accounts.source_position.warmup_start_ts;
+        new_position.warmup_end_ts = ctx.accounts.source_position.warmup_end_ts;
+        let new_warmed = new_position.warmed_effective_stake(new_effective, clock.unix_timestamp);
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            user_account,
+            new_position,
+            new_warmed,
+            new_effective,
+            clock.slot,
+        )?;
+
+        // Room for the new position, plus the source if it predates the index.
+        reserve_position_entries(
+            &ctx.accounts.user_positions,
+            2,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let user_positions = &mut ctx.accounts.user_positions;
+        user_positions.init_owner(owner)?;
+        user_positions.upsert(
+            ctx.accounts.source_position.key(),
+            &ctx.accounts.source_position,
+        )?;
+        user_positions.upsert(ctx.accounts.new_position.key(), &ctx.accounts.new_position)?;
+
+        emit!(PositionSplitEvent {
+            owner,
+            source_position_id: source_id,
+            new_position_id,
+            amount,
+            rewards_claimed: claimed,
         });
         Ok(())
     }
-}
 
-// -------------------------------------
-// Accounts
-// -------------------------------------
+    pub fn distribute_pending_rewards(ctx: Context<DistributePendingRewards>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        update_reward_stream(staking_pool, now)?;
+        require!(
+            staking_pool.total_effective_stake > 0,
+            ArenaError::NoEffectiveStake
+        );
+        fold_pending_undistributed(staking_pool, now)?;
+        Ok(())
+    }
 
-#[account]
-pub struct GlobalConfig {
-    pub admin: Pubkey,
-    pub game_mint: Pubkey,
-    pub xnt_mint: Pubkey,
-    pub treasury_xnt_vault: Pubkey,
-    pub halving_interval: u64,
-    pub halving_level: u8,
-    pub total_minted: u64,
-    pub staking_share_bps: u16,
-}
+    pub fn transfer_position(ctx: Context<TransferPosition>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        upgrade_user_account(
+            &ctx.accounts.new_owner_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let position_id = assert_valid_user_stake_pda(
+            ctx.program_id,
+            &ctx.accounts.user_stake_position.seed_owner(),
+            Some(ctx.accounts.user_stake_position.position_id),
+            &ctx.accounts.user_stake_position.key(),
+        )?;
+        require_keys_eq!(
+            ctx.accounts.user_stake_position.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
+        let new_owner = ctx.accounts.new_owner.key();
+        require!(
+            new_owner != Pubkey::default() && new_owner != ctx.accounts.owner.key(),
+            ArenaError::InvalidNewOwner
+        );
 
-impl GlobalConfig {
-    pub const LEN: usize = 32 * 4 + 8 + 1 + 8 + 2 + 8;
-}
+        // Everything earned so far belongs to the previous owner.
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        let settled = settle_rewards(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_stake_position,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
 
-#[account]
-pub struct StakingPool {
-    pub token_mint: Pubkey,
-    pub xnt_mint: Pubkey,
-    pub staking_vault: Pubkey,
-    pub treasury_xnt_vault: Pubkey,
-    pub total_effective_stake: u128,
-    pub acc_reward_per_share: u128,
+        require_keys_eq!(
+            ctx.accounts.user_account.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
+        let new_owner_account = &mut ctx.accounts.new_owner_account;
+        if new_owner_account.owner == Pubkey::default() {
+            new_owner_account.owner = new_owner;
+        }
+        require_keys_eq!(new_owner_account.owner, new_owner, ArenaError::Unauthorized);
+
+        sync_warmup(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_account,
+            &mut ctx.accounts.user_stake_position,
+            &clock,
+        )?;
+        let stake_position = &mut ctx.accounts.user_stake_position;
+        let effective = stake_position.effective_stake;
+        let warming = stake_position.warming_stake;
+        enforce_stake_caps(
+            &ctx.accounts.staking_pool,
+            new_owner_account.target_effective_stake(),
+            stake_position.amount_staked,
+            stake_position.target_stake(),
+            0,
+        )?;
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.record_effective_stake_change(effective, 0, clock.slot)?;
+        user_account.record_warming_stake_change(warming, 0)?;
+        new_owner_account.record_effective_stake_change(0, effective, clock.slot)?;
+        new_owner_account.record_warming_stake_change(0, warming)?;
+        // Legacy positions leave `seed_owner` unset and are seeded by
+        // `owner`; pin it before the owner changes.
+        if stake_position.seed_owner == Pubkey::default() {
+            stake_position.seed_owner = stake_position.owner;
+        }
+        stake_position.owner = new_owner;
+
+        let position_key = stake_position.key();
+        ctx.accounts
+            .user_positions
+            .init_owner(ctx.accounts.owner.key())?;
+        ctx.accounts.user_positions.remove(&position_key);
+        reserve_position_entries(
+            &ctx.accounts.new_owner_positions,
+            1,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        ctx.accounts.new_owner_positions.init_owner(new_owner)?;
+        ctx.accounts
+            .new_owner_positions
+            .upsert(position_key, &ctx.accounts.user_stake_position)?;
+
+        emit!(PositionTransferredEvent {
+            from: ctx.accounts.owner.key(),
+            to: new_owner,
+            position_id,
+            rewards_settled: settled,
+        });
+        Ok(())
+    }
+
+    pub fn migrate_stake_position(ctx: Context<MigrateStakePosition>) -> Result<()> {
+        grow_account(
+            &ctx.accounts.user_stake_position.to_account_info(),
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &UserStakePosition::DISCRIMINATOR,
+            8 + UserStakePosition::LEN,
+        )
+    }
+
+    /// Rewrites a user account in the current layout ahead of time. Every
+    /// handler that writes the account does the same on first use, so this
+    /// is only needed for clients that decode the raw account.
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>) -> Result<()> {
+        let info = ctx.accounts.user_account.to_account_info();
+        let user_account = {
+            let data = info.try_borrow_data()?;
+            UserAccount::try_deserialize(&mut &data[..])?
+        };
+        resize_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + UserAccount::LEN,
+        )?;
+        let mut data = info.try_borrow_mut_data()?;
+        data[8..].fill(0);
+        user_account.try_serialize(&mut &mut data[..])
+    }
+
+    pub fn set_claim_delegate(ctx: Context<SetClaimDelegate>, delegate: Pubkey) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let user_account = &mut ctx.accounts.user_account;
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = ctx.accounts.owner.key();
+        }
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
+        user_account.claim_delegate = delegate;
+        emit!(ClaimDelegateSetEvent {
+            owner: ctx.accounts.owner.key(),
+            delegate,
+        });
+        Ok(())
+    }
+
+    pub fn delegated_claim(ctx: Context<KeeperPosition>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let position_id = ctx.accounts.validate()?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        let claimed = settle_rewards(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_stake_position,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.owner_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
+        sync_warmup(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_account,
+            &mut ctx.accounts.user_stake_position,
+            &clock,
+        )?;
+
+        emit!(ClaimEvent {
+            owner: ctx.accounts.owner.key(),
+            position_id,
+            rewards_claimed: claimed,
+        });
+        Ok(())
+    }
+
+    /// Moves a warming-up position (and the pool total) to its current ramp
+    /// value. Anyone may call it, so stake keeps warming up while the owner
+    /// is idle; rewards earned so far are paid to the owner first.
+    pub fn advance_warmup(ctx: Context<KeeperPosition>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let position_id = ctx.accounts.validate_position()?;
+        require!(
+            ctx.accounts.user_stake_position.is_warming_up()
+                && !ctx.accounts.user_stake_position.is_unbonding(),
+            ArenaError::NotWarmingUp
+        );
+        warmup_caps_check(
+            &ctx.accounts.staking_pool,
+            &ctx.accounts.user_account,
+            &ctx.accounts.user_stake_position,
+        )?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        let claimed = settle_rewards(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_stake_position,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.owner_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
+        sync_warmup(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_account,
+            &mut ctx.accounts.user_stake_position,
+            &clock,
+        )?;
+
+        emit!(WarmupAdvancedEvent {
+            owner: ctx.accounts.owner.key(),
+            position_id,
+            effective: ctx.accounts.user_stake_position.effective_stake,
+            rewards_claimed: claimed,
+        });
+        Ok(())
+    }
+
+    /// Re-derives the position's boost multiplier from the owner's currently
+    /// active boosts, settling rewards earned at the old rate first. The
+    /// owner or their claim delegate may refresh at any time; anyone else
+    /// only when the multiplier drops, so a staking boost stops counting
+    /// once it has expired.
+    pub fn refresh_position(ctx: Context<KeeperPosition>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let position_id = ctx.accounts.validate_position()?;
+        require!(
+            !ctx.accounts.user_stake_position.is_unbonding(),
+            ArenaError::AlreadyUnbonding
+        );
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.purge_expired(clock.unix_timestamp);
+        let boost_multiplier_bps = resolve_staking_multiplier(
+            &ctx.accounts.global_config,
+            user_account,
+            clock.unix_timestamp,
+        );
+        require!(
+            may_refresh(
+                user_account,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.user_stake_position.boost_multiplier_bps,
+                boost_multiplier_bps,
+            ),
+            ArenaError::Unauthorized
+        );
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        let claimed = settle_rewards(
+            &mut ctx.accounts.staking_pool,
+            &mut ctx.accounts.user_stake_position,
+            &ctx.accounts.global_config,
+            &ctx.accounts.treasury_xnt_vault,
+            &ctx.accounts.owner_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.global_config,
+        )?;
+
+        let user_account = &mut ctx.accounts.user_account;
+        let position = &mut ctx.accounts.user_stake_position;
+        let full_effective = calculate_effective(
+            position.amount_staked,
+            position.lock_multiplier_bps,
+            boost_multiplier_bps,
+        )?;
+        let previous_target = position.target_stake();
+        if full_effective > previous_target {
+            let added = full_effective - previous_target;
+            enforce_stake_caps(
+                &ctx.accounts.staking_pool,
+                user_account.target_effective_stake(),
+                position.amount_staked,
+                added,
+                added,
+            )?;
+        }
+        let effective = position.warmed_effective_stake(full_effective, clock.unix_timestamp);
+        position.boost_multiplier_bps = boost_multiplier_bps;
+        set_effective_stake(
+            &mut ctx.accounts.staking_pool,
+            user_account,
+            position,
+            effective,
+            full_effective,
+            clock.slot,
+        )?;
+
+        emit!(PositionRefreshedEvent {
+            owner: ctx.accounts.owner.key(),
+            position_id,
+            boost_multiplier_bps,
+            effective,
+            rewards_claimed: claimed,
+        });
+        Ok(())
+    }
+
+    /// Grows a staking pool created under the original layout to the
+    /// current size; only the admin may run it. Until it runs the pool
+    /// cannot be loaded; the new fields start zeroed, which keeps rewards
+    /// crediting immediately (no stream), no unbonding, no warmup and no
+    /// caps.
+    ///
+    /// Pool history is seeded with the current total so governance starts
+    /// from the real stake, and the reward reserve starts from
+    /// `unclaimed_rewards` (the stakers' pending rewards, summed off-chain).
+    /// Pools already in the current layout are left as they are.
+    pub fn migrate_staking_pool(
+        ctx: Context<MigrateStakingPool>,
+        unclaimed_rewards: u64,
+    ) -> Result<()> {
+        let info = ctx.accounts.staking_pool.to_account_info();
+        let converted = if info.data_len() < 8 + StakingPool::LEN {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == StakingPool::DISCRIMINATOR[..],
+                ArenaError::InvalidAccountData
+            );
+            require_keys_eq!(
+                ctx.accounts.payer.key(),
+                ctx.accounts.global_config.admin,
+                ArenaError::Unauthorized
+            );
+            let mut pool: StakingPool = decode_zero_padded(&data[8..], StakingPool::LEN)?;
+            pool.checkpoints
+                .record(Clock::get()?.slot, pool.total_effective_stake);
+            seed_reward_reserve(&mut pool, unclaimed_rewards)?;
+            Some(pool)
+        } else {
+            None
+        };
+        grow_account(
+            &info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &StakingPool::DISCRIMINATOR,
+            8 + StakingPool::LEN,
+        )?;
+        if let Some(pool) = converted {
+            let mut data = info.try_borrow_mut_data()?;
+            data.fill(0);
+            pool.try_serialize(&mut &mut data[..])?;
+        }
+        Ok(())
+    }
+
+    /// Recomputes the owner's total effective stake (and records a
+    /// checkpoint) from their positions, passed as remaining accounts. Users
+    /// who staked before checkpoints existed start from zero until this
+    /// runs. Every position in the owner's `UserPositions` index must be
+    /// passed; since positions opened before the index cannot be checked
+    /// that way, the totals are only ever raised, never lowered.
+    pub fn sync_user_stake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SyncUserStake<'info>>,
+    ) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let owner = ctx.accounts.owner.key();
+        let user_account = &mut ctx.accounts.user_account;
+        require_keys_eq!(user_account.owner, owner, ArenaError::Unauthorized);
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut total: u128 = 0;
+        let mut warming: u128 = 0;
+        for account_info in ctx.remaining_accounts.iter() {
+            require!(
+                !seen.contains(account_info.key),
+                ArenaError::DuplicatePosition
+            );
+            seen.push(*account_info.key);
+
+            let position: Account<'info, UserStakePosition> = Account::try_from(account_info)?;
+            assert_valid_user_stake_pda(
+                ctx.program_id,
+                &position.seed_owner(),
+                Some(position.position_id),
+                account_info.key,
+            )?;
+            require_keys_eq!(position.owner, owner, ArenaError::Unauthorized);
+            total = total
+                .checked_add(position.effective_stake)
+                .ok_or(ArenaError::MathOverflow)?;
+            warming = warming
+                .checked_add(position.warming_stake)
+                .ok_or(ArenaError::MathOverflow)?;
+        }
+
+        let index_info = ctx.accounts.user_positions.to_account_info();
+        if index_info.owner == &crate::ID {
+            let index = UserPositions::try_deserialize(&mut &index_info.try_borrow_data()?[..])?;
+            index.require_all_listed(&seen)?;
+        }
+
+        user_account.raise_stake_totals(total, warming, Clock::get()?.slot)?;
+        emit!(UserStakeSyncedEvent {
+            owner,
+            positions: seen.len() as u16,
+            total_effective_stake: user_account.total_effective_stake,
+        });
+        Ok(())
+    }
+
+    /// Adds open positions to the owner's `UserPositions` index, for
+    /// positions opened before the index existed. Entries already listed are
+    /// refreshed.
+    pub fn index_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, IndexPositions<'info>>,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        ctx.accounts.user_positions.init_owner(owner)?;
+        reserve_position_entries(
+            &ctx.accounts.user_positions,
+            ctx.remaining_accounts.len(),
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for account_info in ctx.remaining_accounts.iter() {
+            require!(
+                !seen.contains(account_info.key),
+                ArenaError::DuplicatePosition
+            );
+            seen.push(*account_info.key);
+
+            let position: Account<'info, UserStakePosition> = Account::try_from(account_info)?;
+            assert_valid_user_stake_pda(
+                ctx.program_id,
+                &position.seed_owner(),
+                Some(position.position_id),
+                account_info.key,
+            )?;
+            require_keys_eq!(position.owner, owner, ArenaError::Unauthorized);
+            ctx.accounts
+                .user_positions
+                .upsert(*account_info.key, &position)?;
+        }
+
+        emit!(PositionsIndexedEvent {
+            owner,
+            positions: seen.len() as u16,
+        });
+        Ok(())
+    }
+
+    pub fn check_treasury_solvency(
+        ctx: Context<CheckTreasurySolvency>,
+    ) -> Result<TreasurySolvency> {
+        let staking_pool = &ctx.accounts.staking_pool;
+        let balance = ctx.accounts.treasury_xnt_vault.amount;
+        let reserved = staking_pool.reserved_rewards();
+        let report = TreasurySolvency {
+            balance,
+            reserved,
+            unreserved: unreserved_treasury_balance(staking_pool, balance),
+            deficit: reserved.saturating_sub(balance as u128),
+        };
+        emit!(TreasurySolvencyEvent {
+            balance: report.balance,
+            reserved: report.reserved,
+            unreserved: report.unreserved,
+            deficit: report.deficit,
+        });
+        Ok(report)
+    }
+
+    pub fn configure_treasury_policy(
+        ctx: Context<ConfigureTreasuryPolicy>,
+        args: TreasuryPolicyArgs,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        args.validate()?;
+        let policy = &mut ctx.accounts.treasury_policy;
+        require!(
+            !policy.is_configured() || policy.is_tightened_by(&args),
+            ArenaError::PolicyChangeNeedsQueue
+        );
+        policy.apply(&args);
+        Ok(())
+    }
+
+    /// Queues a policy change that loosens the current policy (a larger
+    /// budget, a shorter period or a shorter timelock). It can be applied
+    /// once the current timelock has passed.
+    pub fn queue_treasury_policy(
+        ctx: Context<QueueTreasuryPolicy>,
+        args: TreasuryPolicyArgs,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        args.validate()?;
+        let now = Clock::get()?.unix_timestamp;
+        let change = &mut ctx.accounts.policy_change;
+        change.budget_per_period = args.budget_per_period;
+        change.period_secs = args.period_secs;
+        change.timelock_secs = args.timelock_secs;
+        change.queued_at_ts = now;
+        change.executable_at_ts = now.saturating_add(
+            ctx.accounts
+                .treasury_policy
+                .timelock_secs
+                .max(MIN_TREASURY_TIMELOCK_SECS),
+        );
+
+        emit!(TreasuryPolicyQueuedEvent {
+            budget_per_period: args.budget_per_period,
+            period_secs: args.period_secs,
+            timelock_secs: args.timelock_secs,
+            executable_at_ts: change.executable_at_ts,
+        });
+        Ok(())
+    }
+
+    pub fn apply_treasury_policy(ctx: Context<ApplyTreasuryPolicy>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let change = &ctx.accounts.policy_change;
+        require!(
+            Clock::get()?.unix_timestamp >= change.executable_at_ts,
+            ArenaError::TimelockActive
+        );
+        let args = TreasuryPolicyArgs {
+            budget_per_period: change.budget_per_period,
+            period_secs: change.period_secs,
+            timelock_secs: change.timelock_secs,
+        };
+        ctx.accounts.treasury_policy.apply(&args);
+
+        emit!(TreasuryPolicyAppliedEvent {
+            budget_per_period: args.budget_per_period,
+            period_secs: args.period_secs,
+            timelock_secs: args.timelock_secs,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_treasury_policy(ctx: Context<CancelTreasuryPolicy>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        emit!(TreasuryPolicyCancelledEvent {
+            budget_per_period: ctx.accounts.policy_change.budget_per_period,
+        });
+        Ok(())
+    }
+
+    pub fn queue_treasury_withdrawal(
+        ctx: Context<QueueTreasuryWithdrawal>,
+        amount: u64,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let policy = &mut ctx.accounts.treasury_policy;
+        require!(
+            amount > 0 && amount <= policy.budget_per_period,
+            ArenaError::TreasuryBudgetExceeded
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let withdrawal = &mut ctx.accounts.treasury_withdrawal;
+        withdrawal.id = policy.next_withdrawal_id;
+        withdrawal.amount = amount;
+        withdrawal.destination = ctx.accounts.destination.key();
+        withdrawal.queued_at_ts = now;
+        withdrawal.executable_at_ts = now.saturating_add(policy.timelock_secs);
+        policy.next_withdrawal_id = policy.next_withdrawal_id.saturating_add(1);
+
+        emit!(TreasuryWithdrawalQueuedEvent {
+            id: withdrawal.id,
+            amount,
+            destination: withdrawal.destination,
+            executable_at_ts: withdrawal.executable_at_ts,
+        });
+        Ok(())
+    }
+
+    pub fn execute_treasury_withdrawal(ctx: Context<ExecuteTreasuryWithdrawal>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let withdrawal = &ctx.accounts.treasury_withdrawal;
+        require!(
+            now >= withdrawal.executable_at_ts,
+            ArenaError::TimelockActive
+        );
+
+        let policy = &mut ctx.accounts.treasury_policy;
+        if now >= policy.period_start_ts.saturating_add(policy.period_secs) {
+            policy.period_start_ts = now;
+            policy.spent_in_period = 0;
+        }
+        let spent = policy
+            .spent_in_period
+            .checked_add(withdrawal.amount)
+            .ok_or(ArenaError::MathOverflow)?;
+        require!(
+            spent <= policy.budget_per_period,
+            ArenaError::TreasuryBudgetExceeded
+        );
+        require!(
+            withdrawal.amount
+                <= unreserved_treasury_balance(
+                    &ctx.accounts.staking_pool,
+                    ctx.accounts.treasury_xnt_vault.amount
+                ),
+            ArenaError::TreasuryFundsReserved
+        );
+        policy.spent_in_period = spent;
+
+        let signer_seeds: &[&[u8]] = &[GLOBAL_CONFIG_SEED, &[ctx.bumps.global_config]];
+        let signer_arr: &[&[&[u8]]] = &[signer_seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.treasury_xnt_vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.global_config.to_account_info(),
+            },
+            signer_arr,
+        );
+        token::transfer(cpi_ctx, withdrawal.amount)?;
+
+        emit!(TreasuryWithdrawalExecutedEvent {
+            id: withdrawal.id,
+            amount: withdrawal.amount,
+            destination: withdrawal.destination,
+            spent_in_period: spent,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_treasury_withdrawal(ctx: Context<CancelTreasuryWithdrawal>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        emit!(TreasuryWithdrawalCancelledEvent {
+            id: ctx.accounts.treasury_withdrawal.id,
+            amount: ctx.accounts.treasury_withdrawal.amount,
+        });
+        Ok(())
+    }
+
+    /// Returns (via return data) the user's and the pool's effective stake
+    /// at the start of the checkpoint interval containing `slot`, for
+    /// governance snapshots. `slot` must fall within the last
+    /// `SNAPSHOT_WINDOW_INTERVALS` intervals.
+    pub fn get_voting_power(ctx: Context<GetVotingPower>, slot: u64) -> Result<VotingPower> {
+        require_snapshot_in_window(slot, Clock::get()?.slot)?;
+        let user_power = ctx.accounts.user_account.checkpoints.value_at(slot)?;
+        let total_power = ctx.accounts.staking_pool.checkpoints.value_at(slot)?;
+        Ok(VotingPower {
+            user: ctx.accounts.user.key(),
+            slot,
+            user_power,
+            total_power,
+        })
+    }
+
+    /// Creates the liquid staking vault with its sGAME share mint and XNT
+    /// reward account.
+    pub fn initialize_liquid_vault(ctx: Context<InitializeLiquidVault>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let liquid_vault = &mut ctx.accounts.liquid_vault;
+        liquid_vault.share_mint = ctx.accounts.share_mint.key();
+        liquid_vault.xnt_vault = ctx.accounts.vault_xnt_account.key();
+        Ok(())
+    }
+
+    /// Deposits GAME into the liquid vault for sGAME at the current GAME per
+    /// share. Shares already carry harvested XNT, so the depositor adds the
+    /// matching pro-rata XNT (at most `max_xnt_in`). Only while the pool has
+    /// no warmup period; see `begin_liquid_deposit` otherwise. The deposit
+    /// counts against the stake caps along with the depositor's positions.
+    /// The first deposit locks `MINIMUM_LIQUID_SHARES`.
+    pub fn deposit_liquid(
+        ctx: Context<DepositLiquid>,
+        amount: u64,
+        min_shares_out: u64,
+        max_xnt_in: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ArenaError::InvalidAmount);
+        require!(
+            ctx.accounts.staking_pool.warmup_period_secs == 0,
+            ArenaError::WarmupRequired
+        );
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let user_account = &mut ctx.accounts.user_account;
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = ctx.accounts.owner.key();
+        }
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
+        let clock = Clock::get()?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        ctx.accounts.harvest_vault(ctx.bumps.global_config)?;
+
+        let supply = ctx.accounts.share_mint.supply;
+        let liquid_vault = &ctx.accounts.liquid_vault;
+        let (shares, locked) = liquid_vault.shares_for_deposit(amount, supply)?;
+        require!(shares > 0, ArenaError::InvalidAmount);
+        require!(shares >= min_shares_out, ArenaError::SlippageExceeded);
+        let xnt_in = liquid_vault.xnt_for_shares(shares, supply, true)?;
+        require!(xnt_in <= max_xnt_in, ArenaError::SlippageExceeded);
+
+        // Caps see the depositor's positions and whole sGAME balance.
+        let holder_shares = ctx
+            .accounts
+            .user_share_account
+            .amount
+            .checked_add(shares)
+            .ok_or(ArenaError::MathOverflow)?;
+        let holder_game = mul_div(
+            holder_shares,
+            liquid_vault
+                .total_game
+                .checked_add(amount)
+                .ok_or(ArenaError::MathOverflow)?,
+            liquid_vault.total_shares(supply) + shares + locked,
+            false,
+        )?;
+        liquid_deposit_caps_check(
+            &ctx.accounts.staking_pool,
+            &ctx.accounts.user_account,
+            amount,
+            holder_game,
+        )?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_game_account.to_account_info(),
+                    to: ctx.accounts.staking_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        pay_liquid_xnt_in(
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.vault_xnt_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+            xnt_in,
+        )?;
+        let vault_seeds: &[&[u8]] = &[LIQUID_VAULT_SEED, &[ctx.bumps.liquid_vault]];
+        let vault_signer: &[&[&[u8]]] = &[vault_seeds];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: ctx.accounts.liquid_vault.to_account_info(),
+                },
+                vault_signer,
+            ),
+            shares,
+        )?;
+
+        let liquid_vault = &mut ctx.accounts.liquid_vault;
+        liquid_vault.locked_shares = liquid_vault
+            .locked_shares
+            .checked_add(locked)
+            .ok_or(ArenaError::MathOverflow)?;
+        liquid_vault.total_game = liquid_vault
+            .total_game
+            .checked_add(amount)
+            .ok_or(ArenaError::MathOverflow)?;
+        liquid_vault.add_xnt(xnt_in)?;
+        set_liquid_vault_stake(&mut ctx.accounts.staking_pool, liquid_vault, clock.slot)?;
+        fold_pending_undistributed(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+
+        emit!(LiquidDepositEvent {
+            owner: ctx.accounts.owner.key(),
+            game_amount: amount,
+            xnt_amount: xnt_in,
+            shares,
+        });
+        Ok(())
+    }
+
+    /// Deposits GAME into a warming `LiquidDeposit` ticket while the pool has
+    /// a warmup period. The GAME counts against the stake caps straight away
+    /// but earns nothing until `claim_liquid_deposit` mints its sGAME, once
+    /// the pool's warmup period has passed. Each ticket warms up on its own,
+    /// so later deposits never delay earlier ones. `ticket_id` is any id
+    /// unused by the owner.
+    pub fn begin_liquid_deposit(
+        ctx: Context<BeginLiquidDeposit>,
+        ticket_id: u32,
+        amount: u64,
+    ) -> Result<()> {
+        // Small enough deposits could not mint a share when claimed.
+        require!(amount > MINIMUM_LIQUID_SHARES, ArenaError::InvalidAmount);
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+        )?;
+        let user_account = &mut ctx.accounts.user_account;
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = ctx.accounts.owner.key();
+        }
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.owner.key(),
+            ArenaError::Unauthorized
+        );
+        let clock = Clock::get()?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        ctx.accounts.harvest_vault(ctx.bumps.global_config)?;
+
+        // Caps see the depositor's positions, sGAME and the deposit.
+        let holder_shares = ctx.accounts.user_share_account.amount;
+        let holder_game = if holder_shares == 0 {
+            0
+        } else {
+            ctx.accounts
+                .liquid_vault
+                .game_for_shares(holder_shares, ctx.accounts.share_mint.supply)?
+        }
+        .checked_add(amount)
+        .ok_or(ArenaError::MathOverflow)?;
+        liquid_deposit_caps_check(
+            &ctx.accounts.staking_pool,
+            &ctx.accounts.user_account,
+            amount,
+            holder_game,
+        )?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_game_account.to_account_info(),
+                    to: ctx.accounts.staking_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let liquid_vault = &mut ctx.accounts.liquid_vault;
+        liquid_vault.add_pending_game(amount)?;
+        set_liquid_vault_stake(&mut ctx.accounts.staking_pool, liquid_vault, clock.slot)?;
+
+        let warm_at_ts = clock
+            .unix_timestamp
+            .saturating_add(ctx.accounts.staking_pool.warmup_period_secs);
+        let ticket = &mut ctx.accounts.deposit_ticket;
+        ticket.owner = ctx.accounts.owner.key();
+        ticket.game_amount = amount;
+        ticket.warm_at_ts = warm_at_ts;
+
+        emit!(LiquidDepositStartedEvent {
+            owner: ctx.accounts.owner.key(),
+            ticket_id,
+            game_amount: amount,
+            warm_at_ts,
+        });
+        Ok(())
+    }
+
+    /// Mints the sGAME for a warmed-up `LiquidDeposit` at the current GAME
+    /// per share, taking the matching pro-rata XNT like `deposit_liquid`, and
+    /// closes the ticket. Rewards earned while it warmed up went to the
+    /// existing holders.
+    pub fn claim_liquid_deposit(
+        ctx: Context<ClaimLiquidDeposit>,
+        ticket_id: u32,
+        min_shares_out: u64,
+        max_xnt_in: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.deposit_ticket.warm_at_ts <= clock.unix_timestamp,
+            ArenaError::WarmupInProgress
+        );
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        ctx.accounts.harvest_vault(ctx.bumps.global_config)?;
+
+        let amount = ctx.accounts.deposit_ticket.game_amount;
+        let supply = ctx.accounts.share_mint.supply;
+        let liquid_vault = &ctx.accounts.liquid_vault;
+        let (shares, locked) = liquid_vault.shares_for_deposit(amount, supply)?;
+        require!(shares > 0, ArenaError::InvalidAmount);
+        require!(shares >= min_shares_out, ArenaError::SlippageExceeded);
+        let xnt_in = liquid_vault.xnt_for_shares(shares, supply, true)?;
+        require!(xnt_in <= max_xnt_in, ArenaError::SlippageExceeded);
+
+        pay_liquid_xnt_in(
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.vault_xnt_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+            xnt_in,
+        )?;
+        let vault_seeds: &[&[u8]] = &[LIQUID_VAULT_SEED, &[ctx.bumps.liquid_vault]];
+        let vault_signer: &[&[&[u8]]] = &[vault_seeds];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.user_share_account.to_account_info(),
+                    authority: ctx.accounts.liquid_vault.to_account_info(),
+                },
+                vault_signer,
+            ),
+            shares,
+        )?;
+
+        let liquid_vault = &mut ctx.accounts.liquid_vault;
+        liquid_vault.locked_shares = liquid_vault
+            .locked_shares
+            .checked_add(locked)
+            .ok_or(ArenaError::MathOverflow)?;
+        liquid_vault.settle_pending_game(amount)?;
+        liquid_vault.add_xnt(xnt_in)?;
+        set_liquid_vault_stake(&mut ctx.accounts.staking_pool, liquid_vault, clock.slot)?;
+        fold_pending_undistributed(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+
+        emit!(LiquidDepositClaimedEvent {
+            owner: ctx.accounts.owner.key(),
+            ticket_id,
+            game_amount: amount,
+            xnt_amount: xnt_in,
+            shares,
+        });
+        Ok(())
+    }
+
+    /// Burns sGAME for its pro-rata share of the vault's GAME and harvested
+    /// XNT. Only while the pool has no unbonding period; see
+    /// `begin_liquid_unbond` otherwise.
+    pub fn redeem_liquid(
+        ctx: Context<LiquidVaultUser>,
+        shares: u64,
+        min_game_out: u64,
+    ) -> Result<()> {
+        require!(shares > 0, ArenaError::InvalidAmount);
+        require!(
+            ctx.accounts.staking_pool.unbonding_period_secs == 0,
+            ArenaError::UnbondingRequired
+        );
+        let clock = Clock::get()?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        ctx.accounts.harvest_vault(ctx.bumps.global_config)?;
+
+        let supply = ctx.accounts.share_mint.supply;
+        let game_out = ctx.accounts.liquid_vault.game_for_shares(shares, supply)?;
+        require!(game_out >= min_game_out, ArenaError::SlippageExceeded);
+        let xnt_out = ctx
+            .accounts
+            .liquid_vault
+            .xnt_for_shares(shares, supply, false)?;
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.user_share_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+        let stake_seeds: &[&[u8]] = &[STAKING_POOL_SEED, &[ctx.bumps.staking_pool]];
+        let stake_signer: &[&[&[u8]]] = &[stake_seeds];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.staking_vault.to_account_info(),
+                    to: ctx.accounts.user_game_account.to_account_info(),
+                    authority: ctx.accounts.staking_pool.to_account_info(),
+                },
+                stake_signer,
+            ),
+            game_out,
+        )?;
+        pay_liquid_xnt_out(
+            &ctx.accounts.liquid_vault,
+            &ctx.accounts.vault_xnt_account,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.liquid_vault,
+            xnt_out,
+        )?;
+
+        let liquid_vault = &mut ctx.accounts.liquid_vault;
+        liquid_vault.remove_game(game_out);
+        liquid_vault.remove_xnt(xnt_out);
+        set_liquid_vault_stake(&mut ctx.accounts.staking_pool, liquid_vault, clock.slot)?;
+
+        emit!(LiquidRedeemEvent {
+            owner: ctx.accounts.owner.key(),
+            shares,
+            game_amount: game_out,
+            xnt_amount: xnt_out,
+        });
+        Ok(())
+    }
+
+    /// Burns sGAME and starts the pool's unbonding period for its GAME, which
+    /// stops earning immediately and is withdrawn with
+    /// `withdraw_liquid_unbonded`. Its harvested XNT is paid out straight
+    /// away. `ticket_id` is any id unused by the owner.
+    pub fn begin_liquid_unbond(
+        ctx: Context<BeginLiquidUnbond>,
+        ticket_id: u32,
+        shares: u64,
+        min_game_out: u64,
+    ) -> Result<()> {
+        require!(shares > 0, ArenaError::InvalidAmount);
+        let clock = Clock::get()?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        ctx.accounts.harvest_vault(ctx.bumps.global_config)?;
+
+        let supply = ctx.accounts.share_mint.supply;
+        let game_out = ctx.accounts.liquid_vault.game_for_shares(shares, supply)?;
+        require!(game_out >= min_game_out, ArenaError::SlippageExceeded);
+        let xnt_out = ctx
+            .accounts
+            .liquid_vault
+            .xnt_for_shares(shares, supply, false)?;
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    from: ctx.accounts.user_share_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+        pay_liquid_xnt_out(
+            &ctx.accounts.liquid_vault,
+            &ctx.accounts.vault_xnt_account,
+            &ctx.accounts.user_xnt_account,
+            &ctx.accounts.token_program,
+            ctx.bumps.liquid_vault,
+            xnt_out,
+        )?;
+
+        let liquid_vault = &mut ctx.accounts.liquid_vault;
+        liquid_vault.remove_game(game_out);
+        liquid_vault.remove_xnt(xnt_out);
+        set_liquid_vault_stake(&mut ctx.accounts.staking_pool, liquid_vault, clock.slot)?;
+
+        let unbonding_until_ts = clock
+            .unix_timestamp
+            .saturating_add(ctx.accounts.staking_pool.unbonding_period_secs);
+        let ticket = &mut ctx.accounts.unbond_ticket;
+        ticket.owner = ctx.accounts.owner.key();
+        ticket.game_amount = game_out;
+        ticket.unbonding_until_ts = unbonding_until_ts;
+
+        emit!(LiquidUnbondStartedEvent {
+            owner: ctx.accounts.owner.key(),
+            ticket_id,
+            shares,
+            game_amount: game_out,
+            xnt_amount: xnt_out,
+            unbonding_until_ts,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_liquid_unbonded(
+        ctx: Context<WithdrawLiquidUnbonded>,
+        ticket_id: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let ticket = &ctx.accounts.unbond_ticket;
+        require!(
+            ticket.unbonding_until_ts <= clock.unix_timestamp,
+            ArenaError::UnbondingInProgress
+        );
+        let game_amount = ticket.game_amount;
+        let stake_seeds: &[&[u8]] = &[STAKING_POOL_SEED, &[ctx.bumps.staking_pool]];
+        let stake_signer: &[&[&[u8]]] = &[stake_seeds];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.staking_vault.to_account_info(),
+                    to: ctx.accounts.user_game_account.to_account_info(),
+                    authority: ctx.accounts.staking_pool.to_account_info(),
+                },
+                stake_signer,
+            ),
+            game_amount,
+        )?;
+
+        emit!(LiquidUnbondWithdrawnEvent {
+            owner: ctx.accounts.owner.key(),
+            ticket_id,
+            game_amount,
+        });
+        Ok(())
+    }
+
+    /// Permissionless crank moving the vault's pending XNT rewards into its
+    /// XNT account for share holders.
+    pub fn harvest_liquid_vault(ctx: Context<HarvestLiquidVault>) -> Result<()> {
+        let clock = Clock::get()?;
+        update_reward_stream(&mut ctx.accounts.staking_pool, clock.unix_timestamp)?;
+        ctx.accounts.harvest_vault(ctx.bumps.global_config)?;
+        Ok(())
+    }
+
+    pub fn apply_ranking_results(
+        ctx: Context<ApplyRankingResults>,
+        boost_points_to_add: u64,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+        let user_account = &mut ctx.accounts.user_account;
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = ctx.accounts.user.key();
+        }
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.user.key(),
+            ArenaError::Unauthorized
+        );
+        user_account.boost_points = user_account
+            .boost_points
+            .saturating_add(boost_points_to_add);
+        emit!(RankingAppliedEvent {
+            user: ctx.accounts.user.key(),
+            added_boost_points: boost_points_to_add,
+        });
+        Ok(())
+    }
+
+    /// Publishes a merkle root of ranking allocations. Leaves are
+    /// `keccak(index_le_u32 || user || boost_points_le_u64)`; inner nodes hash
+    /// the sorted pair of children. Epoch ids must increase so a closed
+    /// epoch can never be published again with a fresh claimed bitmap.
+    pub fn publish_ranking_epoch(
+        ctx: Context<PublishRankingEpoch>,
+        args: RankingEpochArgs,
+    ) -> Result<()> {
+        let global = &mut ctx.accounts.global_config;
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            global.admin,
+            ArenaError::Unauthorized
+        );
+        require!(
+            args.leaf_count > 0 && args.leaf_count <= MAX_RANKING_LEAVES,
+            ArenaError::InvalidAmount
+        );
+        require!(
+            args.epoch_id >= global.next_ranking_epoch_id,
+            ArenaError::RankingEpochTooOld
+        );
+        global.next_ranking_epoch_id = args.epoch_id.saturating_add(1);
+        let epoch = &mut ctx.accounts.ranking_epoch;
+        epoch.epoch_id = args.epoch_id;
+        epoch.merkle_root = args.merkle_root;
+        epoch.leaf_count = args.leaf_count;
+        epoch.total_points = args.total_points;
+        epoch.claimed_bitmap = vec![0; RankingEpoch::bitmap_len(args.leaf_count)];
+        emit!(RankingEpochPublishedEvent {
+            epoch_id: args.epoch_id,
+            merkle_root: args.merkle_root,
+            leaf_count: args.leaf_count,
+            total_points: args.total_points,
+        });
+        Ok(())
+    }
+
+    /// Credits the caller's boost points for one leaf of a ranking epoch.
+    pub fn claim_ranking_reward(
+        ctx: Context<ClaimRankingReward>,
+        _epoch_id: u64,
+        index: u32,
+        boost_points: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+        )?;
+        let user = ctx.accounts.user.key();
+        let epoch = &mut ctx.accounts.ranking_epoch;
+        require!(index < epoch.leaf_count, ArenaError::InvalidLeafIndex);
+        require!(!epoch.is_claimed(index), ArenaError::AlreadyClaimed);
+        let leaf = ranking_leaf(index, &user, boost_points);
+        require!(
+            verify_merkle_proof(&proof, epoch.merkle_root, leaf),
+            ArenaError::InvalidMerkleProof
+        );
+        epoch.record_claim(index, boost_points)?;
+
+        let user_account = &mut ctx.accounts.user_account;
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = user;
+        }
+        require_keys_eq!(user_account.owner, user, ArenaError::Unauthorized);
+        user_account.boost_points = user_account.boost_points.saturating_add(boost_points);
+        emit!(RankingRewardClaimedEvent {
+            epoch_id: epoch.epoch_id,
+            user,
+            index,
+            boost_points,
+        });
+        Ok(())
+    }
+
+    /// Closes a ranking epoch and returns its rent to the admin. Leaves not
+    /// claimed by then can no longer be claimed, and the epoch id can never
+    /// be published again.
+    pub fn close_ranking_epoch(ctx: Context<CloseRankingEpoch>, epoch_id: u64) -> Result<()> {
+        let global = &mut ctx.accounts.global_config;
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            global.admin,
+            ArenaError::Unauthorized
+        );
+        global.next_ranking_epoch_id = global.next_ranking_epoch_id.max(epoch_id.saturating_add(1));
+        let epoch = &ctx.accounts.ranking_epoch;
+        emit!(RankingEpochClosedEvent {
+            epoch_id,
+            unclaimed_points: epoch.total_points.saturating_sub(epoch.claimed_points),
+        });
+        Ok(())
+    }
+
+    /// Rolls a user over to the current day. Users roll over on their own
+    /// when they mine, stake or buy boosts; this covers idle accounts and
+    /// any wallet may call it.
+    pub fn reset_daily_points(ctx: Context<ResetDailyPoints>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let user_account = &mut ctx.accounts.user_account;
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.user.key(),
+            ArenaError::Unauthorized
+        );
+        user_account.roll_over_day(ctx.accounts.global_config.day_id(clock.unix_timestamp));
+        Ok(())
+    }
+
+    /// Sets the UTC offset used to derive day boundaries for daily points.
+    /// The change applies from the next day boundary; a change queued
+    /// earlier that has not applied yet is replaced.
+    pub fn set_day_offset(ctx: Context<SetDayOffset>, offset_secs: i64) -> Result<()> {
+        let global = &mut ctx.accounts.global_config;
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            global.admin,
+            ArenaError::Unauthorized
+        );
+        require!(
+            (-MAX_DAY_OFFSET_SECS..=MAX_DAY_OFFSET_SECS).contains(&offset_secs),
+            ArenaError::InvalidDuration
+        );
+        global.schedule_day_offset(offset_secs, Clock::get()?.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn initialize_leaderboard(ctx: Context<InitializeLeaderboard>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let clock = Clock::get()?;
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.day_id = ctx.accounts.global_config.day_id(clock.unix_timestamp);
+        leaderboard.previous_day_id = leaderboard.day_id.saturating_sub(1);
+        ctx.accounts.global_config.leaderboard_initialized = true;
+        Ok(())
+    }
+
+    /// Permissionless crank freezing the leaderboard when no one has mined
+    /// since the day changed.
+    pub fn finalize_leaderboard(ctx: Context<FinalizeLeaderboard>) -> Result<()> {
+        let clock = Clock::get()?;
+        let day_id = ctx.accounts.global_config.day_id(clock.unix_timestamp);
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        if leaderboard.roll_over(day_id) {
+            emit!(LeaderboardFinalizedEvent {
+                day_id: leaderboard.previous_day_id,
+                entries: leaderboard.previous.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn configure_boost_stacking(
+        ctx: Context<ConfigureBoostStacking>,
+        args: BoostStackingArgs,
+    ) -> Result<()> {
+        let global = &mut ctx.accounts.global_config;
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            global.admin,
+            ArenaError::Unauthorized
+        );
+        if let Some(modes) = args.modes {
+            global.boost_stacking = modes;
+        }
+        if let Some(max_multiplier_bps) = args.max_multiplier_bps {
+            require!(
+                max_multiplier_bps == 0 || max_multiplier_bps >= 10_000,
+                ArenaError::InvalidBps
+            );
+            global.max_boost_multiplier_bps = max_multiplier_bps;
+        }
+        Ok(())
+    }
+
+    pub fn migrate_global_config(ctx: Context<MigrateGlobalConfig>) -> Result<()> {
+        grow_account(
+            &ctx.accounts.global_config.to_account_info(),
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &GlobalConfig::DISCRIMINATOR,
+            8 + GlobalConfig::LEN,
+        )
+    }
+
+    pub fn upsert_boost_config(
+        ctx: Context<UpsertBoostConfig>,
+        args: BoostConfigArgs,
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let boost_config = &mut ctx.accounts.boost_config;
+        boost_config.id = args.id;
+        boost_config.kind = args.kind;
+        boost_config.cost_boost_points = args.cost_boost_points;
+        boost_config.value_bps = args.value_bps;
+        boost_config.duration_seconds = args.duration_seconds;
+        if let Some(rig_id) = args.rig_id {
+            require!(
+                !boost_config.kind.is_staking() && RigConfig::get(rig_id).is_some(),
+                ArenaError::InvalidRig
+            );
+        }
+        boost_config.rig_id = args.rig_id;
+        require!(
+            args.end_ts == 0 || args.end_ts > args.start_ts,
+            ArenaError::InvalidDuration
+        );
+        boost_config.start_ts = args.start_ts;
+        boost_config.end_ts = args.end_ts;
+        boost_config.max_supply = args.max_supply;
+        boost_config.per_user_limit = args.per_user_limit;
+        boost_config.enabled = args.enabled;
+        boost_config.price_game = args.price_game;
+        boost_config.price_xnt = args.price_xnt;
+        require!(
+            args.charges == 0 || !boost_config.kind.is_staking(),
+            ArenaError::InvalidBoostCharges
+        );
+        require!(
+            !args.refundable || !boost_config.kind.is_staking(),
+            ArenaError::BoostNotRefundable
+        );
+        boost_config.charges = args.charges;
+        boost_config.refundable = args.refundable;
+        Ok(())
+    }
+
+    /// Moves a boost config stored under a single-byte id to its two-byte id
+    /// PDA and closes the old account. The config comes out enabled and
+    /// unrestricted, priced in boost points only.
+    pub fn migrate_boost_config(ctx: Context<MigrateBoostConfig>, legacy_id: u8) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        let info = ctx.accounts.legacy_boost_config.to_account_info();
+        let boost_config = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == BoostConfig::DISCRIMINATOR[..],
+                ArenaError::InvalidAccountData
+            );
+            let boost_config = BoostConfig::from(LegacyBoostConfig::deserialize(&mut &data[8..])?);
+            require!(
+                boost_config.id == u16::from(legacy_id),
+                ArenaError::InvalidAccountData
+            );
+            boost_config
+        };
+        ctx.accounts.boost_config.set_inner(boost_config);
+        close_account(&info, &ctx.accounts.admin.to_account_info())
+    }
+
+    /// Closes a boost config and returns its rent to the admin. Boosts
+    /// already active keep running; inventory copies can no longer be
+    /// activated unless the id is configured again.
+    pub fn close_boost_config(ctx: Context<CloseBoostConfig>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.global_config.admin,
+            ArenaError::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn activate_boost(ctx: Context<ActivateBoost>, payment: BoostPayment) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.purge_expired(clock.unix_timestamp);
+        user_account.roll_over_day(ctx.accounts.global_config.day_id(clock.unix_timestamp));
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = ctx.accounts.user.key();
+        }
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.user.key(),
+            ArenaError::Unauthorized
+        );
+
+        record_boost_sale(
+            &mut ctx.accounts.boost_config,
+            &mut ctx.accounts.boost_receipt,
+            ctx.accounts.user.key(),
+            clock.unix_timestamp,
+        )?;
+        let price = collect_boost_payment(
+            payment,
+            &ctx.accounts.boost_config,
+            user_account,
+            &mut ctx.accounts.global_config,
+            BoostPaymentAccounts {
+                user: &ctx.accounts.user,
+                staking_pool: ctx.accounts.staking_pool.as_deref_mut(),
+                game_mint: ctx.accounts.game_mint.as_deref(),
+                user_game_account: ctx.accounts.user_game_account.as_deref(),
+                user_xnt_account: ctx.accounts.user_xnt_account.as_deref(),
+                treasury_xnt_vault: ctx.accounts.treasury_xnt_vault.as_deref(),
+                token_program: ctx.accounts.token_program.as_ref(),
+            },
+            clock.unix_timestamp,
+        )?;
+        let expires_at = push_user_boost(
+            user_account,
+            &ctx.accounts.boost_config,
+            ctx.accounts.boost_config.refundable_points(payment, price),
+            clock.unix_timestamp,
+        )?;
+
+        emit!(BoostActivatedEvent {
+            user: ctx.accounts.user.key(),
+            boost_id: ctx.accounts.boost_config.id,
+            expires_at,
+            payment,
+            price,
+            from_inventory: false,
+        });
+        Ok(())
+    }
+
+    /// Buys a boost into the user's inventory without starting it.
+    pub fn purchase_boost(ctx: Context<PurchaseBoost>, payment: BoostPayment) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.roll_over_day(ctx.accounts.global_config.day_id(clock.unix_timestamp));
+        if user_account.owner == Pubkey::default() {
+            user_account.owner = ctx.accounts.user.key();
+        }
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.user.key(),
+            ArenaError::Unauthorized
+        );
+
+        record_boost_sale(
+            &mut ctx.accounts.boost_config,
+            &mut ctx.accounts.boost_receipt,
+            ctx.accounts.user.key(),
+            clock.unix_timestamp,
+        )?;
+        let price = collect_boost_payment(
+            payment,
+            &ctx.accounts.boost_config,
+            user_account,
+            &mut ctx.accounts.global_config,
+            BoostPaymentAccounts {
+                user: &ctx.accounts.user,
+                staking_pool: ctx.accounts.staking_pool.as_deref_mut(),
+                game_mint: ctx.accounts.game_mint.as_deref(),
+                user_game_account: ctx.accounts.user_game_account.as_deref(),
+                user_xnt_account: ctx.accounts.user_xnt_account.as_deref(),
+                treasury_xnt_vault: ctx.accounts.treasury_xnt_vault.as_deref(),
+                token_program: ctx.accounts.token_program.as_ref(),
+            },
+            clock.unix_timestamp,
+        )?;
+
+        let inventory = &mut ctx.accounts.boost_inventory;
+        inventory.init_owner(ctx.accounts.user.key())?;
+        inventory.add(
+            ctx.accounts.boost_config.id,
+            1,
+            ctx.accounts.boost_config.refundable_points(payment, price),
+        )?;
+
+        emit!(BoostPurchasedEvent {
+            user: ctx.accounts.user.key(),
+            boost_id: ctx.accounts.boost_config.id,
+            payment,
+            price,
+        });
+        Ok(())
+    }
+
+    /// Starts one inventory boost using the boost's current configuration.
+    pub fn activate_from_inventory(ctx: Context<ActivateFromInventory>) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let user_account = &mut ctx.accounts.user_account;
+        user_account.purge_expired(clock.unix_timestamp);
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.user.key(),
+            ArenaError::Unauthorized
+        );
+        let refundable_points = ctx
+            .accounts
+            .boost_inventory
+            .take(ctx.accounts.boost_config.id, 1)?;
+        let expires_at = push_user_boost(
+            user_account,
+            &ctx.accounts.boost_config,
+            refundable_points,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(BoostActivatedEvent {
+            user: ctx.accounts.user.key(),
+            boost_id: ctx.accounts.boost_config.id,
+            expires_at,
+            payment: BoostPayment::BoostPoints,
+            price: 0,
+            from_inventory: true,
+        });
+        Ok(())
+    }
+
+    /// Moves `count` unactivated boosts of `boost_id` to another user's
+    /// inventory.
+    pub fn gift_boost(ctx: Context<GiftBoost>, boost_id: u16, count: u32) -> Result<()> {
+        require!(count > 0, ArenaError::InvalidAmount);
+        let recipient = ctx.accounts.recipient.key();
+        require!(
+            recipient != Pubkey::default() && recipient != ctx.accounts.sender.key(),
+            ArenaError::InvalidNewOwner
+        );
+        let refundable_points = ctx.accounts.sender_inventory.take(boost_id, count)?;
+        let recipient_inventory = &mut ctx.accounts.recipient_inventory;
+        recipient_inventory.init_owner(recipient)?;
+        recipient_inventory.add(boost_id, count, refundable_points)?;
+
+        emit!(BoostGiftedEvent {
+            from: ctx.accounts.sender.key(),
+            to: recipient,
+            boost_id,
+            count,
+        });
+        Ok(())
+    }
+
+    /// Ends the active boost at `index` early, returning the unused share of
+    /// its boost point cost when the boost was bought as refundable.
+    /// Expired boosts are purged first, so `index` counts live boosts only.
+    /// The refund needs the boost's config for the charges a fresh
+    /// activation starts with; without it (e.g. once the config is closed)
+    /// the boost ends without a refund.
+    pub fn deactivate_boost(ctx: Context<DeactivateBoost>, index: u8) -> Result<()> {
+        upgrade_user_account(
+            &ctx.accounts.user_account,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+        )?;
+        let clock = Clock::get()?;
+        let user_account = &mut ctx.accounts.user_account;
+        require_keys_eq!(
+            user_account.owner,
+            ctx.accounts.user.key(),
+            ArenaError::Unauthorized
+        );
+        user_account.purge_expired(clock.unix_timestamp);
+        let index = index as usize;
+        require!(
+            index < user_account.active_boosts.len(),
+            ArenaError::InvalidBoostIndex
+        );
+        let boost = user_account.active_boosts.remove(index);
+        // Staking boosts already shaped the positions opened under them.
+        require!(
+            boost.refundable_points == 0 || !boost.kind.is_staking(),
+            ArenaError::BoostNotRefundable
+        );
+        let refunded = match ctx.accounts.boost_config.as_deref() {
+            Some(boost_config) => {
+                require!(
+                    boost.boost_id == boost_config.id,
+                    ArenaError::InvalidBoostIndex
+                );
+                boost.refund_amount(clock.unix_timestamp, boost_config.initial_charges())
+            }
+            None => 0,
+        };
+        user_account.boost_points = user_account
+            .boost_points
+            .checked_add(refunded)
+            .ok_or(ArenaError::MathOverflow)?;
+
+        emit!(BoostDeactivatedEvent {
+            user: ctx.accounts.user.key(),
+            boost_id: boost.boost_id,
+            refunded_points: refunded,
+        });
+        Ok(())
+    }
+}
+
+// -------------------------------------
+// Accounts
+// -------------------------------------
+
+/// Implements the account traits `#[account]` derives for a struct whose
+/// stored layout can be older than the current one. The data after the
+/// discriminator goes through the type's own `decode`, which converts older
+/// layouts; writing always uses the current layout. `$discriminator` is
+/// sha256("account:<name>")[..8], as `#[account]` derives it.
+macro_rules! legacy_account {
+    ($name:ident, $discriminator:expr) => {
+        impl Discriminator for $name {
+            const DISCRIMINATOR: [u8; 8] = $discriminator;
+        }
+
+        impl Owner for $name {
+            fn owner() -> Pubkey {
+                crate::ID
+            }
+        }
+
+        impl AccountSerialize for $name {
+            fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+                writer
+                    .write_all(&Self::DISCRIMINATOR)
+                    .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+                AnchorSerialize::serialize(self, writer)
+                    .map_err(|_| ErrorCode::AccountDidNotSerialize)?;
+                Ok(())
+            }
+        }
+
+        impl AccountDeserialize for $name {
+            fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+                if buf.len() < 8 {
+                    return err!(ErrorCode::AccountDiscriminatorNotFound);
+                }
+                if buf[..8] != Self::DISCRIMINATOR {
+                    return err!(ErrorCode::AccountDiscriminatorMismatch);
+                }
+                Self::try_deserialize_unchecked(buf)
+            }
+
+            fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+                Self::decode(&buf[8..])
+            }
+        }
+    };
+}
+
+/// Decodes `data` as `T`, reading fields past its end as zero; for layouts
+/// that only ever grew at the end.
+fn decode_zero_padded<T: AnchorDeserialize>(data: &[u8], len: usize) -> Result<T> {
+    let mut padded = data.to_vec();
+    if padded.len() < len {
+        padded.resize(len, 0);
+    }
+    T::deserialize(&mut &padded[..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub game_mint: Pubkey,
+    pub xnt_mint: Pubkey,
+    pub treasury_xnt_vault: Pubkey,
+    pub halving_interval: u64,
+    pub halving_level: u8,
+    pub total_minted: u64,
+    pub staking_share_bps: u16,
+    /// Stacking mode per `BoostKind`, indexed by `BoostKind::index`.
+    pub boost_stacking: [StackingMode; MAX_BOOST_KINDS],
+    /// Upper bound on any combined boost multiplier; 0 means uncapped.
+    pub max_boost_multiplier_bps: u32,
+    /// GAME burned through boost purchases.
+    pub total_burned: u64,
+    /// Seconds added to UTC before deriving the day used for daily points.
+    pub day_offset_secs: i64,
+    /// Added to the offset-derived day so day ids continue across offset
+    /// changes.
+    pub day_id_adjust: i64,
+    /// Offset queued by `set_day_offset`, with its adjustment; it replaces
+    /// the current one from `pending_day_offset_from_ts`, the next day
+    /// boundary when it was queued. 0 means nothing is queued.
+    pub pending_day_offset_secs: i64,
+    pub pending_day_id_adjust: i64,
+    pub pending_day_offset_from_ts: i64,
+    /// Lowest id `publish_ranking_epoch` accepts.
+    pub next_ranking_epoch_id: u64,
+    /// Set by `initialize_leaderboard`; `mine_with_rig` requires the
+    /// leaderboard from then on.
+    pub leaderboard_initialized: bool,
+}
+
+impl GlobalConfig {
+    pub const LEN: usize =
+        32 * 4 + 8 + 1 + 8 + 2 + 8 + MAX_BOOST_KINDS + 4 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn day_id(&self, now: i64) -> i64 {
+        let (offset_secs, adjust) = self.day_params(now);
+        now.saturating_add(offset_secs)
+            .div_euclid(86_400)
+            .saturating_add(adjust)
+    }
+
+    /// Offset and adjustment in force at `now`.
+    fn day_params(&self, now: i64) -> (i64, i64) {
+        if self.pending_day_offset_from_ts > 0 && now >= self.pending_day_offset_from_ts {
+            (self.pending_day_offset_secs, self.pending_day_id_adjust)
+        } else {
+            (self.day_offset_secs, self.day_id_adjust)
+        }
+    }
+
+    /// Queues `offset_secs` to apply from the next day boundary under the
+    /// offset in force at `now`. The first day under the new offset takes
+    /// the next day id, so days never repeat or get skipped; it may be
+    /// shorter than 24 hours.
+    pub fn schedule_day_offset(&mut self, offset_secs: i64, now: i64) {
+        let (current_offset, current_adjust) = self.day_params(now);
+        self.day_offset_secs = current_offset;
+        self.day_id_adjust = current_adjust;
+        self.pending_day_offset_from_ts = 0;
+
+        let today = now.saturating_add(current_offset).div_euclid(86_400);
+        let next_boundary = (today + 1) * 86_400 - current_offset;
+        let next_day_id = self.day_id(next_boundary);
+        self.pending_day_offset_secs = offset_secs;
+        self.pending_day_id_adjust =
+            next_day_id - next_boundary.saturating_add(offset_secs).div_euclid(86_400);
+        self.pending_day_offset_from_ts = next_boundary;
+    }
+
+    pub fn stacking_mode(&self, kind: &BoostKind) -> StackingMode {
+        match self.boost_stacking[kind.index()] {
+            StackingMode::Default if kind.is_staking() || kind.is_bonus() => {
+                StackingMode::HighestOnly
+            }
+            StackingMode::Default => StackingMode::Multiplicative,
+            mode => mode,
+        }
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct StakingPool {
+    pub token_mint: Pubkey,
+    pub xnt_mint: Pubkey,
+    pub staking_vault: Pubkey,
+    pub treasury_xnt_vault: Pubkey,
+    pub total_effective_stake: u128,
+    pub acc_reward_per_share: u128,
+    /// Staker share of deposits received while `total_effective_stake` was
+    /// zero, waiting to be folded into `acc_reward_per_share`.
+    pub pending_undistributed: u64,
+    /// Streaming period for new deposits; 0 credits deposits immediately.
+    pub reward_duration_secs: i64,
+    /// XNT per second scaled by `PRECISION`.
+    pub reward_rate: u128,
+    pub period_finish: i64,
+    pub last_update_ts: i64,
+    /// `total_effective_stake` history by slot.
+    pub checkpoints: PoolCheckpoints,
+    /// XNT committed to stakers (streamed, pending or credited) since
+    /// solvency tracking was introduced.
+    pub total_rewards_accrued: u128,
+    /// XNT actually paid out to stakers.
+    pub total_rewards_claimed: u128,
+    /// Cooldown between `begin_unbond` and `withdraw_unbonded`; 0 lets
+    /// unlocked positions `unstake` directly.
+    pub unbonding_period_secs: i64,
+    /// Time over which a new position's effective stake ramps from 0 to full.
+    pub warmup_period_secs: i64,
+    /// Anti-whale caps; 0 disables each one.
+    pub max_position_amount: u64,
+    pub max_user_effective_stake: u128,
+    pub max_user_share_bps: u16,
+    /// Rewards (scaled by `PRECISION`) left over from integer division when
+    /// crediting, carried into the next credit instead of being dropped.
+    pub reward_dust: u128,
+    /// Effective stake still warming up across positions and the liquid
+    /// vault, not yet in `total_effective_stake`.
+    pub total_warming_stake: u128,
+}
+
+impl StakingPool {
+    pub const LEN: usize = 32 * 4 // mints and vaults
+        + 16                        // total_effective_stake
+        + 16                        // acc_reward_per_share
+        + 8                         // pending_undistributed
+        + 8 + 16 + 8 + 8            // reward stream
+        + PoolCheckpoints::LEN
+        + 16 + 16                   // total_rewards_accrued / claimed
+        + 8                         // unbonding_period_secs
+        + 8                         // warmup_period_secs
+        + 8 + 16 + 2                // stake caps
+        + 16                        // reward_dust
+        + 16; // total_warming_stake
+
+    /// Effective stake once everything warming up has warmed up; what the
+    /// stake caps compare against.
+    pub fn target_effective_stake(&self) -> u128 {
+        self.total_effective_stake
+            .saturating_add(self.total_warming_stake)
+    }
+
+    pub fn record_warming_stake_change(&mut self, removed: u128, added: u128) -> Result<()> {
+        self.total_warming_stake = self
+            .total_warming_stake
+            .saturating_sub(removed)
+            .checked_add(added)
+            .ok_or(ArenaError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Treasury XNT owed to stakers and unavailable for operations.
+    pub fn reserved_rewards(&self) -> u128 {
+        self.total_rewards_accrued
+            .saturating_sub(self.total_rewards_claimed)
+    }
+
+    /// XNT committed to stakers but not yet in `acc_reward_per_share`: the
+    /// buffer, the rest of the running stream and the carried dust, rounded
+    /// up. Streamed amounts are counted from `last_update_ts`.
+    pub fn uncredited_rewards(&self) -> u128 {
+        let remaining_secs = self
+            .period_finish
+            .saturating_sub(self.last_update_ts)
+            .max(0) as u128;
+        let scaled = self
+            .reward_rate
+            .saturating_mul(remaining_secs)
+            .saturating_add(self.reward_dust);
+        (self.pending_undistributed as u128).saturating_add(scaled.div_ceil(PRECISION))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeCheckpoint {
+    pub slot: u64,
+    pub effective_stake: u128,
+}
+
+impl StakeCheckpoint {
+    pub const LEN: usize = 8 + 16;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UserCheckpoints {
+    pub head: u8,
+    pub count: u8,
+    pub entries: [StakeCheckpoint; USER_CHECKPOINTS],
+}
+
+impl Default for UserCheckpoints {
+    fn default() -> Self {
+        Self {
+            head: 0,
+            count: 0,
+            entries: [StakeCheckpoint::default(); USER_CHECKPOINTS],
+        }
+    }
+}
+
+impl UserCheckpoints {
+    pub const LEN: usize = 1 + 1 + USER_CHECKPOINTS * StakeCheckpoint::LEN;
+
+    pub fn record(&mut self, slot: u64, effective_stake: u128) {
+        push_checkpoint(
+            &mut self.entries,
+            &mut self.head,
+            &mut self.count,
+            slot,
+            effective_stake,
+        );
+    }
+
+    pub fn value_at(&self, slot: u64) -> Result<u128> {
+        checkpoint_value_at(&self.entries, self.head, self.count, slot)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PoolCheckpoints {
+    pub head: u8,
+    pub count: u8,
+    pub entries: [StakeCheckpoint; POOL_CHECKPOINTS],
+}
+
+impl Default for PoolCheckpoints {
+    fn default() -> Self {
+        Self {
+            head: 0,
+            count: 0,
+            entries: [StakeCheckpoint::default(); POOL_CHECKPOINTS],
+        }
+    }
+}
+
+impl PoolCheckpoints {
+    pub const LEN: usize = 1 + 1 + POOL_CHECKPOINTS * StakeCheckpoint::LEN;
+
+    pub fn record(&mut self, slot: u64, effective_stake: u128) {
+        push_checkpoint(
+            &mut self.entries,
+            &mut self.head,
+            &mut self.count,
+            slot,
+            effective_stake,
+        );
+    }
+
+    pub fn value_at(&self, slot: u64) -> Result<u128> {
+        checkpoint_value_at(&self.entries, self.head, self.count, slot)
+    }
+}
+
+// Not `#[account]`: positions allocated before `seed_owner` existed are
+// shorter than `LEN` and read the missing trailing fields as zero (see
+// `legacy_account!`). Handlers that write a position grow it to `8 + LEN`
+// first (see the `realloc` constraints).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UserStakePosition {
+    pub owner: Pubkey,
+    pub amount_staked: u64,
+    pub lock_multiplier_bps: u16,
+    pub boost_multiplier_bps: u16,
+    pub effective_stake: u128,
+    pub reward_debt: u128,
+    pub lock_until_ts: i64,
+    pub position_id: u32,
+    /// Wallet whose key seeds the position PDA. Differs from `owner` once the
+    /// position has been transferred; default for legacy positions.
+    pub seed_owner: Pubkey,
+    /// Non-zero once `begin_unbond` ran: GAME is withdrawable from this time.
+    pub unbonding_until_ts: i64,
+    /// Warmup window; `effective_stake` ramps linearly to its full value at
+    /// `warmup_end_ts`. Both zero once fully warmed up.
+    pub warmup_start_ts: i64,
+    pub warmup_end_ts: i64,
+    /// Effective stake still to come from the warmup, on top of
+    /// `effective_stake`. Stake caps count it (see `target_stake`).
+    pub warming_stake: u128,
+}
+
+legacy_account!(UserStakePosition, [123, 46, 248, 17, 240, 135, 201, 17]);
+
+impl UserStakePosition {
+    // The first `LEGACY_LEN` bytes are the layout from before `seed_owner`
+    // existed, which ended in 4 bytes of padding after `position_id`. Fields
+    // are serialized back to back, so `seed_owner` starts where that padding
+    // was and the 4 spare bytes now sit at the end of `LEN`.
+    pub const LEN: usize = 32 + 8 + 2 + 2 + 16 + 16 + 8 + 4 + 4 + 32 + 8 + 8 + 8 + 16;
+    pub const LEGACY_LEN: usize = 32 + 8 + 2 + 2 + 16 + 16 + 8 + 4 + 4;
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        decode_zero_padded(data, Self::LEN)
+    }
+
+    pub fn is_warming_up(&self) -> bool {
+        self.warmup_end_ts != 0
+    }
+
+    /// Effective stake the position reaches once warmed up.
+    pub fn target_stake(&self) -> u128 {
+        self.effective_stake.saturating_add(self.warming_stake)
+    }
+
+    /// Portion of `full_effective` earned at `now` given the warmup window.
+    pub fn warmed_effective_stake(&self, full_effective: u128, now: i64) -> u128 {
+        if !self.is_warming_up() || now >= self.warmup_end_ts {
+            return full_effective;
+        }
+        if now <= self.warmup_start_ts {
+            return 0;
+        }
+        let elapsed = (now - self.warmup_start_ts) as u128;
+        let period = (self.warmup_end_ts - self.warmup_start_ts) as u128;
+        full_effective.saturating_mul(elapsed) / period
+    }
+
+    pub fn is_unbonding(&self) -> bool {
+        self.unbonding_until_ts != 0
+    }
+
+    /// What `unstake` does with this (unlocked) position at `now`.
+    pub fn unstake_action(&self, unbonding_period_secs: i64, now: i64) -> Result<UnstakeAction> {
+        if self.is_unbonding() {
+            require!(
+                self.unbonding_until_ts <= now,
+                ArenaError::UnbondingInProgress
+            );
+            Ok(UnstakeAction::WithdrawUnbonded)
+        } else if unbonding_period_secs > 0 {
+            Ok(UnstakeAction::BeginUnbond {
+                unbonding_until_ts: now.saturating_add(unbonding_period_secs),
+            })
+        } else {
+            Ok(UnstakeAction::Withdraw)
+        }
+    }
+
+    pub fn seed_owner(&self) -> Pubkey {
+        if self.seed_owner == Pubkey::default() {
+            self.owner
+        } else {
+            self.seed_owner
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnstakeAction {
+    /// No unbonding period: settle and return the GAME now.
+    Withdraw,
+    /// Unbonding is enabled: settle and start the cooldown.
+    BeginUnbond { unbonding_until_ts: i64 },
+    /// The cooldown has elapsed: return the GAME.
+    WithdrawUnbonded,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BoostKind {
+    #[default]
+    MiningRewardBps,
+    MiningPointsBps,
+    FreeRigTicket,
+    StakingMultiplierBps,
+    /// `value_bps` off the rig's XNT cost.
+    RigCostDiscountBps,
+    /// `value_bps` added to the rig's high-reward probability.
+    CritChanceBps,
+    /// `value_bps` off the lock length of new stakes.
+    LockReductionBps,
+}
+
+impl BoostKind {
+    pub fn index(&self) -> usize {
+        match self {
+            BoostKind::MiningRewardBps => 0,
+            BoostKind::MiningPointsBps => 1,
+            BoostKind::FreeRigTicket => 2,
+            BoostKind::StakingMultiplierBps => 3,
+            BoostKind::RigCostDiscountBps => 4,
+            BoostKind::CritChanceBps => 5,
+            BoostKind::LockReductionBps => 6,
+        }
+    }
+
+    /// Kinds applied when staking rather than mining.
+    pub fn is_staking(&self) -> bool {
+        matches!(
+            self,
+            BoostKind::StakingMultiplierBps | BoostKind::LockReductionBps
+        )
+    }
+
+    /// Kinds whose `value_bps` is an amount rather than a multiplier.
+    pub fn is_bonus(&self) -> bool {
+        matches!(
+            self,
+            BoostKind::RigCostDiscountBps | BoostKind::CritChanceBps | BoostKind::LockReductionBps
+        )
+    }
+}
+
+/// How several active boosts of the same kind combine. `Default` keeps the
+/// original rule: highest-only for staking and bonus kinds, multiplicative
+/// otherwise.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StackingMode {
+    #[default]
+    Default,
+    Multiplicative,
+    Additive,
+    HighestOnly,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserBoost {
+    pub boost_id: u16,
+    pub kind: BoostKind,
+    pub value_bps: u16,
+    pub expires_at_ts: i64,
+    pub rig_id: Option<u8>,
+    pub applied_to_mining: bool,
+    pub applied_to_staking: bool,
+    /// Mining runs left before the boost is used up; 0 means it only
+    /// expires by time.
+    pub charges: u32,
+    /// Boost points paid for a refundable activation; 0 when not refundable.
+    pub refundable_points: u64,
+    pub activated_at_ts: i64,
+}
+
+impl UserBoost {
+    pub const LEN: usize = 2 + 1 + 2 + 8 + 2 + 1 + 1 + 4 + 8 + 8;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at_ts > 0 && self.expires_at_ts < now
+    }
+
+    pub fn applies_to_rig(&self, rig_id: u8) -> bool {
+        match self.rig_id {
+            Some(id) => id == rig_id,
+            None => true,
+        }
+    }
+
+    /// Share of `refundable_points` matching the unused part of the
+    /// boost's duration and, for charge-based boosts, of the
+    /// `initial_charges` it started with. Boosts with neither an expiry nor
+    /// charges refund nothing.
+    pub fn refund_amount(&self, now: i64, initial_charges: u32) -> u64 {
+        if self.refundable_points == 0 {
+            return 0;
+        }
+        let mut refund = self.refundable_points as u128;
+        if self.expires_at_ts > 0 {
+            let total = self.expires_at_ts.saturating_sub(self.activated_at_ts);
+            if total <= 0 || self.expires_at_ts <= now {
+                return 0;
+            }
+            let remaining = self.expires_at_ts.saturating_sub(now).min(total);
+            refund = refund * remaining as u128 / total as u128;
+        } else if initial_charges == 0 {
+            return 0;
+        }
+        if initial_charges > 0 {
+            refund = refund * self.charges.min(initial_charges) as u128 / initial_charges as u128;
+        }
+        refund as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Achievements {
+    pub first_mine: bool,
+    pub first_stake: bool,
+    pub seven_day_streak: bool,
+    pub heavy_operator: bool,
+}
+
+// Not `#[account]`: accounts created under the original layout, without the
+// version byte, are shorter than `LEN`. `decode` converts them to the
+// current layout; handlers grow them (see `upgrade_user_account`) before the
+// account is written back.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct UserAccount {
+    /// `USER_ACCOUNT_VERSION`; 0 only in a freshly allocated account.
+    pub version: u8,
+    pub owner: Pubkey,
+    pub daily_points: u32,
+    pub lifetime_points: u64,
+    pub boost_points: u64,
+    pub mining_xnt_spent: u64,
+    pub mining_tokens_mined: u64,
+    pub mining_runs_by_rig: [u64; 4],
+    pub mining_crit_count: u64,
+    pub staking_xnt_earned: u64,
+    pub last_day_id: i64,
+    pub achievements: Achievements,
+    pub next_position_id: u32,
+    pub active_boosts: Vec<UserBoost>,
+    /// Wallet allowed to claim and refresh positions on the owner's behalf.
+    /// Payouts always go to the owner's XNT account.
+    pub claim_delegate: Pubkey,
+    /// Sum of effective stake across the user's open positions.
+    pub total_effective_stake: u128,
+    pub checkpoints: UserCheckpoints,
+    /// `daily_points` of the previous day, kept at rollover.
+    pub yesterday_points: u32,
+    /// Sum of the open positions' `warming_stake`.
+    pub warming_stake: u128,
+}
+
+legacy_account!(UserAccount, [211, 33, 136, 16, 186, 110, 242, 127]);
+
+impl UserAccount {
+    pub const LEN: usize = 1  // version
+        + 32                  // owner
+        + 4                   // daily_points
+        + 8                   // lifetime_points
+        + 8                   // boost_points
+        + 8                   // mining_xnt_spent
+        + 8                   // mining_tokens_mined
+        + 32                  // mining_runs_by_rig
+        + 8                   // mining_crit_count
+        + 8                   // staking_xnt_earned
+        + 8                   // last_day_id
+        + 4                   // achievements
+        + 4                   // next_position_id
+        + 4 + MAX_ACTIVE_BOOSTS * UserBoost::LEN
+        + 32                  // claim_delegate
+        + 16                  // total_effective_stake
+        + UserCheckpoints::LEN
+        + 4                   // yesterday_points
+        + 16; // warming_stake
+
+    /// `init_if_needed` space: the current size of an existing account, which
+    /// is smaller for the original layout until a handler grows it, or `LEN` for a
+    /// new one.
+    pub fn init_space(info: &AccountInfo) -> usize {
+        if info.owner == &crate::ID {
+            info.data_len()
+        } else {
+            8 + Self::LEN
+        }
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        let mut account: Self = if data.len() < Self::LEN {
+            LegacyUserAccount::deserialize(&mut &data[..])
+                .map_err(|_| ErrorCode::AccountDidNotDeserialize)?
+                .into()
+        } else {
+            require!(
+                data[0] == 0 || data[0] == USER_ACCOUNT_VERSION,
+                ErrorCode::AccountDidNotDeserialize
+            );
+            AnchorDeserialize::deserialize(&mut &data[..])
+                .map_err(|_| ErrorCode::AccountDidNotDeserialize)?
+        };
+        account.version = USER_ACCOUNT_VERSION;
+        Ok(account)
+    }
+
+    /// Starts a new day for `daily_points` once `day_id` moves on. Points
+    /// carry into `yesterday_points` only when the day is consecutive.
+    /// Earlier days are ignored, so a stale day never resets points.
+    pub fn roll_over_day(&mut self, day_id: i64) {
+        if day_id <= self.last_day_id {
+            return;
+        }
+        self.yesterday_points = if day_id == self.last_day_id.saturating_add(1) {
+            self.daily_points
+        } else {
+            0
+        };
+        self.daily_points = 0;
+        self.last_day_id = day_id;
+    }
+
+    pub fn purge_expired(&mut self, now: i64) {
+        let owner = self.owner;
+        self.active_boosts.retain(|b| {
+            let expired = b.is_expired(now);
+            if expired {
+                emit!(BoostExpiredEvent {
+                    user: owner,
+                    boost_id: b.boost_id,
+                    expired_at: b.expires_at_ts,
+                });
+            }
+            !expired
+        });
+    }
+
+    /// Uses one charge of the boost at `index`, removing it once exhausted.
+    /// Returns the charges left.
+    pub fn consume_boost_charge(&mut self, index: usize) -> u32 {
+        let boost = &mut self.active_boosts[index];
+        boost.charges = boost.charges.saturating_sub(1);
+        let remaining = boost.charges;
+        if remaining == 0 {
+            self.active_boosts.remove(index);
+        }
+        remaining
+    }
+
+    pub fn record_effective_stake_change(
+        &mut self,
+        removed: u128,
+        added: u128,
+        slot: u64,
+    ) -> Result<()> {
+        self.total_effective_stake = self
+            .total_effective_stake
+            .saturating_sub(removed)
+            .checked_add(added)
+            .ok_or(ArenaError::MathOverflow)?;
+        self.checkpoints.record(slot, self.total_effective_stake);
+        Ok(())
+    }
+
+    pub fn record_warming_stake_change(&mut self, removed: u128, added: u128) -> Result<()> {
+        self.warming_stake = self
+            .warming_stake
+            .saturating_sub(removed)
+            .checked_add(added)
+            .ok_or(ArenaError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Raises the stake totals to what `sync_user_stake` counted from the
+    /// owner's positions. Either total only moves up: a count that leaves
+    /// positions out must not lower what the stake caps see.
+    pub fn raise_stake_totals(&mut self, total: u128, warming: u128, slot: u64) -> Result<()> {
+        let previous = self.total_effective_stake;
+        self.record_effective_stake_change(previous, previous.max(total), slot)?;
+        let previous_warming = self.warming_stake;
+        self.record_warming_stake_change(previous_warming, previous_warming.max(warming))
+    }
+
+    /// Effective stake once every open position has warmed up; what the
+    /// stake caps limit.
+    pub fn target_effective_stake(&self) -> u128 {
+        self.total_effective_stake
+            .saturating_add(self.warming_stake)
+    }
+
+    pub fn is_owner_or_delegate(&self, authority: &Pubkey) -> bool {
+        *authority == self.owner
+            || (self.claim_delegate != Pubkey::default() && *authority == self.claim_delegate)
+    }
+}
+
+/// `UserBoost` in the original layout: single-byte ids and no charges.
+#[derive(AnchorDeserialize)]
+struct LegacyUserBoost {
+    boost_id: u8,
+    kind: BoostKind,
+    value_bps: u16,
+    expires_at_ts: i64,
+    rig_id: Option<u8>,
+    applied_to_mining: bool,
+    applied_to_staking: bool,
+}
+
+impl From<LegacyUserBoost> for UserBoost {
+    fn from(b: LegacyUserBoost) -> Self {
+        UserBoost {
+            // Legacy tickets were single-use.
+            charges: u32::from(b.kind == BoostKind::FreeRigTicket),
+            boost_id: b.boost_id.into(),
+            kind: b.kind,
+            value_bps: b.value_bps,
+            expires_at_ts: b.expires_at_ts,
+            rig_id: b.rig_id,
+            applied_to_mining: b.applied_to_mining,
+            applied_to_staking: b.applied_to_staking,
+            refundable_points: 0,
+            activated_at_ts: 0,
+        }
+    }
+}
+
+/// `UserAccount` in the original layout, read by `UserAccount::decode`.
+#[derive(AnchorDeserialize)]
+struct LegacyUserAccount {
+    owner: Pubkey,
+    daily_points: u32,
+    lifetime_points: u64,
+    boost_points: u64,
+    mining_xnt_spent: u64,
+    mining_tokens_mined: u64,
+    mining_runs_by_rig: [u64; 4],
+    mining_crit_count: u64,
+    staking_xnt_earned: u64,
+    last_day_id: i64,
+    achievements: Achievements,
+    next_position_id: u32,
+    active_boosts: Vec<LegacyUserBoost>,
+}
+
+impl From<LegacyUserAccount> for UserAccount {
+    fn from(legacy: LegacyUserAccount) -> Self {
+        UserAccount {
+            version: USER_ACCOUNT_VERSION,
+            owner: legacy.owner,
+            daily_points: legacy.daily_points,
+            lifetime_points: legacy.lifetime_points,
+            boost_points: legacy.boost_points,
+            mining_xnt_spent: legacy.mining_xnt_spent,
+            mining_tokens_mined: legacy.mining_tokens_mined,
+            mining_runs_by_rig: legacy.mining_runs_by_rig,
+            mining_crit_count: legacy.mining_crit_count,
+            staking_xnt_earned: legacy.staking_xnt_earned,
+            last_day_id: legacy.last_day_id,
+            achievements: legacy.achievements,
+            next_position_id: legacy.next_position_id,
+            active_boosts: legacy.active_boosts.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[account]
+pub struct BoostConfig {
+    pub id: u16,
+    pub kind: BoostKind,
+    /// Price per currency; `None` means the currency is not accepted and
+    /// `Some(0)` makes the boost free in it. Applies to `price_game` and
+    /// `price_xnt` too.
+    pub cost_boost_points: Option<u64>,
+    pub value_bps: u16,
+    pub duration_seconds: i64,
+    pub rig_id: Option<u8>,
+    /// Sale window; 0 leaves that side open.
+    pub start_ts: i64,
+    pub end_ts: i64,
+    /// Total activations allowed; 0 means unlimited.
+    pub max_supply: u64,
+    pub sold: u64,
+    /// Activations allowed per user, tracked in `BoostReceipt`; 0 means unlimited.
+    pub per_user_limit: u32,
+    pub enabled: bool,
+    pub price_game: Option<u64>,
+    pub price_xnt: Option<u64>,
+    /// Mining runs granted per activation; 0 means time-limited only
+    /// (`FreeRigTicket` always gets at least one).
+    pub charges: u32,
+    /// Whether `deactivate_boost` returns unused boost points pro rata.
+    pub refundable: bool,
+}
+
+impl BoostConfig {
+    pub const LEN: usize = 2 + 1 + 9 + 2 + 8 + 2 + 8 + 8 + 8 + 8 + 4 + 1 + 9 + 9 + 4 + 1;
+
+    pub fn is_available(&self, now: i64) -> bool {
+        self.enabled
+            && (self.start_ts == 0 || now >= self.start_ts)
+            && (self.end_ts == 0 || now < self.end_ts)
+    }
+
+    /// Charges a fresh activation starts with; tickets always have one.
+    pub fn initial_charges(&self) -> u32 {
+        if self.kind == BoostKind::FreeRigTicket {
+            self.charges.max(1)
+        } else {
+            self.charges
+        }
+    }
+
+    /// Part of `price` that a later `deactivate_boost` may refund. Only
+    /// boost point payments for refundable boosts qualify.
+    pub fn refundable_points(&self, payment: BoostPayment, price: u64) -> u64 {
+        if self.refundable && payment == BoostPayment::BoostPoints {
+            price
+        } else {
+            0
+        }
+    }
+}
+
+/// Per-user, per-boost purchase counter.
+#[account]
+pub struct BoostReceipt {
+    pub user: Pubkey,
+    pub boost_id: u16,
+    pub purchased: u32,
+}
+
+impl BoostReceipt {
+    pub const LEN: usize = 32 + 2 + 4;
+}
+
+/// `BoostConfig` in the original layout, under a single-byte id, read by
+/// `migrate_boost_config`.
+#[derive(AnchorDeserialize)]
+struct LegacyBoostConfig {
+    id: u8,
+    kind: BoostKind,
+    cost_boost_points: u64,
+    value_bps: u16,
+    duration_seconds: i64,
+    rig_id: Option<u8>,
+}
+
+impl From<LegacyBoostConfig> for BoostConfig {
+    fn from(legacy: LegacyBoostConfig) -> Self {
+        BoostConfig {
+            id: legacy.id.into(),
+            kind: legacy.kind,
+            // Boost points were the only currency; a price of 0 was free.
+            cost_boost_points: Some(legacy.cost_boost_points),
+            value_bps: legacy.value_bps,
+            duration_seconds: legacy.duration_seconds,
+            rig_id: legacy.rig_id,
+            start_ts: 0,
+            end_ts: 0,
+            max_supply: 0,
+            sold: 0,
+            per_user_limit: 0,
+            enabled: true,
+            price_game: None,
+            price_xnt: None,
+            charges: 0,
+            refundable: false,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PositionEntry {
+    pub position: Pubkey,
+    pub position_id: u32,
+    pub amount_staked: u64,
+    pub lock_until_ts: i64,
+}
+
+impl PositionEntry {
+    pub const LEN: usize = 32 + 4 + 8 + 8;
+}
+
+/// Index of a wallet's open stake positions so clients can list them in a
+/// single fetch. The account grows by one entry whenever it is full, so there
+/// is no cap on open positions. Positions opened before the index existed are
+/// added with `index_positions`.
+#[account]
+pub struct UserPositions {
+    pub owner: Pubkey,
+    pub positions: Vec<PositionEntry>,
+}
+
+impl UserPositions {
+    /// Account size (with discriminator) holding `entries` entries.
+    pub fn space_for(entries: usize) -> usize {
+        8 + 32 + 4 + entries * PositionEntry::LEN
+    }
+
+    /// `init_if_needed` space: the current size of an existing index, which
+    /// differs between wallets as indexes grow, or room for
+    /// `INITIAL_USER_POSITIONS` entries for a new one.
+    pub fn init_space(info: &AccountInfo) -> usize {
+        if info.owner == &crate::ID {
+            info.data_len()
+        } else {
+            Self::space_for(INITIAL_USER_POSITIONS)
+        }
+    }
+
+    pub fn init_owner(&mut self, owner: Pubkey) -> Result<()> {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+        }
+        require_keys_eq!(self.owner, owner, ArenaError::Unauthorized);
+        Ok(())
+    }
+
+    /// Inserts or refreshes the entry for `position`.
+    pub fn upsert(&mut self, key: Pubkey, position: &UserStakePosition) -> Result<()> {
+        let entry = PositionEntry {
+            position: key,
+            position_id: position.position_id,
+            amount_staked: position.amount_staked,
+            lock_until_ts: position.lock_until_ts,
+        };
+        if let Some(existing) = self.positions.iter_mut().find(|e| e.position == key) {
+            *existing = entry;
+            return Ok(());
+        }
+        self.positions.push(entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &Pubkey) {
+        self.positions.retain(|e| e.position != *key);
+    }
+
+    /// Fails unless every indexed position is among `keys`.
+    pub fn require_all_listed(&self, keys: &[Pubkey]) -> Result<()> {
+        require!(
+            self.positions.iter().all(|e| keys.contains(&e.position)),
+            ArenaError::MissingPosition
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct InventoryItem {
+    pub boost_id: u16,
+    pub count: u32,
+    /// Boost points paid for the refundable boosts among `count`.
+    pub refundable_points: u64,
+}
+
+impl InventoryItem {
+    pub const LEN: usize = 2 + 4 + 8;
+}
+
+/// Purchased boosts waiting to be activated, counted per boost id.
+#[account]
+#[derive(Default)]
+pub struct BoostInventory {
+    pub owner: Pubkey,
+    pub items: Vec<InventoryItem>,
+}
+
+impl BoostInventory {
+    pub const LEN: usize = 32 + 4 + MAX_INVENTORY_ITEMS * InventoryItem::LEN;
+
+    pub fn init_owner(&mut self, owner: Pubkey) -> Result<()> {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+        }
+        require_keys_eq!(self.owner, owner, ArenaError::Unauthorized);
+        Ok(())
+    }
+
+    /// Adds `count` boosts of `boost_id`, of which `refundable_points`
+    /// were paid in refundable boost points.
+    pub fn add(&mut self, boost_id: u16, count: u32, refundable_points: u64) -> Result<()> {
+        if let Some(item) = self.items.iter_mut().find(|i| i.boost_id == boost_id) {
+            item.count = item
+                .count
+                .checked_add(count)
+                .ok_or(ArenaError::MathOverflow)?;
+            item.refundable_points = item
+                .refundable_points
+                .checked_add(refundable_points)
+                .ok_or(ArenaError::MathOverflow)?;
+            return Ok(());
+        }
+        require!(
+            self.items.len() < MAX_INVENTORY_ITEMS,
+            ArenaError::InventoryFull
+        );
+        self.items.push(InventoryItem {
+            boost_id,
+            count,
+            refundable_points,
+        });
+        Ok(())
+    }
+
+    /// Removes `count` boosts of `boost_id` and returns their pro-rata
+    /// share of the item's refundable points.
+    pub fn take(&mut self, boost_id: u16, count: u32) -> Result<u64> {
+        let item = self
+            .items
+            .iter_mut()
+            .find(|i| i.boost_id == boost_id)
+            .ok_or(ArenaError::InsufficientInventory)?;
+        require!(item.count >= count, ArenaError::InsufficientInventory);
+        let refundable_points =
+            (item.refundable_points as u128 * count as u128 / item.count as u128) as u64;
+        item.count -= count;
+        item.refundable_points -= refundable_points;
+        self.items.retain(|i| i.count > 0);
+        Ok(refundable_points)
+    }
+}
+
+/// Spending rules for operator withdrawals from `treasury_xnt_vault`.
+#[account]
+pub struct TreasuryPolicy {
+    /// Maximum XNT withdrawable per period; 0 disables withdrawals.
+    pub budget_per_period: u64,
+    pub period_secs: i64,
+    pub period_start_ts: i64,
+    pub spent_in_period: u64,
+    /// Delay between queueing and executing a withdrawal.
+    pub timelock_secs: i64,
+    pub next_withdrawal_id: u64,
+}
+
+impl TreasuryPolicy {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 8;
+
+    /// Configured policies always have a period; a fresh account does not.
+    pub fn is_configured(&self) -> bool {
+        self.period_secs > 0
+    }
+
+    /// Whether `args` only restricts withdrawals further: no larger budget,
+    /// no shorter period and no shorter timelock.
+    pub fn is_tightened_by(&self, args: &TreasuryPolicyArgs) -> bool {
+        args.budget_per_period <= self.budget_per_period
+            && args.period_secs >= self.period_secs
+            && args.timelock_secs >= self.timelock_secs
+    }
+
+    pub fn apply(&mut self, args: &TreasuryPolicyArgs) {
+        self.budget_per_period = args.budget_per_period;
+        self.period_secs = args.period_secs;
+        self.timelock_secs = args.timelock_secs;
+    }
+}
+
+/// A loosening policy change waiting out the current timelock.
+#[account]
+pub struct TreasuryPolicyChange {
+    pub budget_per_period: u64,
+    pub period_secs: i64,
+    pub timelock_secs: i64,
+    pub queued_at_ts: i64,
+    pub executable_at_ts: i64,
+}
+
+impl TreasuryPolicyChange {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8;
+}
+
+#[account]
+pub struct TreasuryWithdrawal {
+    pub id: u64,
+    pub amount: u64,
+    /// XNT token account receiving the funds.
+    pub destination: Pubkey,
+    pub queued_at_ts: i64,
+    pub executable_at_ts: i64,
+}
+
+impl TreasuryWithdrawal {
+    pub const LEN: usize = 8 + 8 + 32 + 8 + 8;
+}
+
+/// Unlocked pooled stake backing sGAME. Its GAME sits in the staking vault
+/// and counts towards `total_effective_stake` at 1x. Deposits warm up in
+/// their own `LiquidDeposit` ticket, outside `total_game`, so they share in
+/// the vault's rewards only once their sGAME is minted. Harvested XNT stays
+/// in `xnt_vault` as `total_xnt` and belongs to share holders pro rata.
+#[account]
+#[derive(Default)]
+pub struct LiquidVault {
+    pub share_mint: Pubkey,
+    pub xnt_vault: Pubkey,
+    pub total_game: u64,
+    pub effective_stake: u128,
+    pub reward_debt: u128,
+    pub total_xnt_harvested: u64,
+    /// Shares counted in the supply but never minted; see
+    /// `MINIMUM_LIQUID_SHARES`.
+    pub locked_shares: u64,
+    /// GAME held in `LiquidDeposit` tickets still to be claimed.
+    pub pending_game: u64,
+    /// Effective stake of `pending_game`, counted in the pool's
+    /// `total_warming_stake`.
+    pub warming_stake: u128,
+    /// XNT in `xnt_vault` backing the shares: harvested rewards plus what
+    /// depositors added to match them.
+    pub total_xnt: u64,
+}
+
+impl LiquidVault {
+    pub const LEN: usize = 32 + 32 + 8 + 16 + 16 + 8 + 8 + 8 + 16 + 8;
+    /// Shares outstanding: the sGAME supply plus the locked minimum.
+    pub fn total_shares(&self, supply: u64) -> u64 {
+        supply.saturating_add(self.locked_shares)
+    }
+
+    /// sGAME minted for depositing `amount` GAME, and the shares locked
+    /// alongside them (only when the vault is empty).
+    pub fn shares_for_deposit(&self, amount: u64, supply: u64) -> Result<(u64, u64)> {
+        let total_shares = self.total_shares(supply);
+        if total_shares == 0 || self.total_game == 0 {
+            require!(amount > MINIMUM_LIQUID_SHARES, ArenaError::InvalidAmount);
+            return Ok((amount - MINIMUM_LIQUID_SHARES, MINIMUM_LIQUID_SHARES));
+        }
+        Ok((mul_div(amount, total_shares, self.total_game, false)?, 0))
+    }
+
+    /// GAME returned for burning `shares`, rounded down.
+    pub fn game_for_shares(&self, shares: u64, supply: u64) -> Result<u64> {
+        require!(shares <= supply, ArenaError::InvalidAmount);
+        mul_div(shares, self.total_game, self.total_shares(supply), false)
+    }
+
+    /// XNT backing `shares`: paid out on redemption (rounded down) and added
+    /// by depositors (rounded up).
+    pub fn xnt_for_shares(&self, shares: u64, supply: u64, round_up: bool) -> Result<u64> {
+        let total_shares = self.total_shares(supply);
+        if total_shares == 0 {
+            return Ok(0);
+        }
+        mul_div(shares, self.total_xnt, total_shares, round_up)
+    }
+
+    pub fn add_xnt(&mut self, amount: u64) -> Result<()> {
+        self.total_xnt = self
+            .total_xnt
+            .checked_add(amount)
+            .ok_or(ArenaError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn remove_xnt(&mut self, amount: u64) {
+        self.total_xnt = self.total_xnt.saturating_sub(amount);
+    }
+
+    /// Adds GAME deposited into a warming ticket. It earns nothing and has
+    /// no claim on `total_game` until the ticket is claimed.
+    pub fn add_pending_game(&mut self, amount: u64) -> Result<()> {
+        self.pending_game = self
+            .pending_game
+            .checked_add(amount)
+            .ok_or(ArenaError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Moves a claimed ticket's GAME behind the shares minted for it.
+    pub fn settle_pending_game(&mut self, amount: u64) -> Result<()> {
+        self.pending_game = self
+            .pending_game
+            .checked_sub(amount)
+            .ok_or(ArenaError::MathOverflow)?;
+        self.total_game = self
+            .total_game
+            .checked_add(amount)
+            .ok_or(ArenaError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Takes `amount` GAME out of the vault.
+    pub fn remove_game(&mut self, amount: u64) {
+        self.total_game = self.total_game.saturating_sub(amount);
+    }
+}
+
+/// GAME deposited into the liquid vault while the pool has a warmup period.
+/// It stays in the staking vault, counted as warming stake, until
+/// `warm_at_ts`; `claim_liquid_deposit` then mints its sGAME at the price of
+/// the time.
+#[account]
+pub struct LiquidDeposit {
+    pub owner: Pubkey,
+    pub game_amount: u64,
+    pub warm_at_ts: i64,
+}
+
+impl LiquidDeposit {
+    pub const LEN: usize = 32 + 8 + 8;
+}
+
+/// sGAME redeemed while the pool has an unbonding period: the GAME stays in
+/// the staking vault, earning nothing, until `unbonding_until_ts`.
+#[account]
+pub struct LiquidUnbond {
+    pub owner: Pubkey,
+    pub game_amount: u64,
+    pub unbonding_until_ts: i64,
+}
+
+impl LiquidUnbond {
+    pub const LEN: usize = 32 + 8 + 8;
+}
+
+/// Merkle-committed ranking allocations, claimed once per leaf.
+#[account]
+pub struct RankingEpoch {
+    pub epoch_id: u64,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    /// Sum of all leaf allocations; claims can never exceed it.
+    pub total_points: u64,
+    pub claimed_points: u64,
+    pub claimed_bitmap: Vec<u8>,
+}
+
+impl RankingEpoch {
+    pub fn bitmap_len(leaf_count: u32) -> usize {
+        (leaf_count as usize).div_ceil(8)
+    }
+
+    pub fn space(leaf_count: u32) -> usize {
+        8 + 32 + 4 + 8 + 8 + 4 + Self::bitmap_len(leaf_count)
+    }
+
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed_bitmap[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u32) {
+        self.claimed_bitmap[(index / 8) as usize] |= 1 << (index % 8);
+    }
+
+    pub fn record_claim(&mut self, index: u32, boost_points: u64) -> Result<()> {
+        let claimed_points = self
+            .claimed_points
+            .checked_add(boost_points)
+            .ok_or(ArenaError::MathOverflow)?;
+        require!(
+            claimed_points <= self.total_points,
+            ArenaError::RankingEpochExhausted
+        );
+        self.claimed_points = claimed_points;
+        self.set_claimed(index);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub user: Pubkey,
+    pub points: u32,
+}
+
+impl LeaderboardEntry {
+    pub const LEN: usize = 32 + 4;
+}
+
+/// Top users by `daily_points` for `day_id`, highest first. The previous
+/// day's table is kept frozen in `previous` once the day rolls over.
+#[account]
+pub struct Leaderboard {
+    pub day_id: i64,
+    pub entries: Vec<LeaderboardEntry>,
+    pub previous_day_id: i64,
+    pub previous: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub const LEN: usize = 8
+        + 4
+        + LEADERBOARD_SIZE * LeaderboardEntry::LEN
+        + 8
+        + 4
+        + LEADERBOARD_SIZE * LeaderboardEntry::LEN;
+
+    /// Freezes the current table once `day_id` moves on. Returns whether a
+    /// rollover happened; earlier days never replace the current table.
+    pub fn roll_over(&mut self, day_id: i64) -> bool {
+        if day_id <= self.day_id {
+            return false;
+        }
+        self.previous_day_id = self.day_id;
+        self.previous = std::mem::take(&mut self.entries);
+        self.day_id = day_id;
+        true
+    }
+
+    /// Records `user`'s daily total, keeping the table sorted and capped.
+    /// Ties keep the user who got there first ahead.
+    pub fn record(&mut self, user: Pubkey, points: u32) {
+        if let Some(index) = self.entries.iter().position(|e| e.user == user) {
+            self.entries.remove(index);
+        } else if self.entries.len() >= LEADERBOARD_SIZE {
+            match self.entries.last() {
+                Some(last) if points > last.points => {
+                    self.entries.pop();
+                }
+                _ => return,
+            }
+        }
+        let at = self.entries.partition_point(|e| e.points >= points);
+        self.entries.insert(at, LeaderboardEntry { user, points });
+    }
+}
+
+// -------------------------------------
+// Account Contexts
+// -------------------------------------
+
+#[derive(Accounts)]
+#[instruction(args: InitializeArgs)]
+pub struct InitializeGlobal<'info> {
+    #[account(
+        init,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        payer = payer,
+        space = 8 + GlobalConfig::LEN
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// XNT mint must already exist.
+    pub xnt_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = GAME_DECIMALS,
+        mint::authority = global_config,
+        mint::freeze_authority = global_config
+    )]
+    pub game_mint: Box<Account<'info, Mint>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasuryVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump, has_one = xnt_mint)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, address = global_config.xnt_mint)]
+    pub xnt_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = admin,
+        token::mint = xnt_mint,
+        token::authority = global_config
+    )]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStakingPool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump,
+        has_one = game_mint,
+        has_one = xnt_mint
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(
+        init,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        payer = admin,
+        space = 8 + StakingPool::LEN
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+
+    #[account(mut, address = global_config.game_mint)]
+    pub game_mint: Box<Account<'info, Mint>>,
+    #[account(address = global_config.xnt_mint)]
+    pub xnt_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = game_mint,
+        token::authority = staking_pool
+    )]
+    pub staking_vault: Box<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateHalving<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct MineWithRig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault,
+        constraint = staking_pool.token_mint == global_config.game_mint
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = global_config.game_mint)]
+    pub game_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = user_game_account.mint == game_mint.key(), constraint = user_game_account.owner == payer.key())]
+    pub user_game_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == payer.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = global_config.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, payer.key().as_ref()],
+        bump,
+        payer = payer,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+    /// Required once `initialize_leaderboard` has run; pass the program id
+    /// before that.
+    #[account(mut, seeds = [LEADERBOARD_SEED], bump)]
+    pub leaderboard: Option<Box<Account<'info, Leaderboard>>>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init,
+        seeds = [
+            USER_STAKE_SEED,
+            owner.key().as_ref(),
+            &user_account.next_position_id.max(1).to_le_bytes()
+        ],
+        bump,
+        payer = owner,
+        space = 8 + UserStakePosition::LEN
+    )]
+    pub user_stake_position: Box<Account<'info, UserStakePosition>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub user_stake_position: Account<'info, UserStakePosition>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAll<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: the owner's `UserStakePosition` accounts (writable).
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Account<'info, TokenAccount>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub user_stake_position: Account<'info, UserStakePosition>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BeginUnbond<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub user_stake_position: Account<'info, UserStakePosition>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnbonded<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Account<'info, TokenAccount>,
+    #[account(mut, owner = crate::ID, close = owner)]
+    pub user_stake_position: Account<'info, UserStakePosition>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MergePositions<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub target_position: Account<'info, UserStakePosition>,
+    #[account(mut, owner = crate::ID, close = owner)]
+    pub source_position: Account<'info, UserStakePosition>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SplitPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub source_position: Account<'info, UserStakePosition>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init,
+        seeds = [
+            USER_STAKE_SEED,
+            owner.key().as_ref(),
+            &user_account.next_position_id.max(1).to_le_bytes()
+        ],
+        bump,
+        payer = owner,
+        space = 8 + UserStakePosition::LEN
+    )]
+    pub new_position: Box<Account<'info, UserStakePosition>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureStakingPool<'info> {
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [STAKING_POOL_SEED], bump)]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+}
+
+#[derive(Accounts)]
+pub struct DistributePendingRewards<'info> {
+    #[account(mut, seeds = [STAKING_POOL_SEED], bump)]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: any wallet may receive a position
+    pub new_owner: UncheckedAccount<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub user_stake_position: Account<'info, UserStakePosition>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, new_owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserAccount::init_space(new_owner_account)
+    )]
+    pub new_owner_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, new_owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(new_owner_positions)
+    )]
+    pub new_owner_positions: Box<Account<'info, UserPositions>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateStakePosition<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: legacy layout may not deserialize; discriminator checked in handler
+    #[account(mut, owner = crate::ID)]
+    pub user_stake_position: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncUserStake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    /// CHECK: the owner's position index, which may not exist yet; decoded in the handler
+    #[account(seeds = [USER_POSITIONS_SEED, owner.key().as_ref()], bump)]
+    pub user_positions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: every `UserStakePosition` the owner holds.
+}
+
+#[derive(Accounts)]
+pub struct IndexPositions<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_POSITIONS_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserPositions::init_space(user_positions)
+    )]
+    pub user_positions: Box<Account<'info, UserPositions>>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: open `UserStakePosition`s to list.
+}
+
+#[derive(Accounts)]
+pub struct MigrateGlobalConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: legacy layout may not deserialize; discriminator checked in handler
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateStakingPool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    /// CHECK: legacy layout may not deserialize; discriminator checked in handler
+    #[account(mut, seeds = [STAKING_POOL_SEED], bump)]
+    pub staking_pool: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckTreasurySolvency<'info> {
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(address = global_config.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTreasuryPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        init_if_needed,
+        seeds = [TREASURY_POLICY_SEED],
+        bump,
+        payer = admin,
+        space = 8 + TreasuryPolicy::LEN
+    )]
+    pub treasury_policy: Account<'info, TreasuryPolicy>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueueTreasuryPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(seeds = [TREASURY_POLICY_SEED], bump)]
+    pub treasury_policy: Account<'info, TreasuryPolicy>,
+    #[account(
+        init,
+        seeds = [TREASURY_POLICY_CHANGE_SEED],
+        bump,
+        payer = admin,
+        space = 8 + TreasuryPolicyChange::LEN
+    )]
+    pub policy_change: Account<'info, TreasuryPolicyChange>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyTreasuryPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump)]
+    pub treasury_policy: Account<'info, TreasuryPolicy>,
+    #[account(mut, seeds = [TREASURY_POLICY_CHANGE_SEED], bump, close = admin)]
+    pub policy_change: Account<'info, TreasuryPolicyChange>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTreasuryPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [TREASURY_POLICY_CHANGE_SEED], bump, close = admin)]
+    pub policy_change: Account<'info, TreasuryPolicyChange>,
+}
+
+#[derive(Accounts)]
+pub struct QueueTreasuryWithdrawal<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump)]
+    pub treasury_policy: Account<'info, TreasuryPolicy>,
+    #[account(
+        init,
+        seeds = [
+            TREASURY_WITHDRAWAL_SEED,
+            &treasury_policy.next_withdrawal_id.to_le_bytes()
+        ],
+        bump,
+        payer = admin,
+        space = 8 + TreasuryWithdrawal::LEN
+    )]
+    pub treasury_withdrawal: Account<'info, TreasuryWithdrawal>,
+    #[account(constraint = destination.mint == global_config.xnt_mint)]
+    pub destination: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTreasuryWithdrawal<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, seeds = [TREASURY_POLICY_SEED], bump)]
+    pub treasury_policy: Account<'info, TreasuryPolicy>,
+    #[account(
+        mut,
+        seeds = [TREASURY_WITHDRAWAL_SEED, &treasury_withdrawal.id.to_le_bytes()],
+        bump,
+        close = admin
+    )]
+    pub treasury_withdrawal: Account<'info, TreasuryWithdrawal>,
+    #[account(mut, address = global_config.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, address = treasury_withdrawal.destination)]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTreasuryWithdrawal<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [TREASURY_WITHDRAWAL_SEED, &treasury_withdrawal.id.to_le_bytes()],
+        bump,
+        close = admin
+    )]
+    pub treasury_withdrawal: Account<'info, TreasuryWithdrawal>,
+}
+
+#[derive(Accounts)]
+pub struct GetVotingPower<'info> {
+    /// CHECK: wallet whose voting power is queried
+    pub user: UncheckedAccount<'info>,
+    #[account(seeds = [USER_ACCOUNT_SEED, user.key().as_ref()], bump)]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(seeds = [STAKING_POOL_SEED], bump)]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLiquidVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        init,
+        seeds = [LIQUID_VAULT_SEED],
+        bump,
+        payer = admin,
+        space = 8 + LiquidVault::LEN
+    )]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(
+        init,
+        seeds = [LIQUID_SHARE_MINT_SEED],
+        bump,
+        payer = admin,
+        mint::decimals = GAME_DECIMALS,
+        mint::authority = liquid_vault
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(address = global_config.xnt_mint)]
+    pub xnt_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        seeds = [LIQUID_XNT_VAULT_SEED],
+        bump,
+        payer = admin,
+        token::mint = xnt_mint,
+        token::authority = liquid_vault
+    )]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquid<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [LIQUID_VAULT_SEED], bump)]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(mut, address = liquid_vault.share_mint)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut, address = liquid_vault.xnt_vault)]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_share_account.mint == share_mint.key(), constraint = user_share_account.owner == owner.key())]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidVaultUser<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [LIQUID_VAULT_SEED], bump)]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(mut, address = liquid_vault.share_mint)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut, address = liquid_vault.xnt_vault)]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_share_account.mint == share_mint.key(), constraint = user_share_account.owner == owner.key())]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_id: u32)]
+pub struct BeginLiquidDeposit<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [LIQUID_VAULT_SEED], bump)]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(mut, address = liquid_vault.share_mint)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut, address = liquid_vault.xnt_vault)]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = user_share_account.mint == share_mint.key(), constraint = user_share_account.owner == owner.key())]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(
+        init,
+        seeds = [LIQUID_DEPOSIT_SEED, owner.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump,
+        payer = owner,
+        space = 8 + LiquidDeposit::LEN
+    )]
+    pub deposit_ticket: Box<Account<'info, LiquidDeposit>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_id: u32)]
+pub struct ClaimLiquidDeposit<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [LIQUID_VAULT_SEED], bump)]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(mut, address = liquid_vault.share_mint)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut, address = liquid_vault.xnt_vault)]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_share_account.mint == share_mint.key(), constraint = user_share_account.owner == owner.key())]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [LIQUID_DEPOSIT_SEED, owner.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub deposit_ticket: Box<Account<'info, LiquidDeposit>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_id: u32)]
+pub struct BeginLiquidUnbond<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [LIQUID_VAULT_SEED], bump)]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(mut, address = liquid_vault.share_mint)]
+    pub share_mint: Box<Account<'info, Mint>>,
+    #[account(mut, address = liquid_vault.xnt_vault)]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
+    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_share_account.mint == share_mint.key(), constraint = user_share_account.owner == owner.key())]
+    pub user_share_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [LIQUID_UNBOND_SEED, owner.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump,
+        payer = owner,
+        space = 8 + LiquidUnbond::LEN
+    )]
+    pub unbond_ticket: Box<Account<'info, LiquidUnbond>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
-impl StakingPool {
-    pub const LEN: usize = 32 * 4 + 16 + 16 + 8;
+#[derive(Accounts)]
+#[instruction(ticket_id: u32)]
+pub struct WithdrawLiquidUnbonded<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.token_mint == global_config.game_mint
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.staking_vault)]
+    pub staking_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
+    pub user_game_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [LIQUID_UNBOND_SEED, owner.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump,
+        has_one = owner,
+        close = owner
+    )]
+    pub unbond_ticket: Box<Account<'info, LiquidUnbond>>,
+    pub token_program: Program<'info, Token>,
 }
 
-#[account]
-pub struct UserStakePosition {
-    pub owner: Pubkey,
-    pub amount_staked: u64,
-    pub lock_multiplier_bps: u16,
-    pub boost_multiplier_bps: u16,
-    pub effective_stake: u128,
-    pub reward_debt: u128,
-    pub lock_until_ts: i64,
-    pub position_id: u32,
+#[derive(Accounts)]
+pub struct HarvestLiquidVault<'info> {
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [LIQUID_VAULT_SEED], bump)]
+    pub liquid_vault: Box<Account<'info, LiquidVault>>,
+    #[account(mut, address = liquid_vault.xnt_vault)]
+    pub vault_xnt_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
 }
 
-impl UserStakePosition {
-    // Keep the total allocated size the same as before (8 bytes padding
-    // retained) so legacy accounts remain compatible.
-    pub const LEN: usize = 32 + 8 + 2 + 2 + 16 + 16 + 8 + 4 + 4;
+/// Gives the liquid vault contexts, which share these field names, a
+/// `harvest_vault` paying the vault's pending rewards into its XNT account.
+macro_rules! liquid_vault_harvest {
+    ($($name:ident),+) => {
+        $(
+            impl<'info> $name<'info> {
+                fn harvest_vault(&mut self, global_bump: u8) -> Result<u64> {
+                    harvest_liquid_vault_rewards(
+                        &mut self.staking_pool,
+                        &mut self.liquid_vault,
+                        &self.global_config,
+                        &self.treasury_xnt_vault,
+                        &self.vault_xnt_account,
+                        &self.token_program,
+                        global_bump,
+                    )
+                }
+            }
+        )+
+    };
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
-pub enum BoostKind {
-    MiningRewardBps,
-    MiningPointsBps,
-    FreeRigTicket,
-    StakingMultiplierBps,
-}
+liquid_vault_harvest!(
+    DepositLiquid,
+    LiquidVaultUser,
+    BeginLiquidDeposit,
+    ClaimLiquidDeposit,
+    BeginLiquidUnbond,
+    HarvestLiquidVault
+);
 
-impl Default for BoostKind {
-    fn default() -> Self {
-        BoostKind::MiningRewardBps
-    }
+#[derive(Accounts)]
+pub struct MigrateUserAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: legacy layout may not deserialize; discriminator checked in handler
+    #[account(mut, owner = crate::ID)]
+    pub user_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
-pub struct UserBoost {
-    pub boost_id: u8,
-    pub kind: BoostKind,
-    pub value_bps: u16,
-    pub expires_at_ts: i64,
-    pub rig_id: Option<u8>,
-    pub applied_to_mining: bool,
-    pub applied_to_staking: bool,
+#[derive(Accounts)]
+pub struct SetClaimDelegate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    pub system_program: Program<'info, System>,
 }
 
-impl UserBoost {
-    pub const LEN: usize = 1 + 1 + 2 + 8 + 2 + 1 + 1;
-
-    pub fn is_expired(&self, now: i64) -> bool {
-        self.expires_at_ts > 0 && self.expires_at_ts < now
-    }
+/// Shared by `delegated_claim` and `refresh_position`: callable by the
+/// position owner or their `claim_delegate`.
+#[derive(Accounts)]
+pub struct KeeperPosition<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: position owner; checked against `user_account` and the position
+    pub owner: UncheckedAccount<'info>,
+    // Created here when a position was transferred to a wallet that has no
+    // user account yet.
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        payer = authority,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Box<Account<'info, UserAccount>>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        seeds = [STAKING_POOL_SEED],
+        bump,
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+    )]
+    pub staking_pool: Box<Account<'info, StakingPool>>,
+    #[account(mut, address = staking_pool.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Account<'info, TokenAccount>,
+    #[account(mut, constraint = owner_xnt_account.mint == global_config.xnt_mint, constraint = owner_xnt_account.owner == owner.key())]
+    pub owner_xnt_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        owner = crate::ID,
+        realloc = 8 + UserStakePosition::LEN,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub user_stake_position: Account<'info, UserStakePosition>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
 
-    pub fn applies_to_rig(&self, rig_id: u8) -> bool {
-        match self.rig_id {
-            Some(id) => id == rig_id,
-            None => true,
+impl<'info> KeeperPosition<'info> {
+    /// Checks the position belongs to `owner`; any signer may pass.
+    fn validate_position(&mut self) -> Result<u32> {
+        let position_id = assert_valid_user_stake_pda(
+            &crate::ID,
+            &self.user_stake_position.seed_owner(),
+            Some(self.user_stake_position.position_id),
+            &self.user_stake_position.key(),
+        )?;
+        require_keys_eq!(
+            self.user_stake_position.owner,
+            self.owner.key(),
+            ArenaError::Unauthorized
+        );
+        if self.user_account.owner == Pubkey::default() {
+            self.user_account.owner = self.owner.key();
         }
+        require_keys_eq!(
+            self.user_account.owner,
+            self.owner.key(),
+            ArenaError::Unauthorized
+        );
+        Ok(position_id)
     }
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
-pub struct Achievements {
-    pub first_mine: bool,
-    pub first_stake: bool,
-    pub seven_day_streak: bool,
-    pub heavy_operator: bool,
-}
-
-#[account]
-pub struct UserAccount {
-    pub owner: Pubkey,
-    pub daily_points: u32,
-    pub lifetime_points: u64,
-    pub boost_points: u64,
-    pub mining_xnt_spent: u64,
-    pub mining_tokens_mined: u64,
-    pub mining_runs_by_rig: [u64; 4],
-    pub mining_crit_count: u64,
-    pub staking_xnt_earned: u64,
-    pub last_day_id: i64,
-    pub achievements: Achievements,
-    pub next_position_id: u32,
-    pub active_boosts: Vec<UserBoost>,
-}
-
-impl UserAccount {
-    pub const LEN: usize = 32  // owner
-        + 4                   // daily_points
-        + 8                   // lifetime_points
-        + 8                   // boost_points
-        + 8                   // mining_xnt_spent
-        + 8                   // mining_tokens_mined
-        + 32                  // mining_runs_by_rig
-        + 8                   // mining_crit_count
-        + 8                   // staking_xnt_earned
-        + 8                   // last_day_id
-        + 4                   // achievements
-        + 4                   // next_position_id
-        + 4 + MAX_ACTIVE_BOOSTS * UserBoost::LEN
-        + 12; // padding (keeps total size stable after adding next_position_id)
 
-    pub fn purge_expired(&mut self, now: i64) {
-        self.active_boosts.retain(|b| !b.is_expired(now));
+    /// As `validate_position`, but the signer must be the owner or their
+    /// claim delegate.
+    fn validate(&mut self) -> Result<u32> {
+        let position_id = self.validate_position()?;
+        require!(
+            self.user_account
+                .is_owner_or_delegate(&self.authority.key()),
+            ArenaError::Unauthorized
+        );
+        Ok(position_id)
     }
 }
 
-#[account]
-pub struct BoostConfig {
-    pub id: u8,
-    pub kind: BoostKind,
-    pub cost_boost_points: u64,
-    pub value_bps: u16,
-    pub duration_seconds: i64,
-    pub rig_id: Option<u8>,
-}
-
-impl BoostConfig {
-    pub const LEN: usize = 1 + 1 + 8 + 2 + 8 + 2 + 8;
+#[derive(Accounts)]
+pub struct ApplyRankingResults<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    /// CHECK: user to reward
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        bump,
+        payer = admin,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    pub system_program: Program<'info, System>,
 }
 
-// -------------------------------------
-// Account Contexts
-// -------------------------------------
-
 #[derive(Accounts)]
-#[instruction(args: InitializeArgs)]
-pub struct InitializeGlobal<'info> {
+#[instruction(args: RankingEpochArgs)]
+pub struct PublishRankingEpoch<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
     #[account(
         init,
-        seeds = [GLOBAL_CONFIG_SEED],
+        seeds = [RANKING_EPOCH_SEED, &args.epoch_id.to_le_bytes()],
         bump,
-        payer = payer,
-        space = 8 + GlobalConfig::LEN
+        payer = admin,
+        space = 8 + RankingEpoch::space(args.leaf_count)
     )]
-    pub global_config: Box<Account<'info, GlobalConfig>>,
+    pub ranking_epoch: Box<Account<'info, RankingEpoch>>,
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+#[instruction(epoch_id: u64)]
+pub struct ClaimRankingReward<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
-
-    /// XNT mint must already exist.
-    pub xnt_mint: Box<Account<'info, Mint>>,
-
+    pub user: Signer<'info>,
+    #[account(mut, seeds = [RANKING_EPOCH_SEED, &epoch_id.to_le_bytes()], bump)]
+    pub ranking_epoch: Box<Account<'info, RankingEpoch>>,
     #[account(
-        init,
-        payer = payer,
-        mint::decimals = GAME_DECIMALS,
-        mint::authority = global_config,
-        mint::freeze_authority = global_config
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        bump,
+        payer = user,
+        space = UserAccount::init_space(user_account)
     )]
-    pub game_mint: Box<Account<'info, Mint>>,
-
+    pub user_account: Account<'info, UserAccount>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeTreasuryVault<'info> {
+#[instruction(epoch_id: u64)]
+pub struct CloseRankingEpoch<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
-    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump, has_one = xnt_mint)]
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
-    #[account(mut, address = global_config.xnt_mint)]
-    pub xnt_mint: Box<Account<'info, Mint>>,
     #[account(
-        init,
-        payer = admin,
-        token::mint = xnt_mint,
-        token::authority = global_config
+        mut,
+        seeds = [RANKING_EPOCH_SEED, &epoch_id.to_le_bytes()],
+        bump,
+        close = admin
     )]
-    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
+    pub ranking_epoch: Box<Account<'info, RankingEpoch>>,
+}
 
+#[derive(Accounts)]
+pub struct ResetDailyPoints<'info> {
+    /// Pays to grow a user account from the original layout.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    /// CHECK: user to reset
+    pub user: UncheckedAccount<'info>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, user.key().as_ref()], bump)]
+    pub user_account: Account<'info, UserAccount>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeStakingPool<'info> {
-    #[account(mut)]
+pub struct SetDayOffset<'info> {
     pub admin: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [GLOBAL_CONFIG_SEED],
-        bump,
-        has_one = game_mint,
-        has_one = xnt_mint
-    )]
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
+}
 
+#[derive(Accounts)]
+pub struct InitializeLeaderboard<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
     #[account(
         init,
-        seeds = [STAKING_POOL_SEED],
+        seeds = [LEADERBOARD_SEED],
         bump,
         payer = admin,
-        space = 8 + StakingPool::LEN
-    )]
-    pub staking_pool: Box<Account<'info, StakingPool>>,
-
-    #[account(mut, address = global_config.game_mint)]
-    pub game_mint: Box<Account<'info, Mint>>,
-    #[account(address = global_config.xnt_mint)]
-    pub xnt_mint: Box<Account<'info, Mint>>,
-
-    #[account(
-        init,
-        payer = admin,
-        token::mint = game_mint,
-        token::authority = staking_pool
+        space = 8 + Leaderboard::LEN
     )]
-    pub staking_vault: Box<Account<'info, TokenAccount>>,
-
+    pub leaderboard: Box<Account<'info, Leaderboard>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateHalving<'info> {
+pub struct FinalizeLeaderboard<'info> {
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [LEADERBOARD_SEED], bump)]
+    pub leaderboard: Box<Account<'info, Leaderboard>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureBoostStacking<'info> {
     pub admin: Signer<'info>,
     #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 #[derive(Accounts)]
-pub struct MineWithRig<'info> {
+#[instruction(args: BoostConfigArgs)]
+pub struct UpsertBoostConfig<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
-    #[account(
-        mut,
-        seeds = [STAKING_POOL_SEED],
-        bump,
-        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault,
-        constraint = staking_pool.token_mint == global_config.game_mint
-    )]
-    pub staking_pool: Box<Account<'info, StakingPool>>,
-    #[account(mut, address = global_config.game_mint)]
-    pub game_mint: Box<Account<'info, Mint>>,
-    #[account(mut, constraint = user_game_account.mint == game_mint.key(), constraint = user_game_account.owner == payer.key())]
-    pub user_game_account: Box<Account<'info, TokenAccount>>,
-    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == payer.key())]
-    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
-    #[account(mut, address = global_config.treasury_xnt_vault)]
-    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
     #[account(
         init_if_needed,
-        seeds = [USER_ACCOUNT_SEED, payer.key().as_ref()],
+        seeds = [BOOST_CONFIG_SEED, &args.id.to_le_bytes()],
         bump,
-        payer = payer,
-        space = 8 + UserAccount::LEN
+        payer = admin,
+        space = 8 + BoostConfig::LEN
     )]
-    pub user_account: Box<Account<'info, UserAccount>>,
-    pub token_program: Program<'info, Token>,
+    pub boost_config: Account<'info, BoostConfig>,
     pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct Stake<'info> {
+pub struct ActivateBoost<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub user: Signer<'info>,
     #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [BOOST_CONFIG_SEED, &boost_config.id.to_le_bytes()], bump)]
+    pub boost_config: Account<'info, BoostConfig>,
     #[account(
-        mut,
-        seeds = [STAKING_POOL_SEED],
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
         bump,
-        constraint = staking_pool.token_mint == global_config.game_mint,
-        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+        payer = user,
+        space = UserAccount::init_space(user_account)
     )]
-    pub staking_pool: Box<Account<'info, StakingPool>>,
-    #[account(mut, address = staking_pool.staking_vault)]
-    pub staking_vault: Box<Account<'info, TokenAccount>>,
-    #[account(mut, address = staking_pool.treasury_xnt_vault)]
-    pub treasury_xnt_vault: Box<Account<'info, TokenAccount>>,
-    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
-    pub user_game_account: Box<Account<'info, TokenAccount>>,
-    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
-    pub user_xnt_account: Box<Account<'info, TokenAccount>>,
+    pub user_account: Account<'info, UserAccount>,
     #[account(
         init_if_needed,
-        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        seeds = [BOOST_RECEIPT_SEED, user.key().as_ref(), &boost_config.id.to_le_bytes()],
         bump,
-        payer = owner,
-        space = 8 + UserAccount::LEN
+        payer = user,
+        space = 8 + BoostReceipt::LEN
     )]
-    pub user_account: Box<Account<'info, UserAccount>>,
+    pub boost_receipt: Account<'info, BoostReceipt>,
+    pub system_program: Program<'info, System>,
+    // Only needed when paying with GAME or XNT.
     #[account(
-        init,
-        seeds = [
-            USER_STAKE_SEED,
-            owner.key().as_ref(),
-            &user_account.next_position_id.max(1).to_le_bytes()
-        ],
+        mut,
+        seeds = [STAKING_POOL_SEED],
         bump,
-        payer = owner,
-        space = 8 + UserStakePosition::LEN
+        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
     )]
-    pub user_stake_position: Box<Account<'info, UserStakePosition>>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
+    pub staking_pool: Option<Box<Account<'info, StakingPool>>>,
+    #[account(mut, address = global_config.game_mint)]
+    pub game_mint: Option<Box<Account<'info, Mint>>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == user.key())]
+    pub user_game_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == user.key())]
+    pub user_xnt_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, address = global_config.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
-pub struct Claim<'info> {
+pub struct PurchaseBoost<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub user: Signer<'info>,
     #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut, seeds = [BOOST_CONFIG_SEED, &boost_config.id.to_le_bytes()], bump)]
+    pub boost_config: Account<'info, BoostConfig>,
+    #[account(
+        init_if_needed,
+        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        bump,
+        payer = user,
+        space = UserAccount::init_space(user_account)
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    #[account(
+        init_if_needed,
+        seeds = [BOOST_RECEIPT_SEED, user.key().as_ref(), &boost_config.id.to_le_bytes()],
+        bump,
+        payer = user,
+        space = 8 + BoostReceipt::LEN
+    )]
+    pub boost_receipt: Account<'info, BoostReceipt>,
+    #[account(
+        init_if_needed,
+        seeds = [BOOST_INVENTORY_SEED, user.key().as_ref()],
+        bump,
+        payer = user,
+        space = 8 + BoostInventory::LEN
+    )]
+    pub boost_inventory: Account<'info, BoostInventory>,
+    pub system_program: Program<'info, System>,
+    // Only needed when paying with GAME or XNT.
     #[account(
         mut,
         seeds = [STAKING_POOL_SEED],
         bump,
         constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
     )]
-    pub staking_pool: Box<Account<'info, StakingPool>>,
-    #[account(mut, address = staking_pool.treasury_xnt_vault)]
-    pub treasury_xnt_vault: Account<'info, TokenAccount>,
-    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
-    pub user_xnt_account: Account<'info, TokenAccount>,
-    #[account(mut, owner = crate::ID)]
-    pub user_stake_position: Account<'info, UserStakePosition>,
-    pub token_program: Program<'info, Token>,
+    pub staking_pool: Option<Box<Account<'info, StakingPool>>>,
+    #[account(mut, address = global_config.game_mint)]
+    pub game_mint: Option<Box<Account<'info, Mint>>>,
+    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == user.key())]
+    pub user_game_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == user.key())]
+    pub user_xnt_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, address = global_config.treasury_xnt_vault)]
+    pub treasury_xnt_vault: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Option<Program<'info, Token>>,
 }
 
 #[derive(Accounts)]
-pub struct Unstake<'info> {
+pub struct ActivateFromInventory<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
-    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
-    pub global_config: Box<Account<'info, GlobalConfig>>,
+    pub user: Signer<'info>,
+    #[account(seeds = [BOOST_CONFIG_SEED, &boost_config.id.to_le_bytes()], bump)]
+    pub boost_config: Account<'info, BoostConfig>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, user.key().as_ref()], bump)]
+    pub user_account: Account<'info, UserAccount>,
     #[account(
         mut,
-        seeds = [STAKING_POOL_SEED],
+        seeds = [BOOST_INVENTORY_SEED, user.key().as_ref()],
         bump,
-        constraint = staking_pool.token_mint == global_config.game_mint,
-        constraint = staking_pool.treasury_xnt_vault == global_config.treasury_xnt_vault
+        constraint = boost_inventory.owner == user.key() @ ArenaError::Unauthorized
     )]
-    pub staking_pool: Box<Account<'info, StakingPool>>,
-    #[account(mut, address = staking_pool.staking_vault)]
-    pub staking_vault: Account<'info, TokenAccount>,
-    #[account(mut, address = staking_pool.treasury_xnt_vault)]
-    pub treasury_xnt_vault: Account<'info, TokenAccount>,
-    #[account(mut, constraint = user_game_account.mint == global_config.game_mint, constraint = user_game_account.owner == owner.key())]
-    pub user_game_account: Account<'info, TokenAccount>,
-    #[account(mut, constraint = user_xnt_account.mint == global_config.xnt_mint, constraint = user_xnt_account.owner == owner.key())]
-    pub user_xnt_account: Account<'info, TokenAccount>,
-    #[account(mut, owner = crate::ID, close = owner)]
-    pub user_stake_position: Account<'info, UserStakePosition>,
-    pub token_program: Program<'info, Token>,
+    pub boost_inventory: Account<'info, BoostInventory>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ApplyRankingResults<'info> {
+pub struct DeactivateBoost<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
-    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
-    pub global_config: Box<Account<'info, GlobalConfig>>,
-    /// CHECK: user to reward
-    pub user: UncheckedAccount<'info>,
-    #[account(
-        init_if_needed,
-        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
-        bump,
-        payer = admin,
-        space = 8 + UserAccount::LEN
-    )]
+    pub user: Signer<'info>,
+    #[account(seeds = [BOOST_CONFIG_SEED, &boost_config.id.to_le_bytes()], bump)]
+    pub boost_config: Option<Box<Account<'info, BoostConfig>>>,
+    #[account(mut, seeds = [USER_ACCOUNT_SEED, user.key().as_ref()], bump)]
     pub user_account: Account<'info, UserAccount>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ResetDailyPoints<'info> {
+pub struct GiftBoost<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
-    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
-    pub global_config: Box<Account<'info, GlobalConfig>>,
-    /// CHECK: user to reset
-    pub user: UncheckedAccount<'info>,
+    pub sender: Signer<'info>,
+    /// CHECK: any wallet can receive a gift
+    pub recipient: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [BOOST_INVENTORY_SEED, sender.key().as_ref()],
+        bump,
+        constraint = sender_inventory.owner == sender.key() @ ArenaError::Unauthorized
+    )]
+    pub sender_inventory: Account<'info, BoostInventory>,
     #[account(
         init_if_needed,
-        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        seeds = [BOOST_INVENTORY_SEED, recipient.key().as_ref()],
         bump,
-        payer = admin,
-        space = 8 + UserAccount::LEN
+        payer = sender,
+        space = 8 + BoostInventory::LEN
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub recipient_inventory: Account<'info, BoostInventory>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(args: BoostConfigArgs)]
-pub struct UpsertBoostConfig<'info> {
+#[instruction(legacy_id: u8)]
+pub struct MigrateBoostConfig<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
-    #[account(mut, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
+    /// CHECK: legacy layout may not deserialize; discriminator checked in handler
+    #[account(mut, owner = crate::ID, seeds = [BOOST_CONFIG_SEED, &[legacy_id]], bump)]
+    pub legacy_boost_config: UncheckedAccount<'info>,
     #[account(
-        init_if_needed,
-        seeds = [BOOST_CONFIG_SEED, &[args.id]],
+        init,
+        seeds = [BOOST_CONFIG_SEED, &u16::from(legacy_id).to_le_bytes()],
         bump,
         payer = admin,
         space = 8 + BoostConfig::LEN
@@ -957,42 +5680,123 @@ pub struct UpsertBoostConfig<'info> {
 }
 
 #[derive(Accounts)]
-pub struct ActivateBoost<'info> {
+pub struct CloseBoostConfig<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(seeds = [GLOBAL_CONFIG_SEED], bump)]
     pub global_config: Box<Account<'info, GlobalConfig>>,
-    pub boost_config: Account<'info, BoostConfig>,
     #[account(
-        init_if_needed,
-        seeds = [USER_ACCOUNT_SEED, user.key().as_ref()],
+        mut,
+        seeds = [BOOST_CONFIG_SEED, &boost_config.id.to_le_bytes()],
         bump,
-        payer = user,
-        space = 8 + UserAccount::LEN
+        close = admin
     )]
-    pub user_account: Account<'info, UserAccount>,
-    pub system_program: Program<'info, System>,
+    pub boost_config: Account<'info, BoostConfig>,
+}
+
+// -------------------------------------
+// Types and helpers
+// -------------------------------------
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InitializeArgs {
+    pub admin: Pubkey,
+    pub staking_share_bps: u16,
+    pub halving_interval: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct StakingPoolConfigArgs {
+    pub reward_duration_secs: Option<i64>,
+    pub unbonding_period_secs: Option<i64>,
+    pub warmup_period_secs: Option<i64>,
+    pub max_position_amount: Option<u64>,
+    pub max_user_effective_stake: Option<u128>,
+    pub max_user_share_bps: Option<u16>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct BoostStackingArgs {
+    pub modes: Option<[StackingMode; MAX_BOOST_KINDS]>,
+    pub max_multiplier_bps: Option<u32>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RankingEpochArgs {
+    pub epoch_id: u64,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub total_points: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BoostConfigArgs {
+    pub id: u16,
+    pub kind: BoostKind,
+    pub cost_boost_points: Option<u64>,
+    pub value_bps: u16,
+    pub duration_seconds: i64,
+    pub rig_id: Option<u8>,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub max_supply: u64,
+    pub per_user_limit: u32,
+    pub enabled: bool,
+    pub price_game: Option<u64>,
+    pub price_xnt: Option<u64>,
+    pub charges: u32,
+    pub refundable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BoostChargeUsed {
+    pub boost_id: u16,
+    pub charges_remaining: u32,
+}
+
+/// Currency used to buy a boost.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoostPayment {
+    #[default]
+    BoostPoints,
+    /// Burned on purchase.
+    Game,
+    /// Split between stakers and the treasury like a mining deposit.
+    Xnt,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TreasuryPolicyArgs {
+    pub budget_per_period: u64,
+    pub period_secs: i64,
+    pub timelock_secs: i64,
+}
+
+impl TreasuryPolicyArgs {
+    fn validate(&self) -> Result<()> {
+        require!(self.period_secs > 0, ArenaError::InvalidDuration);
+        require!(
+            self.timelock_secs >= MIN_TREASURY_TIMELOCK_SECS,
+            ArenaError::TimelockTooShort
+        );
+        Ok(())
+    }
 }
 
-// -------------------------------------
-// Types and helpers
-// -------------------------------------
-
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct InitializeArgs {
-    pub admin: Pubkey,
-    pub staking_share_bps: u16,
-    pub halving_interval: u64,
+pub struct TreasurySolvency {
+    pub balance: u64,
+    pub reserved: u128,
+    pub unreserved: u64,
+    pub deficit: u128,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct BoostConfigArgs {
-    pub id: u8,
-    pub kind: BoostKind,
-    pub cost_boost_points: u64,
-    pub value_bps: u16,
-    pub duration_seconds: i64,
-    pub rig_id: Option<u8>,
+pub struct VotingPower {
+    pub user: Pubkey,
+    pub slot: u64,
+    pub user_power: u128,
+    pub total_power: u128,
 }
 
 #[derive(Clone, Debug)]
@@ -1047,137 +5851,1134 @@ impl RigConfig {
             .unwrap_or(0)
     }
 
-    pub fn reward_high(&self, halving_level: u8) -> u64 {
-        self.base_reward_high
-            .checked_shr(halving_level as u32)
-            .unwrap_or(0)
+    pub fn reward_high(&self, halving_level: u8) -> u64 {
+        self.base_reward_high
+            .checked_shr(halving_level as u32)
+            .unwrap_or(0)
+    }
+}
+
+fn compute_reward_amount(
+    rig: &RigConfig,
+    halving_level: u8,
+    prob_high_bps: u16,
+    user: &Signer,
+    clock: &Clock,
+    rig_id: u8,
+) -> Result<u64> {
+    let entropy = keccak::hashv(&[
+        &clock.slot.to_le_bytes(),
+        &clock.unix_timestamp.to_le_bytes(),
+        user.key.as_ref(),
+        &[rig_id],
+    ]);
+    let roll = u16::from_le_bytes([entropy.0[0], entropy.0[1]]) % 10_000;
+    let reward = if roll < prob_high_bps {
+        rig.reward_high(halving_level)
+    } else {
+        rig.reward_low(halving_level)
+    };
+    Ok(reward)
+}
+
+/// Combines the user's live boosts of `kind` into one multiplier (bps)
+/// according to the configured stacking mode, then applies the global cap.
+/// With `rig_id` set, boosts targeting another rig are skipped. Bonus kinds
+/// (see `BoostKind::is_bonus`) are combined as multipliers of
+/// `10_000 + value_bps`.
+fn combined_boost_bps(
+    global: &GlobalConfig,
+    user: &UserAccount,
+    kind: BoostKind,
+    rig_id: Option<u8>,
+    now: i64,
+) -> u128 {
+    let mode = global.stacking_mode(&kind);
+    let mut mult = 10_000u128;
+    for boost in user.active_boosts.iter() {
+        let applied = if kind.is_staking() {
+            boost.applied_to_staking
+        } else {
+            boost.applied_to_mining
+        };
+        if !applied || boost.is_expired(now) || boost.kind != kind {
+            continue;
+        }
+        if rig_id.is_some_and(|id| !boost.applies_to_rig(id)) {
+            continue;
+        }
+        let value = if kind.is_bonus() {
+            10_000 + boost.value_bps as u128
+        } else {
+            boost.value_bps as u128
+        };
+        mult = match mode {
+            StackingMode::Additive => mult.saturating_add(value.saturating_sub(10_000)),
+            StackingMode::HighestOnly => mult.max(value),
+            _ => mult.saturating_mul(value) / 10_000u128,
+        };
+    }
+    if global.max_boost_multiplier_bps > 0 {
+        mult = mult.min(global.max_boost_multiplier_bps as u128);
+    }
+    mult
+}
+
+fn apply_mining_reward_boost(
+    global: &GlobalConfig,
+    user: &mut UserAccount,
+    base: u64,
+    rig_id: u8,
+    now: i64,
+) -> u128 {
+    let mult = combined_boost_bps(global, user, BoostKind::MiningRewardBps, Some(rig_id), now);
+    (base as u128).saturating_mul(mult) / 10_000u128
+}
+
+fn apply_mining_point_boost(
+    global: &GlobalConfig,
+    user: &mut UserAccount,
+    base: u128,
+    rig_id: u8,
+    now: i64,
+) -> u128 {
+    let mult = combined_boost_bps(global, user, BoostKind::MiningPointsBps, Some(rig_id), now);
+    base.saturating_mul(mult) / 10_000u128
+}
+
+fn resolve_staking_multiplier(global: &GlobalConfig, user: &UserAccount, now: i64) -> u16 {
+    let mult = combined_boost_bps(global, user, BoostKind::StakingMultiplierBps, None, now);
+    mult.min(u16::MAX as u128) as u16
+}
+
+/// Whether `authority` may refresh a position from `stored_bps` to
+/// `current_bps`: the owner and their claim delegate always, anyone else
+/// only to lower it.
+fn may_refresh(
+    user_account: &UserAccount,
+    authority: &Pubkey,
+    stored_bps: u16,
+    current_bps: u16,
+) -> bool {
+    user_account.is_owner_or_delegate(authority) || current_bps < stored_bps
+}
+
+/// Combined bonus (bps above 1x) of a bonus kind such as a discount.
+fn boost_bonus_bps(
+    global: &GlobalConfig,
+    user: &UserAccount,
+    kind: BoostKind,
+    rig_id: Option<u8>,
+    now: i64,
+) -> u128 {
+    combined_boost_bps(global, user, kind, rig_id, now).saturating_sub(10_000)
+}
+
+/// Lock length for `lock_days` after `LockReductionBps` boosts, never
+/// shorter than `MIN_LOCK_SECS`.
+fn reduced_lock_secs(lock_days: u16, reduction_bps: u128) -> i64 {
+    let full = (lock_days as i64) * 86_400;
+    let reduced = (full as u128) * 10_000u128.saturating_sub(reduction_bps) / 10_000u128;
+    (reduced as i64).max(MIN_LOCK_SECS.min(full))
+}
+
+/// Enforces a boost's sale window, supply and per-user limit and records
+/// one sale.
+fn record_boost_sale(
+    boost_config: &mut BoostConfig,
+    receipt: &mut BoostReceipt,
+    user: Pubkey,
+    now: i64,
+) -> Result<()> {
+    require!(boost_config.is_available(now), ArenaError::BoostUnavailable);
+    require!(
+        boost_config.max_supply == 0 || boost_config.sold < boost_config.max_supply,
+        ArenaError::BoostSoldOut
+    );
+    if receipt.user == Pubkey::default() {
+        receipt.user = user;
+        receipt.boost_id = boost_config.id;
+    }
+    require!(
+        boost_config.per_user_limit == 0 || receipt.purchased < boost_config.per_user_limit,
+        ArenaError::BoostPurchaseLimitReached
+    );
+    boost_config.sold = boost_config.sold.saturating_add(1);
+    receipt.purchased = receipt.purchased.saturating_add(1);
+    Ok(())
+}
+
+/// Starts `boost_config` on the user and returns its expiry (0 = no expiry).
+fn push_user_boost(
+    user_account: &mut UserAccount,
+    boost_config: &BoostConfig,
+    refundable_points: u64,
+    now: i64,
+) -> Result<i64> {
+    require!(
+        user_account.active_boosts.len() < MAX_ACTIVE_BOOSTS,
+        ArenaError::TooManyActiveBoosts
+    );
+    let expires_at = if boost_config.duration_seconds > 0 {
+        now.saturating_add(boost_config.duration_seconds)
+    } else {
+        0
+    };
+    user_account.active_boosts.push(UserBoost {
+        boost_id: boost_config.id,
+        kind: boost_config.kind.clone(),
+        value_bps: boost_config.value_bps,
+        expires_at_ts: expires_at,
+        rig_id: boost_config.rig_id,
+        applied_to_mining: !boost_config.kind.is_staking(),
+        applied_to_staking: boost_config.kind.is_staking(),
+        charges: boost_config.initial_charges(),
+        refundable_points,
+        activated_at_ts: now,
+    });
+    Ok(expires_at)
+}
+
+/// Leaf of a ranking epoch's merkle tree.
+fn ranking_leaf(index: u32, user: &Pubkey, boost_points: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        &index.to_le_bytes(),
+        user.as_ref(),
+        &boost_points.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Walks `proof` from `leaf` up, hashing each sorted pair, and compares the
+/// result with `root`.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
+
+/// Records `user`'s daily total on the leaderboard. Once
+/// `initialize_leaderboard` has run every mining run must pass it, so no
+/// miner is left off.
+fn record_leaderboard_points(
+    global: &GlobalConfig,
+    leaderboard: Option<&mut Leaderboard>,
+    now: i64,
+    user: Pubkey,
+    points: u32,
+) -> Result<()> {
+    match leaderboard {
+        Some(leaderboard) => update_leaderboard(leaderboard, global.day_id(now), user, points),
+        None => require!(
+            !global.leaderboard_initialized,
+            ArenaError::LeaderboardRequired
+        ),
+    }
+    Ok(())
+}
+
+/// Rolls the leaderboard over if needed, then records `user`'s daily total.
+/// Totals from a day before the current table are dropped.
+fn update_leaderboard(leaderboard: &mut Leaderboard, day_id: i64, user: Pubkey, points: u32) {
+    if leaderboard.roll_over(day_id) {
+        emit!(LeaderboardFinalizedEvent {
+            day_id: leaderboard.previous_day_id,
+            entries: leaderboard.previous.clone(),
+        });
+    }
+    if day_id == leaderboard.day_id {
+        leaderboard.record(user, points);
+    }
+}
+
+/// Uses one charge of every live charge-based mining boost that changed a
+/// run on `rig_id`. Under `HighestOnly` only the boost that won for its kind
+/// counts, and rig cost discounts only count when the run was paid for.
+/// Free rig tickets are consumed separately.
+fn consume_mining_charges(
+    global: &GlobalConfig,
+    user_account: &mut UserAccount,
+    rig_id: u8,
+    paid: bool,
+    now: i64,
+) -> Vec<BoostChargeUsed> {
+    let mut applied = vec![false; user_account.active_boosts.len()];
+    for kind in [
+        BoostKind::MiningRewardBps,
+        BoostKind::MiningPointsBps,
+        BoostKind::RigCostDiscountBps,
+        BoostKind::CritChanceBps,
+    ] {
+        if kind == BoostKind::RigCostDiscountBps && !paid {
+            continue;
+        }
+        let live = user_account
+            .active_boosts
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| {
+                b.kind == kind
+                    && b.applied_to_mining
+                    && !b.is_expired(now)
+                    && b.applies_to_rig(rig_id)
+            });
+        if global.stacking_mode(&kind) == StackingMode::HighestOnly {
+            // Ties go to the first boost, as in `combined_boost_bps`.
+            let winner = live.fold(None, |best: Option<(usize, u16)>, (i, b)| match best {
+                Some((_, value)) if value >= b.value_bps => best,
+                _ => Some((i, b.value_bps)),
+            });
+            if let Some((i, _)) = winner {
+                applied[i] = true;
+            }
+        } else {
+            live.for_each(|(i, _)| applied[i] = true);
+        }
+    }
+
+    let mut used = Vec::new();
+    let mut i = 0;
+    for applied in applied {
+        let boost = &user_account.active_boosts[i];
+        if applied && boost.charges > 0 {
+            let boost_id = boost.boost_id;
+            let charges_remaining = user_account.consume_boost_charge(i);
+            used.push(BoostChargeUsed {
+                boost_id,
+                charges_remaining,
+            });
+            if charges_remaining == 0 {
+                continue;
+            }
+        }
+        i += 1;
+    }
+    used
+}
+
+/// Token accounts used by boost purchases paid in GAME or XNT.
+struct BoostPaymentAccounts<'a, 'info> {
+    user: &'a Signer<'info>,
+    staking_pool: Option<&'a mut Account<'info, StakingPool>>,
+    game_mint: Option<&'a Account<'info, Mint>>,
+    user_game_account: Option<&'a Account<'info, TokenAccount>>,
+    user_xnt_account: Option<&'a Account<'info, TokenAccount>>,
+    treasury_xnt_vault: Option<&'a Account<'info, TokenAccount>>,
+    token_program: Option<&'a Program<'info, Token>>,
+}
+
+/// Charges the boost price in the chosen currency and returns the amount
+/// paid. GAME is burned; XNT goes to the treasury with the staker share
+/// funded exactly like a mining deposit. Currencies without a price are
+/// rejected; free ones need no token accounts.
+fn collect_boost_payment(
+    payment: BoostPayment,
+    boost_config: &BoostConfig,
+    user_account: &mut UserAccount,
+    global: &mut GlobalConfig,
+    accounts: BoostPaymentAccounts,
+    now: i64,
+) -> Result<u64> {
+    match payment {
+        BoostPayment::BoostPoints => {
+            let price = boost_config
+                .cost_boost_points
+                .ok_or(ArenaError::PaymentNotAccepted)?;
+            require!(
+                user_account.boost_points >= price,
+                ArenaError::InsufficientBoostPoints
+            );
+            user_account.boost_points = user_account.boost_points.saturating_sub(price);
+            Ok(price)
+        }
+        BoostPayment::Game => {
+            let price = boost_config
+                .price_game
+                .ok_or(ArenaError::PaymentNotAccepted)?;
+            if price == 0 {
+                return Ok(0);
+            }
+            let (Some(game_mint), Some(user_game_account), Some(token_program)) = (
+                accounts.game_mint,
+                accounts.user_game_account,
+                accounts.token_program,
+            ) else {
+                return err!(ArenaError::MissingPaymentAccounts);
+            };
+            token::burn(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    Burn {
+                        mint: game_mint.to_account_info(),
+                        from: user_game_account.to_account_info(),
+                        authority: accounts.user.to_account_info(),
+                    },
+                ),
+                price,
+            )?;
+            global.total_burned = global.total_burned.saturating_add(price);
+            Ok(price)
+        }
+        BoostPayment::Xnt => {
+            let price = boost_config
+                .price_xnt
+                .ok_or(ArenaError::PaymentNotAccepted)?;
+            if price == 0 {
+                return Ok(0);
+            }
+            let (
+                Some(staking_pool),
+                Some(user_xnt_account),
+                Some(treasury_xnt_vault),
+                Some(token_program),
+            ) = (
+                accounts.staking_pool,
+                accounts.user_xnt_account,
+                accounts.treasury_xnt_vault,
+                accounts.token_program,
+            )
+            else {
+                return err!(ArenaError::MissingPaymentAccounts);
+            };
+            token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: user_xnt_account.to_account_info(),
+                        to: treasury_xnt_vault.to_account_info(),
+                        authority: accounts.user.to_account_info(),
+                    },
+                ),
+                price,
+            )?;
+            let to_stakers = (price as u128)
+                .checked_mul(global.staking_share_bps as u128)
+                .ok_or(ArenaError::MathOverflow)?
+                / 10_000u128;
+            fund_staker_rewards(staking_pool, to_stakers, now)?;
+            Ok(price)
+        }
+    }
+}
+
+fn calculate_effective(amount: u64, lock_mult: u16, boost_mult: u16) -> Result<u128> {
+    let amount_u128 = amount as u128;
+    let result = amount_u128
+        .checked_mul(lock_mult as u128)
+        .ok_or(ArenaError::MathOverflow)?
+        .checked_mul(boost_mult as u128)
+        .ok_or(ArenaError::MathOverflow)?
+        / 10_000u128
+        / 10_000u128;
+    Ok(result)
+}
+
+/// Amount-weighted lock multiplier of two merged positions, rounded down, so
+/// merging a small long lock into a large short one cannot upgrade the tier
+/// of the whole amount.
+fn merged_lock_multiplier(
+    amount_a: u64,
+    multiplier_a_bps: u16,
+    amount_b: u64,
+    multiplier_b_bps: u16,
+) -> Result<u16> {
+    let total = (amount_a as u128) + (amount_b as u128);
+    if total == 0 {
+        return Ok(multiplier_a_bps.max(multiplier_b_bps));
+    }
+    let weighted = (amount_a as u128) * (multiplier_a_bps as u128)
+        + (amount_b as u128) * (multiplier_b_bps as u128);
+    u16::try_from(weighted / total).map_err(|_| error!(ArenaError::MathOverflow))
+}
+
+fn lock_multiplier(lock_days: u16) -> Result<u16> {
+    LOCK_TIERS
+        .iter()
+        .find(|(days, _)| *days == lock_days)
+        .map(|(_, multiplier_bps)| *multiplier_bps)
+        .ok_or(error!(ArenaError::InvalidLock))
+}
+
+/// Books fresh treasury inflow as a staker liability and distributes it.
+fn fund_staker_rewards(staking_pool: &mut StakingPool, amount: u128, now: i64) -> Result<()> {
+    staking_pool.total_rewards_accrued = staking_pool
+        .total_rewards_accrued
+        .checked_add(amount)
+        .ok_or(ArenaError::MathOverflow)?;
+    distribute_staker_rewards(staking_pool, amount, now)
+}
+
+/// Books the liability from before solvency tracking existed: `unclaimed`
+/// pending rewards plus everything not yet credited. `unclaimed` cannot
+/// exceed what the accumulator has credited to the current stake.
+fn seed_reward_reserve(staking_pool: &mut StakingPool, unclaimed: u64) -> Result<()> {
+    let credited = staking_pool
+        .total_effective_stake
+        .checked_mul(staking_pool.acc_reward_per_share)
+        .ok_or(ArenaError::MathOverflow)?
+        / PRECISION;
+    require!(
+        unclaimed as u128 <= credited,
+        ArenaError::UnclaimedRewardsTooHigh
+    );
+    staking_pool.total_rewards_accrued = (unclaimed as u128)
+        .checked_add(staking_pool.uncredited_rewards())
+        .ok_or(ArenaError::MathOverflow)?;
+    staking_pool.total_rewards_claimed = 0;
+    Ok(())
+}
+
+/// Once the pool is empty every position has been settled, so anything
+/// still reserved beyond the uncredited rewards is per-position rounding
+/// that nobody can claim; stop reserving it.
+fn release_reward_dust(staking_pool: &mut StakingPool) {
+    if staking_pool.total_effective_stake != 0 {
+        return;
+    }
+    let owed = staking_pool
+        .total_rewards_claimed
+        .saturating_add(staking_pool.uncredited_rewards());
+    if staking_pool.total_rewards_accrued > owed {
+        emit!(RewardDustReleasedEvent {
+            amount: staking_pool.total_rewards_accrued - owed,
+        });
+        staking_pool.total_rewards_accrued = owed;
+    }
+}
+
+/// XNT in the treasury vault that is not owed to stakers.
+fn unreserved_treasury_balance(staking_pool: &StakingPool, vault_balance: u64) -> u64 {
+    let reserved = staking_pool.reserved_rewards();
+    (vault_balance as u128).saturating_sub(reserved) as u64
+}
+
+/// Credits `amount` of XNT to stakers. With a reward duration configured the
+/// amount is streamed over that period (leftovers of a running period are
+/// rolled in); otherwise it is added to `acc_reward_per_share` at once. When
+/// nobody is staked the amount is buffered in `pending_undistributed`.
+fn distribute_staker_rewards(staking_pool: &mut StakingPool, amount: u128, now: i64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    update_reward_stream(staking_pool, now)?;
+
+    if staking_pool.reward_duration_secs > 0 {
+        let leftover = if now < staking_pool.period_finish {
+            staking_pool
+                .reward_rate
+                .checked_mul((staking_pool.period_finish - now) as u128)
+                .ok_or(ArenaError::MathOverflow)?
+        } else {
+            0
+        };
+        staking_pool.reward_rate = amount
+            .checked_mul(PRECISION)
+            .ok_or(ArenaError::MathOverflow)?
+            .checked_add(leftover)
+            .ok_or(ArenaError::MathOverflow)?
+            / staking_pool.reward_duration_secs as u128;
+        staking_pool.period_finish = now.saturating_add(staking_pool.reward_duration_secs);
+        staking_pool.last_update_ts = now;
+        return Ok(());
+    }
+
+    credit_reward_per_share(
+        staking_pool,
+        amount
+            .checked_mul(PRECISION)
+            .ok_or(ArenaError::MathOverflow)?,
+    )
+}
+
+/// Advances the reward stream to `now`, crediting everything streamed since
+/// the last checkpoint. Must run before any change to effective stake.
+fn update_reward_stream(staking_pool: &mut StakingPool, now: i64) -> Result<()> {
+    let applicable = now.min(staking_pool.period_finish);
+    if applicable > staking_pool.last_update_ts && staking_pool.reward_rate > 0 {
+        let elapsed = (applicable - staking_pool.last_update_ts) as u128;
+        let streamed = staking_pool
+            .reward_rate
+            .checked_mul(elapsed)
+            .ok_or(ArenaError::MathOverflow)?;
+        credit_reward_per_share(staking_pool, streamed)?;
+    }
+    staking_pool.last_update_ts = now;
+    Ok(())
+}
+
+/// Adds `scaled_amount` (XNT scaled by `PRECISION`) to the pool's
+/// accumulator, or buffers it when there is no effective stake. Whatever
+/// the division leaves over is kept in `reward_dust` for the next credit.
+fn credit_reward_per_share(staking_pool: &mut StakingPool, scaled_amount: u128) -> Result<()> {
+    let scaled_amount = scaled_amount
+        .checked_add(staking_pool.reward_dust)
+        .ok_or(ArenaError::MathOverflow)?;
+    if staking_pool.total_effective_stake == 0 {
+        let amount_u64 =
+            u64::try_from(scaled_amount / PRECISION).map_err(|_| ArenaError::MathOverflow)?;
+        staking_pool.pending_undistributed = staking_pool
+            .pending_undistributed
+            .checked_add(amount_u64)
+            .ok_or(ArenaError::MathOverflow)?;
+        staking_pool.reward_dust = scaled_amount % PRECISION;
+        return Ok(());
+    }
+    staking_pool.acc_reward_per_share = staking_pool
+        .acc_reward_per_share
+        .checked_add(scaled_amount / staking_pool.total_effective_stake)
+        .ok_or(ArenaError::MathOverflow)?;
+    staking_pool.reward_dust = scaled_amount % staking_pool.total_effective_stake;
+    Ok(())
+}
+
+fn fold_pending_undistributed(staking_pool: &mut StakingPool, now: i64) -> Result<()> {
+    let pending = staking_pool.pending_undistributed;
+    if pending == 0 || staking_pool.total_effective_stake == 0 {
+        return Ok(());
+    }
+    staking_pool.pending_undistributed = 0;
+    distribute_staker_rewards(staking_pool, pending as u128, now)?;
+    emit!(PendingRewardsDistributedEvent {
+        amount: pending,
+        total_effective_stake: staking_pool.total_effective_stake,
+    });
+    Ok(())
+}
+
+fn settle_rewards<'info>(
+    staking_pool: &mut StakingPool,
+    user_stake: &mut UserStakePosition,
+    global: &Account<'info, GlobalConfig>,
+    treasury_xnt_vault: &Account<'info, TokenAccount>,
+    user_xnt_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    global_bump: u8,
+) -> Result<u64> {
+    let pending = harvest_position(staking_pool, user_stake)?;
+    pay_staker_rewards(
+        staking_pool,
+        pending,
+        global,
+        treasury_xnt_vault,
+        user_xnt_account,
+        token_program,
+        global_bump,
+    )?;
+    Ok(pending)
+}
+
+/// Computes the position's pending rewards and resets its reward debt
+/// without moving any tokens.
+fn harvest_position(staking_pool: &StakingPool, user_stake: &mut UserStakePosition) -> Result<u64> {
+    let pending = pending_rewards(staking_pool.acc_reward_per_share, user_stake)?;
+    user_stake.reward_debt = user_stake
+        .effective_stake
+        .checked_mul(staking_pool.acc_reward_per_share)
+        .ok_or(ArenaError::MathOverflow)?
+        / PRECISION;
+    Ok(pending)
+}
+
+fn pay_staker_rewards<'info>(
+    staking_pool: &mut StakingPool,
+    amount: u64,
+    global: &Account<'info, GlobalConfig>,
+    treasury_xnt_vault: &Account<'info, TokenAccount>,
+    user_xnt_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    global_bump: u8,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    require!(
+        treasury_xnt_vault.amount >= amount,
+        ArenaError::TreasuryInsolvent
+    );
+    staking_pool.total_rewards_claimed = staking_pool
+        .total_rewards_claimed
+        .checked_add(amount as u128)
+        .ok_or(ArenaError::MathOverflow)?;
+    let signer_seeds: &[&[u8]] = &[GLOBAL_CONFIG_SEED, &[global_bump]];
+    let signer_arr: &[&[&[u8]]] = &[signer_seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        Transfer {
+            from: treasury_xnt_vault.to_account_info(),
+            to: user_xnt_account.to_account_info(),
+            authority: global.to_account_info(),
+        },
+        signer_arr,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+fn pending_rewards(acc_reward_per_share: u128, user_stake: &UserStakePosition) -> Result<u64> {
+    let accumulated = user_stake
+        .effective_stake
+        .checked_mul(acc_reward_per_share)
+        .ok_or(ArenaError::MathOverflow)?
+        / PRECISION;
+    let pending = accumulated.saturating_sub(user_stake.reward_debt);
+    Ok(pending.min(u64::MAX as u128) as u64)
+}
+
+/// Replaces the position's effective stake, and the `target` it warms up
+/// to, and keeps the pool and user totals (and their checkpoints) in sync.
+/// Pending rewards must be harvested beforehand; the reward debt is reset
+/// against the current accumulator.
+fn set_effective_stake(
+    staking_pool: &mut StakingPool,
+    user_account: &mut UserAccount,
+    position: &mut UserStakePosition,
+    effective: u128,
+    target: u128,
+    slot: u64,
+) -> Result<()> {
+    let warming = target.saturating_sub(effective);
+    staking_pool.record_warming_stake_change(position.warming_stake, warming)?;
+    user_account.record_warming_stake_change(position.warming_stake, warming)?;
+    position.warming_stake = warming;
+    staking_pool.total_effective_stake = staking_pool
+        .total_effective_stake
+        .saturating_sub(position.effective_stake)
+        .checked_add(effective)
+        .ok_or(ArenaError::MathOverflow)?;
+    staking_pool
+        .checkpoints
+        .record(slot, staking_pool.total_effective_stake);
+    release_reward_dust(staking_pool);
+    user_account.record_effective_stake_change(position.effective_stake, effective, slot)?;
+    position.effective_stake = effective;
+    position.reward_debt = effective
+        .checked_mul(staking_pool.acc_reward_per_share)
+        .ok_or(ArenaError::MathOverflow)?
+        / PRECISION;
+    Ok(())
+}
+
+/// Checks the pool's anti-whale caps for a position of `amount_staked` that
+/// adds `user_added` to the user's target stake (currently `user_total`)
+/// and `pool_added` to the pool's. Targets count stake still warming up at
+/// full weight. The share cap only binds once other stakers are present.
+fn enforce_stake_caps(
+    staking_pool: &StakingPool,
+    user_total: u128,
+    amount_staked: u64,
+    user_added: u128,
+    pool_added: u128,
+) -> Result<()> {
+    if staking_pool.max_position_amount > 0 {
+        require!(
+            amount_staked <= staking_pool.max_position_amount,
+            ArenaError::PositionCapExceeded
+        );
+    }
+    let user_after = user_total
+        .checked_add(user_added)
+        .ok_or(ArenaError::MathOverflow)?;
+    if staking_pool.max_user_effective_stake > 0 {
+        require!(
+            user_after <= staking_pool.max_user_effective_stake,
+            ArenaError::UserStakeCapExceeded
+        );
+    }
+    let pool_after = staking_pool
+        .target_effective_stake()
+        .checked_add(pool_added)
+        .ok_or(ArenaError::MathOverflow)?;
+    if staking_pool.max_user_share_bps > 0 && pool_after > user_after {
+        require!(
+            user_after
+                .checked_mul(10_000)
+                .ok_or(ArenaError::MathOverflow)?
+                <= pool_after
+                    .checked_mul(staking_pool.max_user_share_bps as u128)
+                    .ok_or(ArenaError::MathOverflow)?,
+            ArenaError::UserShareCapExceeded
+        );
+    }
+    Ok(())
+}
+
+/// Checks the stake caps for a liquid deposit of `amount` GAME that leaves
+/// the depositor's sGAME backed by `holder_game` GAME, counted at full
+/// weight on top of the target stake of their positions.
+fn liquid_deposit_caps_check(
+    staking_pool: &StakingPool,
+    user_account: &UserAccount,
+    amount: u64,
+    holder_game: u64,
+) -> Result<()> {
+    enforce_stake_caps(
+        staking_pool,
+        user_account.target_effective_stake(),
+        amount,
+        calculate_effective(holder_game, 10_000, 10_000)?,
+        calculate_effective(amount, 10_000, 10_000)?,
+    )
+}
+
+/// Re-derives the liquid vault's 1x effective stake from `total_game`, and
+/// its warming stake from `pending_game`, and updates the pool totals.
+/// Pending rewards must be harvested beforehand.
+fn set_liquid_vault_stake(
+    staking_pool: &mut StakingPool,
+    liquid_vault: &mut LiquidVault,
+    slot: u64,
+) -> Result<()> {
+    let effective = calculate_effective(liquid_vault.total_game, 10_000, 10_000)?;
+    let warming = calculate_effective(liquid_vault.pending_game, 10_000, 10_000)?;
+    staking_pool.record_warming_stake_change(liquid_vault.warming_stake, warming)?;
+    liquid_vault.warming_stake = warming;
+    staking_pool.total_effective_stake = staking_pool
+        .total_effective_stake
+        .saturating_sub(liquid_vault.effective_stake)
+        .checked_add(effective)
+        .ok_or(ArenaError::MathOverflow)?;
+    staking_pool
+        .checkpoints
+        .record(slot, staking_pool.total_effective_stake);
+    release_reward_dust(staking_pool);
+    liquid_vault.effective_stake = effective;
+    liquid_vault.reward_debt = effective
+        .checked_mul(staking_pool.acc_reward_per_share)
+        .ok_or(ArenaError::MathOverflow)?
+        / PRECISION;
+    Ok(())
+}
+
+/// Pays the liquid vault's pending rewards into its XNT account, where they
+/// belong to share holders pro rata.
+fn harvest_liquid_vault_rewards<'info>(
+    staking_pool: &mut StakingPool,
+    liquid_vault: &mut LiquidVault,
+    global: &Account<'info, GlobalConfig>,
+    treasury_xnt_vault: &Account<'info, TokenAccount>,
+    vault_xnt_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    global_bump: u8,
+) -> Result<u64> {
+    let accumulated = liquid_vault
+        .effective_stake
+        .checked_mul(staking_pool.acc_reward_per_share)
+        .ok_or(ArenaError::MathOverflow)?
+        / PRECISION;
+    let pending = accumulated
+        .saturating_sub(liquid_vault.reward_debt)
+        .min(u64::MAX as u128) as u64;
+    liquid_vault.reward_debt = accumulated;
+    if pending == 0 {
+        return Ok(0);
+    }
+    pay_staker_rewards(
+        staking_pool,
+        pending,
+        global,
+        treasury_xnt_vault,
+        vault_xnt_account,
+        token_program,
+        global_bump,
+    )?;
+    liquid_vault.add_xnt(pending)?;
+    liquid_vault.total_xnt_harvested = liquid_vault.total_xnt_harvested.saturating_add(pending);
+    emit!(LiquidVaultHarvestedEvent {
+        amount: pending,
+        total_xnt_harvested: liquid_vault.total_xnt_harvested,
+    });
+    Ok(pending)
+}
+
+/// Moves the XNT a depositor adds to match the shares minted.
+fn pay_liquid_xnt_in<'info>(
+    user_xnt_account: &Account<'info, TokenAccount>,
+    vault_xnt_account: &Account<'info, TokenAccount>,
+    owner: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: user_xnt_account.to_account_info(),
+                to: vault_xnt_account.to_account_info(),
+                authority: owner.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Pays out the XNT backing redeemed shares.
+fn pay_liquid_xnt_out<'info>(
+    liquid_vault: &Account<'info, LiquidVault>,
+    vault_xnt_account: &Account<'info, TokenAccount>,
+    user_xnt_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
     }
+    let vault_seeds: &[&[u8]] = &[LIQUID_VAULT_SEED, &[vault_bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault_xnt_account.to_account_info(),
+                to: user_xnt_account.to_account_info(),
+                authority: liquid_vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+    )
 }
 
-fn compute_reward_amount(
-    rig: &RigConfig,
-    halving_level: u8,
-    user: &Signer,
-    clock: &Clock,
-    rig_id: u8,
-) -> Result<u64> {
-    let entropy = keccak::hashv(&[
-        &clock.slot.to_le_bytes(),
-        &clock.unix_timestamp.to_le_bytes(),
-        user.key.as_ref(),
-        &[rig_id],
-    ]);
-    let roll = u16::from_le_bytes([entropy.0[0], entropy.0[1]]) % 10_000;
-    let reward = if roll < rig.prob_high_bps {
-        rig.reward_high(halving_level)
+/// `a * b / c` in u128, rounding up when `round_up` is set.
+fn mul_div(a: u64, b: u64, c: u64, round_up: bool) -> Result<u64> {
+    require!(c > 0, ArenaError::MathOverflow);
+    let product = (a as u128) * (b as u128);
+    let result = if round_up {
+        product.div_ceil(c as u128)
     } else {
-        rig.reward_low(halving_level)
+        product / c as u128
     };
-    Ok(reward)
+    u64::try_from(result).map_err(|_| ArenaError::MathOverflow.into())
 }
 
-fn apply_mining_reward_boost(user: &mut UserAccount, base: u64, now: i64) -> u128 {
-    let mut result = base as u128;
-    for boost in user.active_boosts.iter() {
-        if boost.applied_to_mining
-            && !boost.is_expired(now)
-            && matches!(boost.kind, BoostKind::MiningRewardBps)
-        {
-            result = result.saturating_mul(boost.value_bps as u128) / 10_000u128;
-        }
+/// Moves a warming-up position's effective stake to its current ramp value.
+/// Rewards must already be harvested at the previous effective stake. The
+/// stake is held where it is while the owner's target breaches a stake cap
+/// (say the cap was lowered since), and resumes warming once it fits.
+fn sync_warmup(
+    staking_pool: &mut StakingPool,
+    user_account: &mut UserAccount,
+    position: &mut UserStakePosition,
+    clock: &Clock,
+) -> Result<()> {
+    if !position.is_warming_up() || position.is_unbonding() {
+        return Ok(());
+    }
+    let full_effective = calculate_effective(
+        position.amount_staked,
+        position.lock_multiplier_bps,
+        position.boost_multiplier_bps,
+    )?;
+    let warmed = position.warmed_effective_stake(full_effective, clock.unix_timestamp);
+    if warmed > position.effective_stake
+        && warmup_caps_check(staking_pool, user_account, position).is_err()
+    {
+        return Ok(());
+    }
+    if clock.unix_timestamp >= position.warmup_end_ts {
+        position.warmup_start_ts = 0;
+        position.warmup_end_ts = 0;
+    }
+    if warmed != position.effective_stake || position.warming_stake != full_effective - warmed {
+        set_effective_stake(
+            staking_pool,
+            user_account,
+            position,
+            warmed,
+            full_effective,
+            clock.slot,
+        )?;
     }
-    result
+    Ok(())
 }
 
-fn apply_mining_point_boost(user: &mut UserAccount, base: u128, now: i64) -> u128 {
-    let mut result = base;
-    for boost in user.active_boosts.iter() {
-        if boost.applied_to_mining
-            && !boost.is_expired(now)
-            && matches!(boost.kind, BoostKind::MiningPointsBps)
-        {
-            result = result.saturating_mul(boost.value_bps as u128) / 10_000u128;
+/// Stake caps for letting `position` warm up further: the owner's and the
+/// pool's targets, which already include the position at full weight,
+/// must fit.
+fn warmup_caps_check(
+    staking_pool: &StakingPool,
+    user_account: &UserAccount,
+    position: &UserStakePosition,
+) -> Result<()> {
+    let target = position.target_stake();
+    enforce_stake_caps(
+        staking_pool,
+        user_account.target_effective_stake().saturating_sub(target),
+        position.amount_staked,
+        target,
+        0,
+    )
+}
+
+/// Records `effective_stake` as the value at the end of the checkpoint
+/// interval containing `slot`, overwriting the newest entry when it belongs
+/// to the same interval. Entries are keyed by the interval's first slot.
+fn push_checkpoint(
+    entries: &mut [StakeCheckpoint],
+    head: &mut u8,
+    count: &mut u8,
+    slot: u64,
+    effective_stake: u128,
+) {
+    let cap = entries.len();
+    let interval_start = slot - slot % CHECKPOINT_INTERVAL_SLOTS;
+    if *count > 0 {
+        let newest = (*head as usize + cap - 1) % cap;
+        if entries[newest].slot >= interval_start {
+            entries[newest].effective_stake = effective_stake;
+            return;
         }
     }
-    result
+    entries[*head as usize] = StakeCheckpoint {
+        slot: interval_start,
+        effective_stake,
+    };
+    *head = ((*head as usize + 1) % cap) as u8;
+    if (*count as usize) < cap {
+        *count += 1;
+    }
 }
 
-fn resolve_staking_multiplier(user: &UserAccount, now: i64) -> u16 {
-    let mut mult = 10_000u16;
-    for boost in user.active_boosts.iter() {
-        if boost.applied_to_staking
-            && !boost.is_expired(now)
-            && matches!(boost.kind, BoostKind::StakingMultiplierBps)
-        {
-            mult = mult.max(boost.value_bps);
+/// Value at the start of the checkpoint interval containing `slot`: the
+/// newest entry from an earlier interval. Changes made within the interval
+/// do not count, so stake moved in just before a snapshot carries no weight.
+/// Fails when the buffer has wrapped past `slot`, since the value there is
+/// no longer known.
+fn checkpoint_value_at(
+    entries: &[StakeCheckpoint],
+    head: u8,
+    count: u8,
+    slot: u64,
+) -> Result<u128> {
+    let cap = entries.len();
+    let interval_start = slot - slot % CHECKPOINT_INTERVAL_SLOTS;
+    for i in 1..=count as usize {
+        let entry = &entries[(head as usize + cap - i) % cap];
+        if entry.slot < interval_start {
+            return Ok(entry.effective_stake);
         }
     }
-    mult
+    require!((count as usize) < cap, ArenaError::CheckpointUnavailable);
+    Ok(0)
 }
 
-fn calculate_effective(amount: u64, lock_mult: u16, boost_mult: u16) -> Result<u128> {
-    let amount_u128 = amount as u128;
-    let result = amount_u128
-        .checked_mul(lock_mult as u128)
-        .ok_or(ArenaError::MathOverflow)?
-        .checked_mul(boost_mult as u128)
-        .ok_or(ArenaError::MathOverflow)?
-        / 10_000u128
-        / 10_000u128;
-    Ok(result)
+/// Checks that `slot` is not in the future and its checkpoint interval is
+/// one of the last `SNAPSHOT_WINDOW_INTERVALS`, which every checkpoint ring
+/// can still answer for.
+fn require_snapshot_in_window(slot: u64, current_slot: u64) -> Result<()> {
+    require!(slot <= current_slot, ArenaError::InvalidSlot);
+    let intervals_back =
+        current_slot / CHECKPOINT_INTERVAL_SLOTS - slot / CHECKPOINT_INTERVAL_SLOTS;
+    require!(
+        intervals_back < SNAPSHOT_WINDOW_INTERVALS,
+        ArenaError::CheckpointUnavailable
+    );
+    Ok(())
 }
 
-fn lock_multiplier(lock_days: u16) -> Result<u16> {
-    match lock_days {
-        7 => Ok(10_500),
-        14 => Ok(11_000),
-        30 => Ok(12_000),
-        _ => Err(error!(ArenaError::InvalidLock)),
-    }
+/// Grows `user_positions` so it can hold `additional` more entries than it
+/// does now, topping up rent from `payer`.
+fn reserve_position_entries<'info>(
+    user_positions: &Account<'info, UserPositions>,
+    additional: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    // Not `grow_account`: an index created in this instruction has no
+    // discriminator on-chain until it is written back.
+    resize_account(
+        &user_positions.to_account_info(),
+        payer,
+        system_program,
+        UserPositions::space_for(user_positions.positions.len() + additional),
+    )
 }
 
-fn settle_rewards<'info>(
-    staking_pool: &mut StakingPool,
-    user_stake: &mut UserStakePosition,
-    global: &Account<'info, GlobalConfig>,
-    treasury_xnt_vault: &Account<'info, TokenAccount>,
-    user_xnt_account: &Account<'info, TokenAccount>,
-    token_program: &Program<'info, Token>,
-    global_bump: u8,
-) -> Result<u64> {
-    let pending = pending_rewards(staking_pool.acc_reward_per_share, user_stake)?;
-    if pending > 0 {
-        let signer_seeds: &[&[u8]] = &[GLOBAL_CONFIG_SEED, &[global_bump]];
-        let signer_arr: &[&[&[u8]]] = &[signer_seeds];
-        let cpi_ctx = CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            Transfer {
-                from: treasury_xnt_vault.to_account_info(),
-                to: user_xnt_account.to_account_info(),
-                authority: global.to_account_info(),
-            },
-            signer_arr,
+/// Reallocates a program account created under an older, shorter layout up
+/// to `target_len`, topping up rent from `payer`. New bytes are zeroed.
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    discriminator: &[u8; 8],
+    target_len: usize,
+) -> Result<()> {
+    {
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == discriminator[..],
+            ArenaError::InvalidAccountData
         );
-        token::transfer(cpi_ctx, pending)?;
     }
+    resize_account(account, payer, system_program, target_len)
+}
 
-    user_stake.reward_debt = user_stake
-        .effective_stake
-        .checked_mul(staking_pool.acc_reward_per_share)
-        .ok_or(ArenaError::MathOverflow)?
-        / PRECISION;
+/// Grows a user account loaded from the original layout so the current one can
+/// be written back on exit. Call it from every handler that writes the
+/// account.
+fn upgrade_user_account<'info>(
+    user_account: &Account<'info, UserAccount>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    resize_account(
+        &user_account.to_account_info(),
+        payer,
+        system_program,
+        8 + UserAccount::LEN,
+    )
+}
 
-    Ok(pending)
+/// Grows `account` to `target_len` if it is shorter, topping up rent from
+/// `payer`. New bytes are zeroed.
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    target_len: usize,
+) -> Result<()> {
+    if account.data_len() >= target_len {
+        return Ok(());
+    }
+
+    let rent_due = Rent::get()?
+        .minimum_balance(target_len)
+        .saturating_sub(account.lamports());
+    if rent_due > 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(cpi_ctx, rent_due)?;
+    }
+    account.realloc(target_len, true)?;
+    Ok(())
 }
 
-fn pending_rewards(acc_reward_per_share: u128, user_stake: &UserStakePosition) -> Result<u64> {
-    let accumulated = user_stake
-        .effective_stake
-        .checked_mul(acc_reward_per_share)
-        .ok_or(ArenaError::MathOverflow)?
-        / PRECISION;
-    let pending = accumulated.saturating_sub(user_stake.reward_debt);
-    Ok(pending.min(u64::MAX as u128) as u64)
+/// Drains `account` into `destination` and hands it back to the system
+/// program, for accounts that cannot be loaded as `Account<T>`.
+fn close_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ArenaError::MathOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
 }
 
 fn assert_valid_user_stake_pda(
@@ -1195,11 +6996,7 @@ fn assert_valid_user_stake_pda(
 
     let position_id = maybe_position_id.unwrap();
     let (pda, _) = Pubkey::find_program_address(
-        &[
-            USER_STAKE_SEED,
-            owner.as_ref(),
-            &position_id.to_le_bytes(),
-        ],
+        &[USER_STAKE_SEED, owner.as_ref(), &position_id.to_le_bytes()],
         program_id,
     );
     require_keys_eq!(*stake_account_key, pda, ArenaError::InvalidStakePda);
@@ -1217,6 +7014,14 @@ pub struct MiningEvent {
     pub deposit_xnt: u64,
     pub reward_game: u64,
     pub used_free_rig: bool,
+    /// Charge-based boosts consumed by this run.
+    pub charges_used: Vec<BoostChargeUsed>,
+}
+
+#[event]
+pub struct LeaderboardFinalizedEvent {
+    pub day_id: i64,
+    pub entries: Vec<LeaderboardEntry>,
 }
 
 #[event]
@@ -1235,6 +7040,34 @@ pub struct UnstakeEvent {
     pub amount: u64,
 }
 
+#[event]
+pub struct UnbondStartedEvent {
+    pub owner: Pubkey,
+    pub position_id: u32,
+    pub amount: u64,
+    pub unbonding_until_ts: i64,
+}
+
+#[event]
+pub struct PositionsMergedEvent {
+    pub owner: Pubkey,
+    pub target_position_id: u32,
+    pub source_position_id: u32,
+    pub amount: u64,
+    pub lock_until_ts: i64,
+    pub effective: u128,
+    pub rewards_claimed: u64,
+}
+
+#[event]
+pub struct PositionSplitEvent {
+    pub owner: Pubkey,
+    pub source_position_id: u32,
+    pub new_position_id: u32,
+    pub amount: u64,
+    pub rewards_claimed: u64,
+}
+
 #[event]
 pub struct ClaimEvent {
     pub owner: Pubkey,
@@ -1242,11 +7075,212 @@ pub struct ClaimEvent {
     pub rewards_claimed: u64,
 }
 
+#[event]
+pub struct ClaimAllEvent {
+    pub owner: Pubkey,
+    pub positions: u16,
+    pub rewards_claimed: u64,
+}
+
+#[event]
+pub struct PositionTransferredEvent {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub position_id: u32,
+    pub rewards_settled: u64,
+}
+
+#[event]
+pub struct ClaimDelegateSetEvent {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct PositionsIndexedEvent {
+    pub owner: Pubkey,
+    pub positions: u16,
+}
+
+#[event]
+pub struct UserStakeSyncedEvent {
+    pub owner: Pubkey,
+    pub positions: u16,
+    pub total_effective_stake: u128,
+}
+
+#[event]
+pub struct WarmupAdvancedEvent {
+    pub owner: Pubkey,
+    pub position_id: u32,
+    pub effective: u128,
+    pub rewards_claimed: u64,
+}
+
+#[event]
+pub struct PositionRefreshedEvent {
+    pub owner: Pubkey,
+    pub position_id: u32,
+    pub boost_multiplier_bps: u16,
+    pub effective: u128,
+    pub rewards_claimed: u64,
+}
+
+#[event]
+pub struct TreasurySolvencyEvent {
+    pub balance: u64,
+    pub reserved: u128,
+    pub unreserved: u64,
+    pub deficit: u128,
+}
+
+#[event]
+pub struct RewardDustReleasedEvent {
+    pub amount: u128,
+}
+
+#[event]
+pub struct TreasuryPolicyQueuedEvent {
+    pub budget_per_period: u64,
+    pub period_secs: i64,
+    pub timelock_secs: i64,
+    pub executable_at_ts: i64,
+}
+
+#[event]
+pub struct TreasuryPolicyAppliedEvent {
+    pub budget_per_period: u64,
+    pub period_secs: i64,
+    pub timelock_secs: i64,
+}
+
+#[event]
+pub struct TreasuryPolicyCancelledEvent {
+    pub budget_per_period: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawalQueuedEvent {
+    pub id: u64,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub executable_at_ts: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawalExecutedEvent {
+    pub id: u64,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub spent_in_period: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawalCancelledEvent {
+    pub id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct LiquidDepositEvent {
+    pub owner: Pubkey,
+    pub game_amount: u64,
+    pub xnt_amount: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct LiquidDepositStartedEvent {
+    pub owner: Pubkey,
+    pub ticket_id: u32,
+    pub game_amount: u64,
+    pub warm_at_ts: i64,
+}
+
+#[event]
+pub struct LiquidDepositClaimedEvent {
+    pub owner: Pubkey,
+    pub ticket_id: u32,
+    pub game_amount: u64,
+    pub xnt_amount: u64,
+    pub shares: u64,
+}
+
+#[event]
+pub struct LiquidRedeemEvent {
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub game_amount: u64,
+    pub xnt_amount: u64,
+}
+
+#[event]
+pub struct LiquidUnbondStartedEvent {
+    pub owner: Pubkey,
+    pub ticket_id: u32,
+    pub shares: u64,
+    pub game_amount: u64,
+    pub xnt_amount: u64,
+    pub unbonding_until_ts: i64,
+}
+
+#[event]
+pub struct LiquidUnbondWithdrawnEvent {
+    pub owner: Pubkey,
+    pub ticket_id: u32,
+    pub game_amount: u64,
+}
+
+#[event]
+pub struct LiquidVaultHarvestedEvent {
+    pub amount: u64,
+    pub total_xnt_harvested: u64,
+}
+
+#[event]
+pub struct PendingRewardsDistributedEvent {
+    pub amount: u64,
+    pub total_effective_stake: u128,
+}
+
 #[event]
 pub struct BoostActivatedEvent {
     pub user: Pubkey,
-    pub boost_id: u8,
+    pub boost_id: u16,
     pub expires_at: i64,
+    pub payment: BoostPayment,
+    pub price: u64,
+    pub from_inventory: bool,
+}
+
+#[event]
+pub struct BoostPurchasedEvent {
+    pub user: Pubkey,
+    pub boost_id: u16,
+    pub payment: BoostPayment,
+    pub price: u64,
+}
+
+#[event]
+pub struct BoostGiftedEvent {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub boost_id: u16,
+    pub count: u32,
+}
+
+#[event]
+pub struct BoostDeactivatedEvent {
+    pub user: Pubkey,
+    pub boost_id: u16,
+    pub refunded_points: u64,
+}
+
+#[event]
+pub struct BoostExpiredEvent {
+    pub user: Pubkey,
+    pub boost_id: u16,
+    pub expired_at: i64,
 }
 
 #[event]
@@ -1255,6 +7289,28 @@ pub struct RankingAppliedEvent {
     pub added_boost_points: u64,
 }
 
+#[event]
+pub struct RankingEpochPublishedEvent {
+    pub epoch_id: u64,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub total_points: u64,
+}
+
+#[event]
+pub struct RankingRewardClaimedEvent {
+    pub epoch_id: u64,
+    pub user: Pubkey,
+    pub index: u32,
+    pub boost_points: u64,
+}
+
+#[event]
+pub struct RankingEpochClosedEvent {
+    pub epoch_id: u64,
+    pub unclaimed_points: u64,
+}
+
 // -------------------------------------
 // Errors
 // -------------------------------------
@@ -1281,4 +7337,99 @@ pub enum ArenaError {
     IncompleteConfig,
     #[msg("Invalid stake PDA")]
     InvalidStakePda,
+    #[msg("No effective stake in pool")]
+    NoEffectiveStake,
+    #[msg("Invalid duration")]
+    InvalidDuration,
+    #[msg("No stake positions provided")]
+    NoPositionsProvided,
+    #[msg("Stake position passed more than once")]
+    DuplicatePosition,
+    #[msg("Invalid new owner")]
+    InvalidNewOwner,
+    #[msg("Invalid account data")]
+    InvalidAccountData,
+    #[msg("Invalid slot")]
+    InvalidSlot,
+    #[msg("Checkpoint no longer available for slot")]
+    CheckpointUnavailable,
+    #[msg("Treasury cannot cover staker rewards")]
+    TreasuryInsolvent,
+    #[msg("Treasury withdrawal budget exceeded")]
+    TreasuryBudgetExceeded,
+    #[msg("Withdrawal would use funds reserved for stakers")]
+    TreasuryFundsReserved,
+    #[msg("Timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Unbonding is enabled; use begin_unbond")]
+    UnbondingRequired,
+    #[msg("Position is already unbonding")]
+    AlreadyUnbonding,
+    #[msg("Position is not unbonding")]
+    NotUnbonding,
+    #[msg("Unbonding period has not elapsed")]
+    UnbondingInProgress,
+    #[msg("Too many open positions")]
+    TooManyPositions,
+    #[msg("Invalid split amount")]
+    InvalidSplitAmount,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Slippage limit exceeded")]
+    SlippageExceeded,
+    #[msg("Position exceeds the maximum stake amount")]
+    PositionCapExceeded,
+    #[msg("User exceeds the maximum effective stake")]
+    UserStakeCapExceeded,
+    #[msg("User exceeds the maximum share of the pool")]
+    UserShareCapExceeded,
+    #[msg("Boost is not available")]
+    BoostUnavailable,
+    #[msg("Boost sold out")]
+    BoostSoldOut,
+    #[msg("Boost purchase limit reached")]
+    BoostPurchaseLimitReached,
+    #[msg("Payment currency not accepted for this boost")]
+    PaymentNotAccepted,
+    #[msg("Accounts required for this payment are missing")]
+    MissingPaymentAccounts,
+    #[msg("Boost inventory is full")]
+    InventoryFull,
+    #[msg("Not enough boosts in inventory")]
+    InsufficientInventory,
+    #[msg("Charges are not supported for this boost kind")]
+    InvalidBoostCharges,
+    #[msg("No active boost at that index")]
+    InvalidBoostIndex,
+    #[msg("Leaf index outside the ranking epoch")]
+    InvalidLeafIndex,
+    #[msg("Ranking reward already claimed")]
+    AlreadyClaimed,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Unclaimed rewards exceed what the pool has credited")]
+    UnclaimedRewardsTooHigh,
+    #[msg("Treasury timelock is below the minimum")]
+    TimelockTooShort,
+    #[msg("Loosening the treasury policy must be queued")]
+    PolicyChangeNeedsQueue,
+    #[msg("Position is not warming up")]
+    NotWarmingUp,
+    #[msg("Staking boosts cannot be refunded")]
+    BoostNotRefundable,
+    #[msg("Ranking epoch id was already used")]
+    RankingEpochTooOld,
+    #[msg("An indexed stake position was not passed")]
+    MissingPosition,
+    #[msg("Warmup is enabled; use begin_liquid_deposit")]
+    WarmupRequired,
+    #[msg("Deposit is still warming up")]
+    WarmupInProgress,
+    #[msg("Ranking epoch has no points left to claim")]
+    RankingEpochExhausted,
+    #[msg("The leaderboard must be passed once it is initialized")]
+    LeaderboardRequired,
 }
+
+#[cfg(test)]
+mod tests;
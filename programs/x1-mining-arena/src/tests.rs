@@ -0,0 +1,1662 @@
+use super::*;
+
+fn pool_with_stake(total_effective_stake: u128) -> StakingPool {
+    StakingPool {
+        total_effective_stake,
+        ..Default::default()
+    }
+}
+
+// user-026: rewards buffer while the pool is empty.
+
+#[test]
+fn rewards_buffer_while_pool_is_empty() {
+    let mut pool = pool_with_stake(0);
+    distribute_staker_rewards(&mut pool, 500, 100).unwrap();
+    assert_eq!(pool.pending_undistributed, 500);
+    assert_eq!(pool.acc_reward_per_share, 0);
+}
+
+#[test]
+fn buffered_rewards_fold_once_stake_arrives() {
+    let mut pool = pool_with_stake(0);
+    distribute_staker_rewards(&mut pool, 500, 100).unwrap();
+
+    pool.total_effective_stake = 1_000;
+    fold_pending_undistributed(&mut pool, 200).unwrap();
+    assert_eq!(pool.pending_undistributed, 0);
+    assert_eq!(pool.acc_reward_per_share, 500 * PRECISION / 1_000);
+}
+
+#[test]
+fn folding_without_stake_keeps_the_buffer() {
+    let mut pool = pool_with_stake(0);
+    distribute_staker_rewards(&mut pool, 500, 100).unwrap();
+    fold_pending_undistributed(&mut pool, 200).unwrap();
+    assert_eq!(pool.pending_undistributed, 500);
+}
+
+// user-027: streamed rewards and dust carry.
+
+#[test]
+fn streamed_rewards_accrue_linearly() {
+    let mut pool = pool_with_stake(1_000);
+    pool.reward_duration_secs = 100;
+    distribute_staker_rewards(&mut pool, 1_000, 0).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 0);
+
+    update_reward_stream(&mut pool, 25).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 250 * PRECISION / 1_000);
+
+    // Nothing streams past the end of the period.
+    update_reward_stream(&mut pool, 500).unwrap();
+    assert_eq!(pool.acc_reward_per_share, PRECISION);
+}
+
+#[test]
+fn new_deposit_rolls_leftover_into_the_stream() {
+    let mut pool = pool_with_stake(1_000);
+    pool.reward_duration_secs = 100;
+    distribute_staker_rewards(&mut pool, 1_000, 0).unwrap();
+    distribute_staker_rewards(&mut pool, 1_000, 50).unwrap();
+    assert_eq!(pool.period_finish, 150);
+    assert_eq!(pool.reward_rate, 1_500 * PRECISION / 100);
+
+    update_reward_stream(&mut pool, 150).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 2 * PRECISION);
+}
+
+#[test]
+fn division_dust_is_carried_into_the_next_credit() {
+    let mut pool = pool_with_stake(3 * PRECISION);
+    credit_reward_per_share(&mut pool, PRECISION).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 0);
+    assert_eq!(pool.reward_dust, PRECISION);
+
+    credit_reward_per_share(&mut pool, 2 * PRECISION).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 1);
+    assert_eq!(pool.reward_dust, 0);
+}
+
+#[test]
+fn buffered_dust_is_not_lost() {
+    let mut pool = pool_with_stake(0);
+    credit_reward_per_share(&mut pool, PRECISION / 2).unwrap();
+    credit_reward_per_share(&mut pool, PRECISION / 2).unwrap();
+    assert_eq!(pool.pending_undistributed, 1);
+    assert_eq!(pool.reward_dust, 0);
+}
+
+// user-029: positions allocated before `seed_owner` existed.
+
+fn sample_position() -> UserStakePosition {
+    UserStakePosition {
+        owner: Pubkey::new_unique(),
+        amount_staked: 1_000,
+        lock_multiplier_bps: 12_000,
+        boost_multiplier_bps: 10_000,
+        effective_stake: 1_200,
+        reward_debt: 77,
+        lock_until_ts: 1_700_000_000,
+        position_id: 3,
+        seed_owner: Pubkey::new_unique(),
+        unbonding_until_ts: 5,
+        warmup_start_ts: 6,
+        warmup_end_ts: 7,
+        warming_stake: 0,
+    }
+}
+
+fn position_bytes(position: &UserStakePosition) -> Vec<u8> {
+    let mut data = Vec::new();
+    position.try_serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn position_round_trips_at_current_size() {
+    let position = sample_position();
+    let data = position_bytes(&position);
+
+    let decoded = UserStakePosition::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(decoded.seed_owner, position.seed_owner);
+    assert_eq!(decoded.warmup_end_ts, 7);
+}
+
+#[test]
+fn legacy_position_reads_appended_fields_as_zero() {
+    let position = sample_position();
+    let mut data = position_bytes(&position);
+    // Legacy accounts end after the padding that followed `position_id`.
+    data.truncate(8 + UserStakePosition::LEGACY_LEN);
+    data[8 + UserStakePosition::LEGACY_LEN - 4..].fill(0);
+
+    let decoded = UserStakePosition::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(decoded.owner, position.owner);
+    assert_eq!(decoded.amount_staked, 1_000);
+    assert_eq!(decoded.reward_debt, 77);
+    assert_eq!(decoded.position_id, 3);
+    assert_eq!(decoded.seed_owner, Pubkey::default());
+    assert_eq!(decoded.seed_owner(), position.owner);
+    assert!(!decoded.is_unbonding());
+    assert!(!decoded.is_warming_up());
+}
+
+#[test]
+fn position_with_foreign_discriminator_is_rejected() {
+    let mut data = position_bytes(&sample_position());
+    data[..8].copy_from_slice(&UserAccount::DISCRIMINATOR);
+    assert!(UserStakePosition::try_deserialize(&mut &data[..]).is_err());
+}
+
+#[test]
+fn legacy_account_discriminators_match_account_derive() {
+    fn derived(name: &str) -> [u8; 8] {
+        let hash = anchor_lang::solana_program::hash::hash(format!("account:{name}").as_bytes());
+        hash.to_bytes()[..8].try_into().unwrap()
+    }
+    assert_eq!(
+        UserStakePosition::DISCRIMINATOR,
+        derived("UserStakePosition")
+    );
+    assert_eq!(UserAccount::DISCRIMINATOR, derived("UserAccount"));
+    assert_eq!(BoostInventory::DISCRIMINATOR, derived("BoostInventory"));
+    assert_eq!(LiquidVault::DISCRIMINATOR, derived("LiquidVault"));
+}
+
+// user-030: refreshes by anyone but the owner or delegate only downgrade.
+
+#[test]
+fn strangers_may_only_lower_the_boost_multiplier() {
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let user = UserAccount {
+        owner,
+        claim_delegate: delegate,
+        ..Default::default()
+    };
+    let keeper = Pubkey::new_unique();
+
+    assert!(may_refresh(&user, &keeper, 15_000, 10_000));
+    assert!(!may_refresh(&user, &keeper, 10_000, 10_000));
+    assert!(!may_refresh(&user, &keeper, 10_000, 15_000));
+    assert!(may_refresh(&user, &owner, 10_000, 15_000));
+    assert!(may_refresh(&user, &delegate, 10_000, 15_000));
+}
+
+// user-031: checkpoints are kept per interval and sampled at its start.
+
+const I: u64 = CHECKPOINT_INTERVAL_SLOTS;
+
+#[test]
+fn voting_power_is_sampled_at_the_interval_start() {
+    let mut checkpoints = UserCheckpoints::default();
+    checkpoints.record(10, 100);
+    checkpoints.record(I + 5, 300);
+
+    assert_eq!(checkpoints.value_at(5).unwrap(), 0);
+    assert_eq!(checkpoints.value_at(I).unwrap(), 100);
+    // Stake added inside the snapshot's own interval does not count yet.
+    assert_eq!(checkpoints.value_at(I + 10).unwrap(), 100);
+    assert_eq!(checkpoints.value_at(2 * I).unwrap(), 300);
+}
+
+#[test]
+fn changes_within_an_interval_share_one_entry() {
+    let mut checkpoints = UserCheckpoints::default();
+    checkpoints.record(1, 100);
+    checkpoints.record(2, 200);
+    checkpoints.record(I - 1, 50);
+    assert_eq!(checkpoints.count, 1);
+    assert_eq!(checkpoints.value_at(I).unwrap(), 50);
+}
+
+#[test]
+fn snapshots_older_than_the_window_are_unavailable() {
+    let mut checkpoints = PoolCheckpoints::default();
+    for n in 0..=POOL_CHECKPOINTS as u64 {
+        checkpoints.record(n * I, n as u128);
+    }
+    let newest = POOL_CHECKPOINTS as u64 * I;
+    assert_eq!(
+        checkpoints.value_at(newest + I).unwrap(),
+        POOL_CHECKPOINTS as u128
+    );
+    assert_eq!(checkpoints.value_at(2 * I).unwrap(), 1);
+    assert!(checkpoints.value_at(I).is_err());
+}
+
+#[test]
+fn a_wrapped_user_ring_still_answers_within_the_snapshot_window() {
+    let mut user = UserAccount::default();
+    let current = 100 * I + 7;
+    for n in 0..=100 {
+        let previous = user.total_effective_stake;
+        user.record_effective_stake_change(previous, n as u128, n * I + 1)
+            .unwrap();
+    }
+    assert_eq!(user.checkpoints.count as usize, USER_CHECKPOINTS);
+
+    for back in 0..SNAPSHOT_WINDOW_INTERVALS {
+        let slot = current - back * I;
+        require_snapshot_in_window(slot, current).unwrap();
+        let interval = slot / I;
+        assert_eq!(
+            user.checkpoints.value_at(slot).unwrap(),
+            interval as u128 - 1
+        );
+    }
+    let too_old = current - SNAPSHOT_WINDOW_INTERVALS * I;
+    assert_eq!(
+        require_snapshot_in_window(too_old, current).unwrap_err(),
+        ArenaError::CheckpointUnavailable.into()
+    );
+    assert_eq!(
+        require_snapshot_in_window(current + 1, current).unwrap_err(),
+        ArenaError::InvalidSlot.into()
+    );
+}
+
+#[test]
+fn syncing_replaces_the_user_total() {
+    let mut user = UserAccount::default();
+    user.record_effective_stake_change(0, 40, 1).unwrap();
+    let previous = user.total_effective_stake;
+    user.record_effective_stake_change(previous, 900, I)
+        .unwrap();
+    assert_eq!(user.total_effective_stake, 900);
+    assert_eq!(user.checkpoints.value_at(2 * I).unwrap(), 900);
+}
+
+#[test]
+fn syncing_requires_every_indexed_position() {
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut index = UserPositions {
+        owner: Pubkey::new_unique(),
+        positions: Vec::new(),
+    };
+    index.upsert(first, &sample_position()).unwrap();
+    index.upsert(second, &sample_position()).unwrap();
+
+    assert!(index.require_all_listed(&[second]).is_err());
+    assert!(index.require_all_listed(&[]).is_err());
+    index.require_all_listed(&[second, first]).unwrap();
+    // Positions the index does not know about may be passed as well.
+    index
+        .require_all_listed(&[first, second, Pubkey::new_unique()])
+        .unwrap();
+}
+
+#[test]
+fn syncing_never_lowers_the_totals() {
+    let mut user = UserAccount::default();
+    user.record_effective_stake_change(0, 900, 1).unwrap();
+    user.record_warming_stake_change(0, 300).unwrap();
+
+    user.raise_stake_totals(0, 0, I).unwrap();
+    assert_eq!(user.total_effective_stake, 900);
+    assert_eq!(user.target_effective_stake(), 1_200);
+
+    user.raise_stake_totals(1_000, 500, 2 * I).unwrap();
+    assert_eq!(user.total_effective_stake, 1_000);
+    assert_eq!(user.target_effective_stake(), 1_500);
+    assert_eq!(user.checkpoints.value_at(3 * I).unwrap(), 1_000);
+}
+
+// user-032: seeding the reserve at migration and releasing dust.
+
+#[test]
+fn seeded_reserve_covers_unclaimed_buffered_and_streaming_rewards() {
+    let mut pool = StakingPool {
+        total_effective_stake: 1_000,
+        acc_reward_per_share: 5 * PRECISION,
+        pending_undistributed: 7,
+        reward_rate: 3 * PRECISION,
+        period_finish: 110,
+        last_update_ts: 100,
+        ..Default::default()
+    };
+    seed_reward_reserve(&mut pool, 4_000).unwrap();
+    assert_eq!(pool.total_rewards_accrued, 4_000 + 7 + 30);
+    assert_eq!(pool.reserved_rewards(), 4_037);
+}
+
+#[test]
+fn seeded_reserve_cannot_exceed_credited_rewards() {
+    let mut pool = StakingPool {
+        total_effective_stake: 1_000,
+        acc_reward_per_share: 5 * PRECISION,
+        ..Default::default()
+    };
+    assert!(seed_reward_reserve(&mut pool, 5_001).is_err());
+}
+
+#[test]
+fn emptied_pool_releases_rounding_dust() {
+    let mut pool = pool_with_stake(3);
+    fund_staker_rewards(&mut pool, 10, 0).unwrap();
+    // Three positions of one unit each can only ever claim 3 apiece.
+    pool.total_rewards_claimed = 9;
+    pool.total_effective_stake = 0;
+    release_reward_dust(&mut pool);
+    assert_eq!(pool.reserved_rewards(), 1);
+
+    pool.reward_dust = 0;
+    release_reward_dust(&mut pool);
+    assert_eq!(pool.reserved_rewards(), 0);
+}
+
+#[test]
+fn dust_is_kept_while_stake_remains() {
+    let mut pool = pool_with_stake(3);
+    fund_staker_rewards(&mut pool, 10, 0).unwrap();
+    pool.total_rewards_claimed = 9;
+    release_reward_dust(&mut pool);
+    assert_eq!(pool.reserved_rewards(), 1);
+}
+
+// user-033: treasury policy changes that loosen the policy are timelocked.
+
+fn policy_args(budget_per_period: u64, period_secs: i64, timelock_secs: i64) -> TreasuryPolicyArgs {
+    TreasuryPolicyArgs {
+        budget_per_period,
+        period_secs,
+        timelock_secs,
+    }
+}
+
+#[test]
+fn policy_timelock_has_a_floor() {
+    assert!(policy_args(1, 1, MIN_TREASURY_TIMELOCK_SECS - 1)
+        .validate()
+        .is_err());
+    assert!(policy_args(1, 0, MIN_TREASURY_TIMELOCK_SECS)
+        .validate()
+        .is_err());
+    assert!(policy_args(1, 1, MIN_TREASURY_TIMELOCK_SECS)
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn only_tightening_changes_skip_the_queue() {
+    let day = 86_400;
+    let mut policy = TreasuryPolicy {
+        budget_per_period: 0,
+        period_secs: 0,
+        period_start_ts: 0,
+        spent_in_period: 0,
+        timelock_secs: 0,
+        next_withdrawal_id: 0,
+    };
+    assert!(!policy.is_configured());
+    policy.apply(&policy_args(1_000, day, 2 * day));
+    assert!(policy.is_configured());
+
+    assert!(policy.is_tightened_by(&policy_args(1_000, day, 2 * day)));
+    assert!(policy.is_tightened_by(&policy_args(999, 2 * day, 3 * day)));
+    assert!(!policy.is_tightened_by(&policy_args(1_001, day, 2 * day)));
+    assert!(!policy.is_tightened_by(&policy_args(1_000, day - 1, 2 * day)));
+    assert!(!policy.is_tightened_by(&policy_args(1_000, day, 2 * day - 1)));
+}
+
+// user-034: unstake routes through unbonding when the pool has a period.
+
+#[test]
+fn unstake_withdraws_without_unbonding_period() {
+    let position = UserStakePosition {
+        unbonding_until_ts: 0,
+        ..sample_position()
+    };
+    assert_eq!(
+        position.unstake_action(0, 1_000).unwrap(),
+        UnstakeAction::Withdraw
+    );
+}
+
+#[test]
+fn unstake_starts_unbonding_when_enabled() {
+    let position = UserStakePosition {
+        unbonding_until_ts: 0,
+        ..sample_position()
+    };
+    assert_eq!(
+        position.unstake_action(3_600, 1_000).unwrap(),
+        UnstakeAction::BeginUnbond {
+            unbonding_until_ts: 4_600
+        }
+    );
+}
+
+#[test]
+fn unstake_withdraws_only_after_cooldown() {
+    let mut position = sample_position();
+    position.unbonding_until_ts = 4_600;
+    assert!(position.unstake_action(3_600, 4_599).is_err());
+    assert_eq!(
+        position.unstake_action(3_600, 4_600).unwrap(),
+        UnstakeAction::WithdrawUnbonded
+    );
+    // Turning unbonding off later does not strand a position mid-cooldown.
+    assert_eq!(
+        position.unstake_action(0, 4_600).unwrap(),
+        UnstakeAction::WithdrawUnbonded
+    );
+}
+
+// user-035: the position index grows instead of capping open positions.
+
+#[test]
+fn position_index_has_no_cap() {
+    let mut index = UserPositions {
+        owner: Pubkey::new_unique(),
+        positions: Vec::new(),
+    };
+    let position = sample_position();
+    for _ in 0..40 {
+        index.upsert(Pubkey::new_unique(), &position).unwrap();
+    }
+    assert_eq!(index.positions.len(), 40);
+
+    let mut data = Vec::new();
+    index.try_serialize(&mut data).unwrap();
+    assert_eq!(data.len(), UserPositions::space_for(40));
+}
+
+#[test]
+fn position_index_upsert_refreshes_existing_entry() {
+    let mut index = UserPositions {
+        owner: Pubkey::new_unique(),
+        positions: Vec::new(),
+    };
+    let key = Pubkey::new_unique();
+    let mut position = sample_position();
+    index.upsert(key, &position).unwrap();
+    position.amount_staked += 1;
+    index.upsert(key, &position).unwrap();
+    assert_eq!(index.positions.len(), 1);
+    assert_eq!(index.positions[0].amount_staked, position.amount_staked);
+}
+
+#[test]
+fn position_index_init_space_keeps_existing_size() {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    // Size of an index allocated with the old 32-entry cap.
+    let mut data = vec![0u8; UserPositions::space_for(32)];
+    let system = anchor_lang::system_program::ID;
+    let existing = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut data,
+        &crate::ID,
+        false,
+        0,
+    );
+    assert_eq!(
+        UserPositions::init_space(&existing),
+        UserPositions::space_for(32)
+    );
+
+    let mut lamports = 0;
+    let mut empty: Vec<u8> = Vec::new();
+    let fresh = AccountInfo::new(
+        &key,
+        false,
+        true,
+        &mut lamports,
+        &mut empty,
+        &system,
+        false,
+        0,
+    );
+    assert_eq!(
+        UserPositions::init_space(&fresh),
+        UserPositions::space_for(INITIAL_USER_POSITIONS)
+    );
+}
+
+// user-036: merged positions weight the lock multiplier by amount.
+
+#[test]
+fn merge_weights_lock_multiplier_by_amount() {
+    // A small 30-day position does not lift a large 7-day one to 30-day terms.
+    assert_eq!(
+        merged_lock_multiplier(100, 12_000, 900, 10_500).unwrap(),
+        10_650
+    );
+    assert_eq!(
+        merged_lock_multiplier(500, 11_000, 500, 11_000).unwrap(),
+        11_000
+    );
+    // Rounds down.
+    assert_eq!(
+        merged_lock_multiplier(1, 10_501, 2, 10_500).unwrap(),
+        10_500
+    );
+}
+
+#[test]
+fn merge_of_empty_positions_keeps_higher_multiplier() {
+    assert_eq!(
+        merged_lock_multiplier(0, 10_500, 0, 12_000).unwrap(),
+        12_000
+    );
+}
+
+// user-037: warmup advances the pool total whenever it is synced.
+
+fn warming_position() -> UserStakePosition {
+    UserStakePosition {
+        amount_staked: 1_000,
+        lock_multiplier_bps: 10_000,
+        boost_multiplier_bps: 10_000,
+        effective_stake: 0,
+        reward_debt: 0,
+        unbonding_until_ts: 0,
+        warmup_start_ts: 100,
+        warmup_end_ts: 200,
+        ..sample_position()
+    }
+}
+
+fn clock_at(unix_timestamp: i64) -> Clock {
+    Clock {
+        slot: 1,
+        unix_timestamp,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn warmup_sync_moves_pool_total_along_the_ramp() {
+    let mut pool = pool_with_stake(0);
+    let mut user = UserAccount::default();
+    let mut position = warming_position();
+
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(150)).unwrap();
+    assert_eq!(position.effective_stake, 500);
+    assert_eq!(pool.total_effective_stake, 500);
+    assert_eq!(user.total_effective_stake, 500);
+    assert!(position.is_warming_up());
+
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(250)).unwrap();
+    assert_eq!(position.effective_stake, 1_000);
+    assert_eq!(pool.total_effective_stake, 1_000);
+    assert!(!position.is_warming_up());
+}
+
+#[test]
+fn warmup_sync_skips_unbonding_positions() {
+    let mut pool = pool_with_stake(0);
+    let mut user = UserAccount::default();
+    let mut position = UserStakePosition {
+        unbonding_until_ts: 500,
+        ..warming_position()
+    };
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(150)).unwrap();
+    assert_eq!(position.effective_stake, 0);
+    assert_eq!(pool.total_effective_stake, 0);
+}
+
+// user-038: liquid vault shares, warming deposits and harvested XNT.
+
+#[test]
+fn first_liquid_deposit_locks_minimum_shares() {
+    let vault = LiquidVault::default();
+    assert!(vault.shares_for_deposit(MINIMUM_LIQUID_SHARES, 0).is_err());
+    assert_eq!(
+        vault.shares_for_deposit(10_000, 0).unwrap(),
+        (10_000 - MINIMUM_LIQUID_SHARES, MINIMUM_LIQUID_SHARES)
+    );
+}
+
+#[test]
+fn donated_game_cannot_zero_out_later_deposits() {
+    // First depositor redeemed everything but the locked shares, then
+    // donated GAME to inflate the price per share.
+    let vault = LiquidVault {
+        total_game: 1_000_000,
+        locked_shares: MINIMUM_LIQUID_SHARES,
+        ..Default::default()
+    };
+    let (shares, locked) = vault.shares_for_deposit(5_000, 0).unwrap();
+    assert_eq!((shares, locked), (5, 0));
+    // The donation accrues to the locked shares, not to an attacker.
+    assert_eq!(vault.game_for_shares(0, 0).unwrap(), 0);
+    assert!(vault.game_for_shares(1, 0).is_err());
+}
+
+#[test]
+fn harvested_xnt_is_held_pro_rata_per_share() {
+    let mut vault = LiquidVault {
+        total_game: 2_000,
+        locked_shares: MINIMUM_LIQUID_SHARES,
+        ..Default::default()
+    };
+    assert_eq!(vault.xnt_for_shares(500, 1_000, true).unwrap(), 0);
+
+    // A harvest adds XNT without changing the GAME per share.
+    vault.add_xnt(3_001).unwrap();
+    assert_eq!(vault.game_for_shares(500, 1_000).unwrap(), 500);
+    assert_eq!(vault.shares_for_deposit(1_000, 1_000).unwrap(), (1_000, 0));
+    // Redeemers round down and depositors round up, so holders never lose.
+    assert_eq!(vault.xnt_for_shares(500, 1_000, false).unwrap(), 750);
+    assert_eq!(vault.xnt_for_shares(500, 1_000, true).unwrap(), 751);
+
+    vault.remove_xnt(750);
+    assert_eq!(vault.total_xnt, 2_251);
+}
+
+#[test]
+fn empty_vault_takes_no_xnt() {
+    let vault = LiquidVault {
+        total_xnt: 1_000,
+        ..Default::default()
+    };
+    assert_eq!(vault.xnt_for_shares(9_000, 0, true).unwrap(), 0);
+}
+
+#[test]
+fn warming_deposits_earn_nothing_until_claimed() {
+    let mut vault = LiquidVault {
+        total_game: 1_000,
+        ..Default::default()
+    };
+    vault.add_pending_game(1_000).unwrap();
+    assert_eq!(vault.game_for_shares(1_000, 1_000).unwrap(), 1_000);
+
+    // XNT harvested while the deposit warms up goes to existing holders.
+    vault.add_xnt(100).unwrap();
+    assert_eq!(vault.xnt_for_shares(1_000, 1_000, false).unwrap(), 100);
+    let (shares, _) = vault.shares_for_deposit(1_000, 1_000).unwrap();
+    assert_eq!(shares, 1_000);
+    assert_eq!(vault.xnt_for_shares(shares, 1_000, true).unwrap(), 100);
+
+    vault.settle_pending_game(1_000).unwrap();
+    assert_eq!(vault.pending_game, 0);
+    assert_eq!(vault.total_game, 2_000);
+    assert!(vault.settle_pending_game(1).is_err());
+}
+
+// user-039: stake caps count stake still warming up.
+
+#[test]
+fn user_cap_counts_stake_still_warming_up() {
+    let mut pool = StakingPool {
+        max_user_effective_stake: 1_500,
+        ..pool_with_stake(0)
+    };
+    let mut user = UserAccount::default();
+    let mut position = warming_position();
+    set_effective_stake(&mut pool, &mut user, &mut position, 0, 1_000, 1).unwrap();
+    assert_eq!(user.total_effective_stake, 0);
+    assert_eq!(user.target_effective_stake(), 1_000);
+    assert_eq!(pool.target_effective_stake(), 1_000);
+
+    assert!(enforce_stake_caps(&pool, user.target_effective_stake(), 1_000, 1_000, 1_000).is_err());
+    assert!(enforce_stake_caps(&pool, user.target_effective_stake(), 500, 500, 500).is_ok());
+}
+
+#[test]
+fn share_cap_compares_pool_targets() {
+    let mut pool = StakingPool {
+        max_user_share_bps: 5_000,
+        ..pool_with_stake(0)
+    };
+    let mut other = UserAccount::default();
+    let mut position = warming_position();
+    set_effective_stake(&mut pool, &mut other, &mut position, 0, 1_000, 1).unwrap();
+
+    assert!(enforce_stake_caps(&pool, 0, 1_000, 1_000, 1_000).is_ok());
+    assert!(enforce_stake_caps(&pool, 0, 1_001, 1_001, 1_001).is_err());
+}
+
+#[test]
+fn liquid_deposits_count_the_depositors_positions() {
+    let mut pool = StakingPool {
+        max_user_effective_stake: 1_000,
+        ..pool_with_stake(0)
+    };
+    let mut user = UserAccount::default();
+    let mut position = warming_position();
+    set_effective_stake(&mut pool, &mut user, &mut position, 0, 1_000, 1).unwrap();
+
+    // The user's positions already fill the cap, so the vault cannot add more.
+    let err = liquid_deposit_caps_check(&pool, &user, 500, 500).unwrap_err();
+    assert_eq!(err, ArenaError::UserStakeCapExceeded.into());
+    assert!(liquid_deposit_caps_check(&pool, &UserAccount::default(), 500, 500).is_ok());
+}
+
+#[test]
+fn warmup_holds_while_the_target_breaches_a_cap() {
+    let mut pool = pool_with_stake(0);
+    let mut user = UserAccount::default();
+    let mut position = warming_position();
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(120)).unwrap();
+    assert_eq!(position.effective_stake, 200);
+    assert_eq!(position.warming_stake, 800);
+
+    pool.max_user_effective_stake = 900;
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(150)).unwrap();
+    assert_eq!(position.effective_stake, 200);
+    assert!(warmup_caps_check(&pool, &user, &position).is_err());
+
+    pool.max_user_effective_stake = 1_000;
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(150)).unwrap();
+    assert_eq!(position.effective_stake, 500);
+    assert_eq!(pool.total_effective_stake + pool.total_warming_stake, 1_000);
+}
+
+#[test]
+fn closing_a_position_clears_its_warming_stake() {
+    let mut pool = pool_with_stake(0);
+    let mut user = UserAccount::default();
+    let mut position = warming_position();
+    sync_warmup(&mut pool, &mut user, &mut position, &clock_at(150)).unwrap();
+    assert_eq!(user.warming_stake, 500);
+
+    set_effective_stake(&mut pool, &mut user, &mut position, 0, 0, 1).unwrap();
+    assert_eq!(user.target_effective_stake(), 0);
+    assert_eq!(pool.target_effective_stake(), 0);
+}
+
+#[test]
+fn liquid_vault_warming_counts_towards_the_pool_target() {
+    let mut pool = pool_with_stake(0);
+    let mut vault = LiquidVault {
+        total_game: 250,
+        ..Default::default()
+    };
+    vault.add_pending_game(750).unwrap();
+    set_liquid_vault_stake(&mut pool, &mut vault, 1).unwrap();
+    assert_eq!(pool.total_effective_stake, 250);
+    assert_eq!(pool.total_warming_stake, 750);
+
+    vault.settle_pending_game(750).unwrap();
+    set_liquid_vault_stake(&mut pool, &mut vault, 1).unwrap();
+    assert_eq!(pool.total_effective_stake, 1_000);
+    assert_eq!(pool.total_warming_stake, 0);
+}
+
+// user-040: per-kind stacking modes and the combined cap.
+
+fn sample_global() -> GlobalConfig {
+    GlobalConfig {
+        admin: Pubkey::new_unique(),
+        game_mint: Pubkey::new_unique(),
+        xnt_mint: Pubkey::new_unique(),
+        treasury_xnt_vault: Pubkey::new_unique(),
+        halving_interval: 1_000,
+        halving_level: 0,
+        total_minted: 0,
+        staking_share_bps: 3_000,
+        boost_stacking: [StackingMode::Default; MAX_BOOST_KINDS],
+        max_boost_multiplier_bps: 0,
+        total_burned: 0,
+        day_offset_secs: 0,
+        day_id_adjust: 0,
+        pending_day_offset_secs: 0,
+        pending_day_id_adjust: 0,
+        pending_day_offset_from_ts: 0,
+        next_ranking_epoch_id: 0,
+        leaderboard_initialized: false,
+    }
+}
+
+fn active_boost(kind: BoostKind, value_bps: u16) -> UserBoost {
+    UserBoost {
+        applied_to_mining: !kind.is_staking(),
+        applied_to_staking: kind.is_staking(),
+        kind,
+        value_bps,
+        ..Default::default()
+    }
+}
+
+fn user_with_boosts(boosts: Vec<UserBoost>) -> UserAccount {
+    UserAccount {
+        active_boosts: boosts,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn mining_boosts_multiply_by_default() {
+    let global = sample_global();
+    let user = user_with_boosts(vec![
+        active_boost(BoostKind::MiningRewardBps, 15_000),
+        active_boost(BoostKind::MiningRewardBps, 12_000),
+    ]);
+    assert_eq!(
+        combined_boost_bps(&global, &user, BoostKind::MiningRewardBps, None, 0),
+        18_000
+    );
+}
+
+#[test]
+fn additive_and_highest_only_modes() {
+    let mut global = sample_global();
+    let user = user_with_boosts(vec![
+        active_boost(BoostKind::MiningRewardBps, 15_000),
+        active_boost(BoostKind::MiningRewardBps, 12_000),
+    ]);
+    global.boost_stacking[BoostKind::MiningRewardBps.index()] = StackingMode::Additive;
+    assert_eq!(
+        combined_boost_bps(&global, &user, BoostKind::MiningRewardBps, None, 0),
+        17_000
+    );
+    global.boost_stacking[BoostKind::MiningRewardBps.index()] = StackingMode::HighestOnly;
+    assert_eq!(
+        combined_boost_bps(&global, &user, BoostKind::MiningRewardBps, None, 0),
+        15_000
+    );
+}
+
+#[test]
+fn staking_boosts_default_to_highest_only() {
+    let global = sample_global();
+    let user = user_with_boosts(vec![
+        active_boost(BoostKind::StakingMultiplierBps, 11_000),
+        active_boost(BoostKind::StakingMultiplierBps, 13_000),
+    ]);
+    assert_eq!(resolve_staking_multiplier(&global, &user, 0), 13_000);
+}
+
+#[test]
+fn combined_multiplier_is_capped() {
+    let mut global = sample_global();
+    global.max_boost_multiplier_bps = 20_000;
+    let user = user_with_boosts(vec![active_boost(BoostKind::MiningRewardBps, 15_000); 8]);
+    assert_eq!(
+        combined_boost_bps(&global, &user, BoostKind::MiningRewardBps, None, 0),
+        20_000
+    );
+}
+
+#[test]
+fn expired_and_other_kind_boosts_are_ignored() {
+    let global = sample_global();
+    let user = user_with_boosts(vec![
+        UserBoost {
+            expires_at_ts: 10,
+            ..active_boost(BoostKind::MiningRewardBps, 15_000)
+        },
+        active_boost(BoostKind::MiningPointsBps, 15_000),
+    ]);
+    assert_eq!(
+        combined_boost_bps(&global, &user, BoostKind::MiningRewardBps, None, 11),
+        10_000
+    );
+}
+
+// user-041: sale windows, supply and per-user limits.
+
+fn sample_boost_config(kind: BoostKind) -> BoostConfig {
+    BoostConfig {
+        id: 1,
+        kind,
+        cost_boost_points: Some(100),
+        value_bps: 12_000,
+        duration_seconds: 3_600,
+        rig_id: None,
+        start_ts: 0,
+        end_ts: 0,
+        max_supply: 0,
+        sold: 0,
+        per_user_limit: 0,
+        enabled: true,
+        price_game: None,
+        price_xnt: None,
+        charges: 0,
+        refundable: false,
+    }
+}
+
+fn empty_receipt() -> BoostReceipt {
+    BoostReceipt {
+        user: Pubkey::default(),
+        boost_id: 0,
+        purchased: 0,
+    }
+}
+
+#[test]
+fn sales_respect_the_window_and_enabled_flag() {
+    let user = Pubkey::new_unique();
+    let mut config = BoostConfig {
+        start_ts: 100,
+        end_ts: 200,
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    let mut receipt = empty_receipt();
+    assert!(record_boost_sale(&mut config, &mut receipt, user, 99).is_err());
+    assert!(record_boost_sale(&mut config, &mut receipt, user, 200).is_err());
+    record_boost_sale(&mut config, &mut receipt, user, 100).unwrap();
+
+    config.enabled = false;
+    assert!(record_boost_sale(&mut config, &mut receipt, user, 150).is_err());
+    assert_eq!(config.sold, 1);
+}
+
+#[test]
+fn sales_stop_at_max_supply() {
+    let mut config = BoostConfig {
+        max_supply: 2,
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    for _ in 0..2 {
+        record_boost_sale(&mut config, &mut empty_receipt(), Pubkey::new_unique(), 0).unwrap();
+    }
+    assert!(record_boost_sale(&mut config, &mut empty_receipt(), Pubkey::new_unique(), 0).is_err());
+    assert_eq!(config.sold, 2);
+}
+
+#[test]
+fn receipts_enforce_the_per_user_limit() {
+    let user = Pubkey::new_unique();
+    let mut config = BoostConfig {
+        per_user_limit: 2,
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    let mut receipt = empty_receipt();
+    record_boost_sale(&mut config, &mut receipt, user, 0).unwrap();
+    record_boost_sale(&mut config, &mut receipt, user, 0).unwrap();
+    assert!(record_boost_sale(&mut config, &mut receipt, user, 0).is_err());
+    assert_eq!(receipt.user, user);
+    assert_eq!(receipt.boost_id, config.id);
+    assert_eq!(receipt.purchased, 2);
+
+    // Another user's receipt starts from zero.
+    record_boost_sale(&mut config, &mut empty_receipt(), Pubkey::new_unique(), 0).unwrap();
+}
+
+// user-043: the boost inventory holds purchases until activation.
+
+fn empty_inventory() -> BoostInventory {
+    BoostInventory {
+        owner: Pubkey::default(),
+        items: Vec::new(),
+    }
+}
+
+#[test]
+fn inventory_counts_per_boost_id() {
+    let mut inventory = empty_inventory();
+    inventory.add(7, 2, 0).unwrap();
+    inventory.add(7, 1, 0).unwrap();
+    inventory.add(300, 1, 0).unwrap();
+    assert_eq!(inventory.items.len(), 2);
+    assert_eq!(inventory.items[0].count, 3);
+
+    inventory.take(7, 3).unwrap();
+    assert_eq!(inventory.items.len(), 1);
+    assert_eq!(inventory.items[0].boost_id, 300);
+}
+
+#[test]
+fn inventory_rejects_missing_items_and_overflowing_slots() {
+    let mut inventory = empty_inventory();
+    inventory.add(1, 1, 0).unwrap();
+    assert!(inventory.take(1, 2).is_err());
+    assert!(inventory.take(2, 1).is_err());
+
+    for id in 2..=MAX_INVENTORY_ITEMS as u16 {
+        inventory.add(id, 1, 0).unwrap();
+    }
+    assert!(inventory.add(1_000, 1, 0).is_err());
+    // Existing ids still stack.
+    inventory.add(1, 1, 0).unwrap();
+}
+
+#[test]
+fn inventory_owner_is_set_once() {
+    let owner = Pubkey::new_unique();
+    let mut inventory = empty_inventory();
+    inventory.init_owner(owner).unwrap();
+    inventory.init_owner(owner).unwrap();
+    assert!(inventory.init_owner(Pubkey::new_unique()).is_err());
+}
+
+#[test]
+fn activation_starts_the_timer_when_used() {
+    let config = sample_boost_config(BoostKind::MiningRewardBps);
+    let mut user = UserAccount::default();
+    let expires_at = push_user_boost(&mut user, &config, 0, 5_000).unwrap();
+    assert_eq!(expires_at, 5_000 + config.duration_seconds);
+    assert_eq!(user.active_boosts[0].activated_at_ts, 5_000);
+
+    for _ in 1..MAX_ACTIVE_BOOSTS {
+        push_user_boost(&mut user, &config, 0, 5_000).unwrap();
+    }
+    assert!(push_user_boost(&mut user, &config, 0, 5_000).is_err());
+}
+
+// user-042: a missing price means the currency is not accepted.
+
+fn pay_for_boost(
+    payment: BoostPayment,
+    config: &BoostConfig,
+    user: &mut UserAccount,
+) -> Result<u64> {
+    let key = Pubkey::new_unique();
+    let owner = system_program::ID;
+    let mut lamports = 0;
+    let mut data = [];
+    let info = AccountInfo::new(
+        &key,
+        true,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    let signer = Signer::try_from(&info).unwrap();
+    let mut global = sample_global();
+    collect_boost_payment(
+        payment,
+        config,
+        user,
+        &mut global,
+        BoostPaymentAccounts {
+            user: &signer,
+            staking_pool: None,
+            game_mint: None,
+            user_game_account: None,
+            user_xnt_account: None,
+            treasury_xnt_vault: None,
+            token_program: None,
+        },
+        0,
+    )
+}
+
+#[test]
+fn boost_points_are_spent_at_the_configured_price() {
+    let config = sample_boost_config(BoostKind::MiningRewardBps);
+    let mut user = UserAccount {
+        boost_points: 150,
+        ..Default::default()
+    };
+    assert_eq!(
+        pay_for_boost(BoostPayment::BoostPoints, &config, &mut user).unwrap(),
+        100
+    );
+    assert_eq!(user.boost_points, 50);
+    assert!(pay_for_boost(BoostPayment::BoostPoints, &config, &mut user).is_err());
+}
+
+#[test]
+fn unpriced_currencies_are_rejected() {
+    let config = BoostConfig {
+        cost_boost_points: None,
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    let mut user = UserAccount {
+        boost_points: 1_000,
+        ..Default::default()
+    };
+    for payment in [
+        BoostPayment::BoostPoints,
+        BoostPayment::Game,
+        BoostPayment::Xnt,
+    ] {
+        assert!(pay_for_boost(payment, &config, &mut user).is_err());
+    }
+    assert_eq!(user.boost_points, 1_000);
+}
+
+#[test]
+fn zero_prices_are_free_in_every_currency() {
+    let config = BoostConfig {
+        cost_boost_points: Some(0),
+        price_game: Some(0),
+        price_xnt: Some(0),
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    let mut user = UserAccount::default();
+    for payment in [
+        BoostPayment::BoostPoints,
+        BoostPayment::Game,
+        BoostPayment::Xnt,
+    ] {
+        assert_eq!(pay_for_boost(payment, &config, &mut user).unwrap(), 0);
+    }
+}
+
+#[test]
+fn priced_token_payments_need_their_accounts() {
+    let config = BoostConfig {
+        price_game: Some(5),
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    assert!(pay_for_boost(BoostPayment::Game, &config, &mut UserAccount::default()).is_err());
+}
+
+// user-044: user accounts in the original layout load lazily.
+
+/// Size of a user account in the original layout: eight single-byte-id
+/// boosts of 16 bytes and 12 bytes of padding.
+const ORIGINAL_USER_LEN: usize = 32 + 4 + 4 * 8 + 32 + 3 * 8 + 4 + 4 + 4 + 8 * 16 + 12;
+
+fn original_user_bytes(boosts: &[Vec<u8>], owner: Pubkey) -> Vec<u8> {
+    let mut data = UserAccount::DISCRIMINATOR.to_vec();
+    owner.serialize(&mut data).unwrap();
+    7u32.serialize(&mut data).unwrap(); // daily_points
+    [70u64, 5, 0, 0].serialize(&mut data).unwrap(); // lifetime, boost points, xnt, mined
+    [0u64; 4].serialize(&mut data).unwrap(); // runs by rig
+    [0u64, 0].serialize(&mut data).unwrap(); // crits, staking xnt
+    19i64.serialize(&mut data).unwrap(); // last_day_id
+    [0u8; 4].serialize(&mut data).unwrap(); // achievements
+    2u32.serialize(&mut data).unwrap(); // next_position_id
+    (boosts.len() as u32).serialize(&mut data).unwrap();
+    for boost in boosts {
+        data.extend_from_slice(boost);
+    }
+    data.resize(8 + ORIGINAL_USER_LEN, 0);
+    data
+}
+
+fn original_ticket(boost_id: u8) -> Vec<u8> {
+    let mut boost = vec![boost_id, BoostKind::FreeRigTicket as u8];
+    10_000u16.serialize(&mut boost).unwrap();
+    0i64.serialize(&mut boost).unwrap();
+    Some(2u8).serialize(&mut boost).unwrap();
+    boost.extend_from_slice(&[1, 0]);
+    boost
+}
+
+#[test]
+fn original_user_account_loads_as_current() {
+    let owner = Pubkey::new_unique();
+    let data = original_user_bytes(&[original_ticket(9)], owner);
+    let account = UserAccount::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(account.version, USER_ACCOUNT_VERSION);
+    assert_eq!(account.owner, owner);
+    assert_eq!(account.boost_points, 5);
+    assert_eq!(account.last_day_id, 19);
+    assert_eq!(account.total_effective_stake, 0);
+    assert_eq!(account.active_boosts.len(), 1);
+    assert_eq!(account.active_boosts[0].boost_id, 9);
+    assert_eq!(account.active_boosts[0].rig_id, Some(2));
+    assert_eq!(account.active_boosts[0].charges, 1);
+}
+
+#[test]
+fn versioned_user_account_round_trips() {
+    let account = UserAccount {
+        version: USER_ACCOUNT_VERSION,
+        owner: Pubkey::new_unique(),
+        boost_points: 3,
+        ..Default::default()
+    };
+    let mut data = vec![0u8; 8 + UserAccount::LEN];
+    account.try_serialize(&mut &mut data[..]).unwrap();
+    let decoded = UserAccount::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(decoded.owner, account.owner);
+    assert_eq!(decoded.boost_points, 3);
+
+    data[8] = USER_ACCOUNT_VERSION + 1;
+    assert!(UserAccount::try_deserialize(&mut &data[..]).is_err());
+}
+
+#[test]
+fn freshly_allocated_user_account_is_current() {
+    let mut data = vec![0u8; 8 + UserAccount::LEN];
+    data[..8].copy_from_slice(&UserAccount::DISCRIMINATOR);
+    let account = UserAccount::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(account.version, USER_ACCOUNT_VERSION);
+    assert!(account.active_boosts.is_empty());
+}
+
+#[test]
+fn highest_only_charges_just_the_winning_boost() {
+    let mut global = sample_global();
+    global.boost_stacking[BoostKind::MiningRewardBps.index()] = StackingMode::HighestOnly;
+    let mut user = user_with_boosts(vec![
+        UserBoost {
+            charges: 2,
+            ..active_boost(BoostKind::MiningRewardBps, 12_000)
+        },
+        UserBoost {
+            charges: 2,
+            ..active_boost(BoostKind::MiningRewardBps, 15_000)
+        },
+    ]);
+    let used = consume_mining_charges(&global, &mut user, 0, true, 0);
+    assert_eq!(used.len(), 1);
+    assert_eq!(user.active_boosts[0].charges, 2);
+    assert_eq!(user.active_boosts[1].charges, 1);
+
+    // Multiplied boosts all change the outcome.
+    global.boost_stacking[BoostKind::MiningRewardBps.index()] = StackingMode::Multiplicative;
+    assert_eq!(
+        consume_mining_charges(&global, &mut user, 0, true, 0).len(),
+        2
+    );
+}
+
+#[test]
+fn discounts_are_not_charged_on_free_runs() {
+    let global = sample_global();
+    let mut user = user_with_boosts(vec![UserBoost {
+        charges: 2,
+        ..active_boost(BoostKind::RigCostDiscountBps, 5_000)
+    }]);
+    assert!(consume_mining_charges(&global, &mut user, 0, false, 0).is_empty());
+    assert_eq!(user.active_boosts[0].charges, 2);
+    assert_eq!(
+        consume_mining_charges(&global, &mut user, 0, true, 0).len(),
+        1
+    );
+}
+
+// user-045: lock reduction floor and rig-targeted mining boosts.
+
+#[test]
+fn lock_reduction_shortens_the_lock_down_to_the_floor() {
+    assert_eq!(reduced_lock_secs(30, 0), 30 * 86_400);
+    assert_eq!(reduced_lock_secs(30, 5_000), 15 * 86_400);
+    // Clamped to MIN_LOCK_SECS however large the reduction.
+    assert_eq!(reduced_lock_secs(7, 9_900), MIN_LOCK_SECS);
+    assert_eq!(reduced_lock_secs(7, 10_000), MIN_LOCK_SECS);
+    // The multiplier still comes from the tier chosen.
+    assert_eq!(lock_multiplier(30).unwrap(), 12_000);
+    assert!(lock_multiplier(15).is_err());
+}
+
+#[test]
+fn mining_boosts_skip_other_rigs() {
+    let global = sample_global();
+    let mut user = user_with_boosts(vec![
+        UserBoost {
+            rig_id: Some(1),
+            ..active_boost(BoostKind::MiningRewardBps, 20_000)
+        },
+        active_boost(BoostKind::MiningPointsBps, 15_000),
+    ]);
+    assert_eq!(
+        apply_mining_reward_boost(&global, &mut user, 100, 1, 0),
+        200
+    );
+    assert_eq!(
+        apply_mining_reward_boost(&global, &mut user, 100, 2, 0),
+        100
+    );
+    assert_eq!(apply_mining_point_boost(&global, &mut user, 100, 2, 0), 150);
+}
+
+#[test]
+fn mining_charges_are_only_used_on_the_targeted_rig() {
+    let mut user = user_with_boosts(vec![
+        UserBoost {
+            rig_id: Some(1),
+            charges: 2,
+            ..active_boost(BoostKind::MiningRewardBps, 20_000)
+        },
+        UserBoost {
+            charges: 2,
+            ..active_boost(BoostKind::MiningPointsBps, 15_000)
+        },
+    ]);
+    let global = sample_global();
+    let used = consume_mining_charges(&global, &mut user, 2, true, 0);
+    assert_eq!(used.len(), 1);
+    assert_eq!(user.active_boosts[0].charges, 2);
+    assert_eq!(user.active_boosts[1].charges, 1);
+
+    consume_mining_charges(&global, &mut user, 1, true, 0);
+    assert_eq!(user.active_boosts[0].charges, 1);
+}
+
+// user-046: refunds for charges, staking boosts and inventory activations.
+
+fn refundable_boost(kind: BoostKind, charges: u32, expires_at_ts: i64) -> UserBoost {
+    UserBoost {
+        charges,
+        expires_at_ts,
+        refundable_points: 300,
+        activated_at_ts: 0,
+        ..active_boost(kind, 12_000)
+    }
+}
+
+#[test]
+fn timed_refunds_follow_the_unused_duration() {
+    let boost = refundable_boost(BoostKind::MiningRewardBps, 0, 100);
+    assert_eq!(boost.refund_amount(25, 0), 225);
+    assert_eq!(boost.refund_amount(100, 0), 0);
+    // No expiry and no charges: nothing to measure, nothing refunded.
+    let boost = refundable_boost(BoostKind::MiningRewardBps, 0, 0);
+    assert_eq!(boost.refund_amount(25, 0), 0);
+}
+
+#[test]
+fn charge_refunds_cover_only_unused_charges() {
+    let boost = refundable_boost(BoostKind::MiningPointsBps, 1, 0);
+    assert_eq!(boost.refund_amount(50, 3), 100);
+    // Timed and charged: both shares apply.
+    let boost = refundable_boost(BoostKind::MiningPointsBps, 2, 100);
+    assert_eq!(boost.refund_amount(50, 4), 75);
+    // A config that lowered its charges since never refunds more than paid.
+    let boost = refundable_boost(BoostKind::FreeRigTicket, 5, 0);
+    assert_eq!(boost.refund_amount(0, 1), 300);
+}
+
+#[test]
+fn initial_charges_match_activation() {
+    let config = sample_boost_config(BoostKind::FreeRigTicket);
+    assert_eq!(config.initial_charges(), 1);
+    let config = BoostConfig {
+        charges: 4,
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    assert_eq!(config.initial_charges(), 4);
+
+    let mut user = UserAccount::default();
+    push_user_boost(&mut user, &config, 0, 0).unwrap();
+    assert_eq!(user.active_boosts[0].charges, config.initial_charges());
+}
+
+#[test]
+fn only_refundable_point_payments_are_refundable() {
+    let config = BoostConfig {
+        refundable: true,
+        ..sample_boost_config(BoostKind::MiningRewardBps)
+    };
+    assert_eq!(config.refundable_points(BoostPayment::BoostPoints, 80), 80);
+    assert_eq!(config.refundable_points(BoostPayment::Game, 80), 0);
+    let config = sample_boost_config(BoostKind::MiningRewardBps);
+    assert_eq!(config.refundable_points(BoostPayment::BoostPoints, 80), 0);
+}
+
+#[test]
+fn inventory_carries_refundable_cost_pro_rata() {
+    let mut inventory = empty_inventory();
+    inventory.add(7, 1, 100).unwrap();
+    inventory.add(7, 1, 0).unwrap();
+    inventory.add(7, 1, 200).unwrap();
+    assert_eq!(inventory.items[0].refundable_points, 300);
+
+    assert_eq!(inventory.take(7, 1).unwrap(), 100);
+    assert_eq!(inventory.take(7, 2).unwrap(), 200);
+    assert!(inventory.items.is_empty());
+}
+
+// user-047: boost configs move to their two-byte id.
+
+#[test]
+fn original_boost_configs_migrate_priced_in_points() {
+    let mut data = vec![3u8, BoostKind::MiningRewardBps as u8];
+    10u64.serialize(&mut data).unwrap(); // cost_boost_points
+    12_000u16.serialize(&mut data).unwrap();
+    3_600i64.serialize(&mut data).unwrap();
+    None::<u8>.serialize(&mut data).unwrap();
+    data.extend_from_slice(&[0; 8]); // padding
+
+    let config = BoostConfig::from(LegacyBoostConfig::deserialize(&mut &data[..]).unwrap());
+    assert_eq!(config.id, 3);
+    assert_eq!(config.cost_boost_points, Some(10));
+    assert_eq!(config.price_game, None);
+    assert_eq!(config.price_xnt, None);
+    assert!(config.enabled);
+}
+
+// user-048: forward-only day rollover and offsets applied at day boundaries.
+
+const DAY: i64 = 86_400;
+
+#[test]
+fn rollover_ignores_past_days() {
+    let mut user = UserAccount {
+        daily_points: 40,
+        last_day_id: 10,
+        ..Default::default()
+    };
+    user.roll_over_day(9);
+    user.roll_over_day(10);
+    assert_eq!(user.daily_points, 40);
+    assert_eq!(user.last_day_id, 10);
+
+    user.roll_over_day(11);
+    assert_eq!(user.yesterday_points, 40);
+    assert_eq!(user.daily_points, 0);
+    user.roll_over_day(13);
+    assert_eq!(user.yesterday_points, 0);
+}
+
+#[test]
+fn day_offset_waits_for_the_next_boundary() {
+    let mut global = sample_global();
+    let now = 100 * DAY + 3_600;
+    global.schedule_day_offset(6 * 3_600, now);
+    // Still day 100 until midnight UTC, even though the new offset would
+    // already say otherwise.
+    assert_eq!(global.day_id(now), 100);
+    assert_eq!(global.day_id(101 * DAY - 1), 100);
+    assert_eq!(global.day_id(101 * DAY), 101);
+    // From then on days turn at 18:00 UTC.
+    assert_eq!(global.day_id(102 * DAY - 6 * 3_600 - 1), 101);
+    assert_eq!(global.day_id(102 * DAY - 6 * 3_600), 102);
+}
+
+#[test]
+fn day_ids_never_repeat_across_offset_changes() {
+    let mut global = sample_global();
+    global.schedule_day_offset(MAX_DAY_OFFSET_SECS, 50 * DAY);
+    let mut now = 50 * DAY;
+    let mut last = global.day_id(now);
+    while now < 60 * DAY {
+        if now == 53 * DAY + 7 {
+            global.schedule_day_offset(-MAX_DAY_OFFSET_SECS, now);
+        }
+        let day = global.day_id(now);
+        assert!(day == last || day == last + 1, "day {day} after {last}");
+        last = day;
+        now += 900 + 7;
+    }
+    assert!(last >= 59);
+}
+
+#[test]
+fn due_offset_changes_become_current_when_replaced() {
+    let mut global = sample_global();
+    global.schedule_day_offset(3_600, 10 * DAY);
+    global.schedule_day_offset(7_200, 20 * DAY);
+    assert_eq!(global.day_offset_secs, 3_600);
+    assert_eq!(global.pending_day_offset_secs, 7_200);
+    assert_eq!(global.day_id(20 * DAY), 20);
+}
+
+// user-049: the leaderboard only moves forward.
+
+fn leaderboard_on(day_id: i64) -> Leaderboard {
+    Leaderboard {
+        day_id,
+        entries: Vec::new(),
+        previous_day_id: day_id - 1,
+        previous: Vec::new(),
+    }
+}
+
+#[test]
+fn leaderboard_rolls_over_forward_only() {
+    let mut leaderboard = leaderboard_on(10);
+    let user = Pubkey::new_unique();
+    leaderboard.record(user, 5);
+
+    assert!(!leaderboard.roll_over(9));
+    assert!(!leaderboard.roll_over(10));
+    assert_eq!(leaderboard.entries.len(), 1);
+
+    assert!(leaderboard.roll_over(11));
+    assert_eq!(leaderboard.previous_day_id, 10);
+    assert_eq!(leaderboard.previous[0].user, user);
+    assert!(leaderboard.entries.is_empty());
+}
+
+#[test]
+fn stale_totals_stay_off_the_leaderboard() {
+    let mut leaderboard = leaderboard_on(10);
+    update_leaderboard(&mut leaderboard, 9, Pubkey::new_unique(), 50);
+    assert!(leaderboard.entries.is_empty());
+    assert_eq!(leaderboard.day_id, 10);
+
+    let user = Pubkey::new_unique();
+    update_leaderboard(&mut leaderboard, 11, user, 7);
+    assert_eq!(leaderboard.day_id, 11);
+    assert_eq!(leaderboard.entries[0].user, user);
+}
+
+#[test]
+fn leaderboard_keeps_the_top_totals() {
+    let mut leaderboard = leaderboard_on(1);
+    for points in 1..=LEADERBOARD_SIZE as u32 + 2 {
+        leaderboard.record(Pubkey::new_unique(), points);
+    }
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+    assert_eq!(leaderboard.entries[0].points, LEADERBOARD_SIZE as u32 + 2);
+    assert_eq!(leaderboard.entries.last().unwrap().points, 3);
+}
+
+#[test]
+fn mining_requires_the_leaderboard_once_initialized() {
+    const DAY: i64 = 86_400;
+    let mut global = sample_global();
+    let user = Pubkey::new_unique();
+    record_leaderboard_points(&global, None, 10 * DAY, user, 5).unwrap();
+
+    global.leaderboard_initialized = true;
+    let err = record_leaderboard_points(&global, None, 10 * DAY, user, 5).unwrap_err();
+    assert_eq!(err, ArenaError::LeaderboardRequired.into());
+
+    let mut leaderboard = leaderboard_on(10);
+    record_leaderboard_points(&global, Some(&mut leaderboard), 10 * DAY, user, 5).unwrap();
+    assert_eq!(leaderboard.entries[0].user, user);
+}
+
+// user-050: ranking merkle claims.
+
+type Proof = Vec<[u8; 32]>;
+
+fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    keccak::hashv(&[&left, &right]).to_bytes()
+}
+
+/// Builds the tree the way `sdk/merkle.ts` does: an odd node out is carried
+/// up unchanged. Returns the root and each leaf's proof.
+fn ranking_tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Proof>) {
+    let mut proofs = vec![Vec::new(); leaves.len()];
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        for (leaf, position) in positions.iter_mut().enumerate() {
+            let sibling = *position ^ 1;
+            if sibling < level.len() {
+                proofs[leaf].push(level[sibling]);
+            }
+            *position /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    (level[0], proofs)
+}
+
+fn sample_ranking() -> (Vec<(Pubkey, u64)>, [u8; 32], Vec<Proof>) {
+    let allocations: Vec<(Pubkey, u64)> = (0..5u64)
+        .map(|i| (Pubkey::new_unique(), 100 * (i + 1)))
+        .collect();
+    let leaves: Vec<[u8; 32]> = allocations
+        .iter()
+        .enumerate()
+        .map(|(i, (user, points))| ranking_leaf(i as u32, user, *points))
+        .collect();
+    let (root, proofs) = ranking_tree(&leaves);
+    (allocations, root, proofs)
+}
+
+#[test]
+fn every_ranking_leaf_verifies() {
+    let (allocations, root, proofs) = sample_ranking();
+    for (i, (user, points)) in allocations.iter().enumerate() {
+        let leaf = ranking_leaf(i as u32, user, *points);
+        assert!(verify_merkle_proof(&proofs[i], root, leaf), "leaf {i}");
+    }
+}
+
+#[test]
+fn ranking_proofs_reject_wrong_leaves() {
+    let (allocations, root, proofs) = sample_ranking();
+    let (user, points) = allocations[2];
+    // Wrong amount, wrong user and wrong index.
+    assert!(!verify_merkle_proof(
+        &proofs[2],
+        root,
+        ranking_leaf(2, &user, points + 1)
+    ));
+    assert!(!verify_merkle_proof(
+        &proofs[2],
+        root,
+        ranking_leaf(2, &Pubkey::new_unique(), points)
+    ));
+    assert!(!verify_merkle_proof(
+        &proofs[2],
+        root,
+        ranking_leaf(3, &user, points)
+    ));
+    // Another leaf's proof does not fit.
+    assert!(!verify_merkle_proof(
+        &proofs[1],
+        root,
+        ranking_leaf(2, &user, points)
+    ));
+}
+
+#[test]
+fn ranking_claims_are_tracked_per_leaf() {
+    let mut epoch = RankingEpoch {
+        epoch_id: 1,
+        merkle_root: [0; 32],
+        leaf_count: 10,
+        total_points: 0,
+        claimed_points: 0,
+        claimed_bitmap: vec![0; RankingEpoch::bitmap_len(10)],
+    };
+    assert_eq!(epoch.claimed_bitmap.len(), 2);
+    epoch.set_claimed(9);
+    assert!(epoch.is_claimed(9));
+    assert!(!epoch.is_claimed(8));
+    assert!(!epoch.is_claimed(1));
+}
+
+#[test]
+fn ranking_claims_beyond_the_epoch_total_are_exhausted() {
+    let mut epoch = RankingEpoch {
+        epoch_id: 1,
+        merkle_root: [0; 32],
+        leaf_count: 2,
+        total_points: 100,
+        claimed_points: 0,
+        claimed_bitmap: vec![0; RankingEpoch::bitmap_len(2)],
+    };
+    epoch.record_claim(0, 60).unwrap();
+    assert_eq!(epoch.claimed_points, 60);
+    assert!(epoch.is_claimed(0));
+
+    let err = epoch.record_claim(1, 50).unwrap_err();
+    assert_eq!(err, ArenaError::RankingEpochExhausted.into());
+    assert_eq!(epoch.claimed_points, 60);
+    assert!(!epoch.is_claimed(1));
+}
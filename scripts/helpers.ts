@@ -76,6 +76,10 @@ export function decodeUserStakePosition(data: Buffer) {
   return coder.decode("userStakePosition", data);
 }
 
+export function decodeUserPositions(data: Buffer) {
+  return coder.decode("userPositions", data);
+}
+
 export function decodeGlobalConfig(data: Buffer) {
   return coder.decode("globalConfig", data);
 }
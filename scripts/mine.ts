@@ -14,6 +14,7 @@ import { PublicKey } from "@solana/web3.js";
 import { ixMineWithRig, PROGRAM_ID } from "../sdk/client";
 import {
   findGlobalConfig,
+  findLeaderboard,
   findStakingPool,
   findUserAccount,
 } from "../sdk/pdas";
@@ -72,6 +73,9 @@ async function main() {
     data.subarray(offset + 32 * 3, offset + 32 * 4)
   );
 
+  const [leaderboard] = findLeaderboard(PROGRAM_ID);
+  const leaderboardInfo = await connection.getAccountInfo(leaderboard);
+
   const sig = await sendTx(
     connection,
     payer,
@@ -83,6 +87,7 @@ async function main() {
         userXntAccount: userXntAta,
         treasuryXntVault: treasuryVault,
         rigId: RIG_ID,
+        leaderboard: leaderboardInfo !== null,
       }),
     ],
     []
@@ -11,9 +11,23 @@ import {
 import { createHash } from "crypto";
 import {
   findBoostConfig,
+  findBoostInventory,
+  findBoostReceipt,
   findGlobalConfig,
+  findLeaderboard,
+  findRankingEpoch,
+  findLegacyBoostConfig,
+  findLiquidShareMint,
+  findLiquidUnbond,
+  findLiquidDeposit,
+  findLiquidVault,
+  findLiquidXntVault,
   findStakingPool,
+  findTreasuryPolicy,
+  findTreasuryPolicyChange,
+  findTreasuryWithdrawal,
   findUserAccount,
+  findUserPositions,
   findUserStake,
   findUserStakePosition,
 } from "./pdas";
@@ -37,17 +51,32 @@ const toU16 = (v: BNish) => {
   b.writeUInt16LE(Number(v));
   return b;
 };
+const toU32 = (v: BNish) => {
+  const b = Buffer.alloc(4);
+  b.writeUInt32LE(Number(v));
+  return b;
+};
 const toU64 = (v: BNish) => {
   const b = Buffer.alloc(8);
   b.writeBigUInt64LE(BigInt(v));
   return b;
 };
+const toU128 = (v: BNish) => {
+  const b = Buffer.alloc(16);
+  const n = BigInt(v);
+  b.writeBigUInt64LE(n & 0xffffffffffffffffn);
+  b.writeBigUInt64LE(n >> 64n, 8);
+  return b;
+};
 const toI64 = (v: BNish) => {
   const b = Buffer.alloc(8);
   b.writeBigInt64LE(BigInt(v));
   return b;
 };
 
+const toOption = (v: BNish | undefined, encode: (v: BNish) => Buffer) =>
+  v === undefined ? Buffer.from([0]) : Buffer.concat([Buffer.from([1]), encode(v)]);
+
 function discriminator(name: string) {
   return createHash("sha256").update(`global:${name}`).digest().subarray(0, 8);
 }
@@ -64,6 +93,13 @@ function ix(
   });
 }
 
+/** Position PDA; id 0 is the legacy single-position account. */
+function findPosition(seedOwner: PublicKey, positionId: number) {
+  return positionId === 0
+    ? findUserStake(seedOwner, PROGRAM_ID)
+    : findUserStakePosition(seedOwner, positionId, PROGRAM_ID);
+}
+
 // Instruction builders
 
 export function ixInitializeGlobal(params: {
@@ -139,11 +175,14 @@ export function ixMineWithRig(params: {
   userXntAccount: PublicKey;
   treasuryXntVault: PublicKey;
   rigId: number;
+  /** Set once `initialize_leaderboard` has run; the program then requires it. */
+  leaderboard?: boolean;
 }) {
   const { payer, gameMint, userGameAccount, userXntAccount, treasuryXntVault, rigId } = params;
   const [globalConfig] = findGlobalConfig(PROGRAM_ID);
   const [stakingPool] = findStakingPool(PROGRAM_ID);
   const [userAccount] = findUserAccount(payer, PROGRAM_ID);
+  const leaderboard = params.leaderboard ? findLeaderboard(PROGRAM_ID)[0] : PROGRAM_ID;
   return ix("mine_with_rig", [toU8(rigId)], [
     { pubkey: payer, isSigner: true, isWritable: true },
     { pubkey: globalConfig, isSigner: false, isWritable: true },
@@ -157,6 +196,7 @@ export function ixMineWithRig(params: {
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
     { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: leaderboard, isSigner: false, isWritable: true },
   ]);
 }
 
@@ -188,6 +228,7 @@ export function ixStake(params: {
     PROGRAM_ID
   );
   const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  const [userPositions] = findUserPositions(owner, PROGRAM_ID);
   return ix("stake", [toU64(amount), toU16(lockDays)], [
     { pubkey: owner, isSigner: true, isWritable: true },
     { pubkey: globalConfig, isSigner: false, isWritable: true },
@@ -196,8 +237,9 @@ export function ixStake(params: {
     { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
     { pubkey: userGameAccount, isSigner: false, isWritable: true },
     { pubkey: userXntAccount, isSigner: false, isWritable: true },
-    { pubkey: userStakePosition, isSigner: false, isWritable: true },
     { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: userStakePosition, isSigner: false, isWritable: true },
+    { pubkey: userPositions, isSigner: false, isWritable: true },
     { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
@@ -210,14 +252,17 @@ export function ixClaim(params: {
   treasuryXntVault: PublicKey;
   userXntAccount: PublicKey;
   positionId: number;
+  /** Original staker whose key seeds the PDA, if the position was transferred. */
+  seedOwner?: PublicKey;
 }) {
   const { owner, treasuryXntVault, userXntAccount, positionId } = params;
   const [globalConfig] = findGlobalConfig(PROGRAM_ID);
   const [stakingPool] = findStakingPool(PROGRAM_ID);
-  const [userStakePosition] =
-    positionId === 0
-      ? findUserStake(owner, PROGRAM_ID)
-      : findUserStakePosition(owner, positionId, PROGRAM_ID);
+  const [userStakePosition] = findPosition(
+    params.seedOwner ?? owner,
+    positionId
+  );
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
   return ix("claim", [], [
     { pubkey: owner, isSigner: true, isWritable: true },
     { pubkey: globalConfig, isSigner: false, isWritable: true },
@@ -225,10 +270,16 @@ export function ixClaim(params: {
     { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
     { pubkey: userXntAccount, isSigner: false, isWritable: true },
     { pubkey: userStakePosition, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
     { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
   ]);
 }
 
+/**
+ * With an unbonding period configured the first call starts unbonding and a
+ * call after `unbondingUntilTs` withdraws.
+ */
 export function ixUnstake(params: {
   owner: PublicKey;
   stakingVault: PublicKey;
@@ -236,6 +287,7 @@ export function ixUnstake(params: {
   userGameAccount: PublicKey;
   userXntAccount: PublicKey;
   positionId: number;
+  seedOwner?: PublicKey;
 }) {
   const {
     owner,
@@ -247,10 +299,7 @@ export function ixUnstake(params: {
   } = params;
   const [globalConfig] = findGlobalConfig(PROGRAM_ID);
   const [stakingPool] = findStakingPool(PROGRAM_ID);
-  const [stakePosition] =
-    positionId === 0
-      ? findUserStake(owner, PROGRAM_ID)
-      : findUserStakePosition(owner, positionId, PROGRAM_ID);
+  const [stakePosition] = findPosition(params.seedOwner ?? owner, positionId);
   return ix("unstake", [], [
     { pubkey: owner, isSigner: true, isWritable: true },
     { pubkey: globalConfig, isSigner: false, isWritable: true },
@@ -260,35 +309,178 @@ export function ixUnstake(params: {
     { pubkey: userGameAccount, isSigner: false, isWritable: true },
     { pubkey: userXntAccount, isSigner: false, isWritable: true },
     { pubkey: stakePosition, isSigner: false, isWritable: true },
+    { pubkey: findUserAccount(owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: findUserPositions(owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
     { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
   ]);
 }
 
+/** 0: BoostPoints, 1: Game (burned), 2: Xnt */
+export type BoostPayment = 0 | 1 | 2;
+
 export function ixActivateBoost(params: {
   user: PublicKey;
   boostId: number;
+  payment?: BoostPayment;
+  /** Required when paying with GAME. */
+  gameMint?: PublicKey;
+  userGameAccount?: PublicKey;
+  /** Required when paying with XNT. */
+  userXntAccount?: PublicKey;
+  treasuryXntVault?: PublicKey;
 }) {
-  const { user, boostId } = params;
+  const { user, boostId, payment = 0 } = params;
   const [globalConfig] = findGlobalConfig(PROGRAM_ID);
   const [boostConfig] = findBoostConfig(boostId, PROGRAM_ID);
   const [userAccount] = findUserAccount(user, PROGRAM_ID);
-  return ix("activate_boost", [], [
+  const [boostReceipt] = findBoostReceipt(user, boostId, PROGRAM_ID);
+  return ix("activate_boost", [toU8(payment)], [
+    { pubkey: user, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: boostConfig, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: boostReceipt, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...boostPaymentKeys(params, payment),
+  ]);
+}
+
+export function ixPurchaseBoost(params: {
+  user: PublicKey;
+  boostId: number;
+  payment?: BoostPayment;
+  gameMint?: PublicKey;
+  userGameAccount?: PublicKey;
+  userXntAccount?: PublicKey;
+  treasuryXntVault?: PublicKey;
+}) {
+  const { user, boostId, payment = 0 } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [boostConfig] = findBoostConfig(boostId, PROGRAM_ID);
+  const [userAccount] = findUserAccount(user, PROGRAM_ID);
+  const [boostReceipt] = findBoostReceipt(user, boostId, PROGRAM_ID);
+  const [boostInventory] = findBoostInventory(user, PROGRAM_ID);
+  return ix("purchase_boost", [toU8(payment)], [
+    { pubkey: user, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: boostConfig, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: boostReceipt, isSigner: false, isWritable: true },
+    { pubkey: boostInventory, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...boostPaymentKeys(params, payment),
+  ]);
+}
+
+export function ixActivateFromInventory(params: {
+  user: PublicKey;
+  boostId: number;
+}) {
+  const { user, boostId } = params;
+  const [boostConfig] = findBoostConfig(boostId, PROGRAM_ID);
+  const [userAccount] = findUserAccount(user, PROGRAM_ID);
+  const [boostInventory] = findBoostInventory(user, PROGRAM_ID);
+  return ix("activate_from_inventory", [], [
+    { pubkey: user, isSigner: true, isWritable: true },
+    { pubkey: boostConfig, isSigner: false, isWritable: false },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: boostInventory, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixGiftBoost(params: {
+  sender: PublicKey;
+  recipient: PublicKey;
+  boostId: number;
+  count: number;
+}) {
+  const { sender, recipient, boostId, count } = params;
+  const [senderInventory] = findBoostInventory(sender, PROGRAM_ID);
+  const [recipientInventory] = findBoostInventory(recipient, PROGRAM_ID);
+  return ix("gift_boost", [toU16(boostId), toU32(count)], [
+    { pubkey: sender, isSigner: true, isWritable: true },
+    { pubkey: recipient, isSigner: false, isWritable: false },
+    { pubkey: senderInventory, isSigner: false, isWritable: true },
+    { pubkey: recipientInventory, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+/**
+ * `index` counts the user's unexpired boosts in order; `boostId` is the id of
+ * the boost at that index. Without `boostId` (e.g. once its config is closed)
+ * the boost ends without a refund.
+ */
+export function ixDeactivateBoost(params: {
+  user: PublicKey;
+  index: number;
+  boostId?: number;
+}) {
+  const { user, index, boostId } = params;
+  const boostConfig =
+    boostId === undefined ? PROGRAM_ID : findBoostConfig(boostId, PROGRAM_ID)[0];
+  const [userAccount] = findUserAccount(user, PROGRAM_ID);
+  return ix("deactivate_boost", [toU8(index)], [
     { pubkey: user, isSigner: true, isWritable: true },
-    { pubkey: globalConfig, isSigner: false, isWritable: false },
     { pubkey: boostConfig, isSigner: false, isWritable: false },
     { pubkey: userAccount, isSigner: false, isWritable: true },
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
   ]);
 }
 
+/** Optional payment accounts; unused slots are filled with the program id. */
+function boostPaymentKeys(
+  params: {
+    gameMint?: PublicKey;
+    userGameAccount?: PublicKey;
+    userXntAccount?: PublicKey;
+    treasuryXntVault?: PublicKey;
+  },
+  payment: BoostPayment
+) {
+  const opt = (pubkey: PublicKey | undefined, used: boolean) =>
+    used && pubkey
+      ? { pubkey, isSigner: false, isWritable: true }
+      : { pubkey: PROGRAM_ID, isSigner: false, isWritable: false };
+  const game = payment === 1;
+  const xnt = payment === 2;
+  return [
+    opt(findStakingPool(PROGRAM_ID)[0], xnt),
+    opt(params.gameMint, game),
+    opt(params.userGameAccount, game),
+    opt(params.userXntAccount, xnt),
+    opt(params.treasuryXntVault, xnt),
+    game || xnt
+      ? { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false }
+      : opt(undefined, false),
+  ];
+}
+
 export function ixUpsertBoostConfig(params: {
   admin: PublicKey;
   id: number;
-  kind: number; // 0: MiningRewardBps, 1: MiningPointsBps, 2: FreeRigTicket, 3: StakingMultiplierBps
-  costBoostPoints: bigint;
+  kind: number; // 0: MiningRewardBps, 1: MiningPointsBps, 2: FreeRigTicket, 3: StakingMultiplierBps,
+  // 4: RigCostDiscountBps, 5: CritChanceBps, 6: LockReductionBps
+  /** Prices in base units; omit a currency to refuse it, 0n makes it free. */
+  costBoostPoints?: bigint;
   valueBps: number;
   durationSeconds: bigint;
   rigId?: number;
+  /** Sale window in unix seconds; 0 leaves that side open. */
+  startTs?: bigint;
+  endTs?: bigint;
+  /** 0 means unlimited. */
+  maxSupply?: bigint;
+  perUserLimit?: number;
+  enabled?: boolean;
+  priceGame?: bigint;
+  priceXnt?: bigint;
+  /** Mining runs per activation; 0 means time-limited only. */
+  charges?: number;
+  /** Refund unused boost points pro rata on deactivation. */
+  refundable?: boolean;
 }) {
   const {
     admin,
@@ -298,24 +490,42 @@ export function ixUpsertBoostConfig(params: {
     valueBps,
     durationSeconds,
     rigId,
+    startTs = 0n,
+    endTs = 0n,
+    maxSupply = 0n,
+    perUserLimit = 0,
+    enabled = true,
+    priceGame,
+    priceXnt,
+    charges = 0,
+    refundable = false,
   } = params;
   const [globalConfig] = findGlobalConfig(PROGRAM_ID);
   const [boostConfig] = findBoostConfig(id, PROGRAM_ID);
 
-  // encode args manually: id(u8) kind(u8) cost(u64) value_bps(u16) duration(i64) rig_id(option u8)
-  const buf = Buffer.alloc(8 + 1 + 1 + 8 + 2 + 8 + 2); // discriminator + fields (rig option up to 2 bytes)
+  // encode args manually: id(u16) kind(u8) cost(option u64) value_bps(u16) duration(i64) rig_id(option u8)
+  // start_ts(i64) end_ts(i64) max_supply(u64) per_user_limit(u32) enabled(bool)
+  // price_game(option u64) price_xnt(option u64) charges(u32) refundable(bool)
+  const buf = Buffer.alloc(8 + 2 + 1 + 9 + 2 + 8 + 2 + 8 + 8 + 8 + 4 + 1 + 9 + 9 + 4 + 1); // discriminator + fields (options at their largest)
   const disc = createHash("sha256")
     .update("global:upsert_boost_config")
     .digest()
     .subarray(0, 8);
   disc.copy(buf, 0);
   let offset = 8;
-  buf.writeUInt8(id, offset);
-  offset += 1;
+  const writeOptionU64 = (value?: bigint) => {
+    buf.writeUInt8(value === undefined ? 0 : 1, offset);
+    offset += 1;
+    if (value !== undefined) {
+      buf.writeBigUInt64LE(BigInt(value), offset);
+      offset += 8;
+    }
+  };
+  buf.writeUInt16LE(id, offset);
+  offset += 2;
   buf.writeUInt8(kind, offset);
   offset += 1;
-  buf.writeBigUInt64LE(BigInt(costBoostPoints), offset);
-  offset += 8;
+  writeOptionU64(costBoostPoints);
   buf.writeUInt16LE(valueBps, offset);
   offset += 2;
   buf.writeBigInt64LE(BigInt(durationSeconds), offset);
@@ -329,6 +539,22 @@ export function ixUpsertBoostConfig(params: {
     buf.writeUInt8(0, offset);
     offset += 1;
   }
+  buf.writeBigInt64LE(BigInt(startTs), offset);
+  offset += 8;
+  buf.writeBigInt64LE(BigInt(endTs), offset);
+  offset += 8;
+  buf.writeBigUInt64LE(BigInt(maxSupply), offset);
+  offset += 8;
+  buf.writeUInt32LE(perUserLimit, offset);
+  offset += 4;
+  buf.writeUInt8(enabled ? 1 : 0, offset);
+  offset += 1;
+  writeOptionU64(priceGame);
+  writeOptionU64(priceXnt);
+  buf.writeUInt32LE(charges, offset);
+  offset += 4;
+  buf.writeUInt8(refundable ? 1 : 0, offset);
+  offset += 1;
   const data = buf.subarray(0, offset);
 
   return new TransactionInstruction({
@@ -359,3 +585,936 @@ export function ixApplyRankingResults(params: {
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
   ]);
 }
+
+/**
+ * Publishes a ranking merkle root. Leaves are
+ * keccak256(index u32 LE || user || boostPoints u64 LE); parents hash the
+ * sorted pair of children (see `buildRankingTree` in ./merkle). Epoch ids
+ * must increase.
+ */
+export function ixPublishRankingEpoch(params: {
+  admin: PublicKey;
+  epochId: BNish;
+  merkleRoot: Uint8Array;
+  leafCount: number;
+  totalPoints: BNish;
+}) {
+  const { admin, epochId, merkleRoot, leafCount, totalPoints } = params;
+  if (merkleRoot.length !== 32) {
+    throw new Error("merkleRoot must be 32 bytes");
+  }
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [rankingEpoch] = findRankingEpoch(epochId, PROGRAM_ID);
+  return ix(
+    "publish_ranking_epoch",
+    [toU64(epochId), Buffer.from(merkleRoot), toU32(leafCount), toU64(totalPoints)],
+    [
+      { pubkey: admin, isSigner: true, isWritable: true },
+      { pubkey: globalConfig, isSigner: false, isWritable: true },
+      { pubkey: rankingEpoch, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+/** Closes a ranking epoch; unclaimed leaves are forfeited. */
+export function ixCloseRankingEpoch(params: { admin: PublicKey; epochId: BNish }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [rankingEpoch] = findRankingEpoch(params.epochId, PROGRAM_ID);
+  return ix("close_ranking_epoch", [toU64(params.epochId)], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: rankingEpoch, isSigner: false, isWritable: true },
+  ]);
+}
+
+export function ixClaimRankingReward(params: {
+  user: PublicKey;
+  epochId: BNish;
+  index: number;
+  boostPoints: BNish;
+  proof: Uint8Array[];
+}) {
+  const { user, epochId, index, boostPoints, proof } = params;
+  const [rankingEpoch] = findRankingEpoch(epochId, PROGRAM_ID);
+  const [userAccount] = findUserAccount(user, PROGRAM_ID);
+  return ix(
+    "claim_ranking_reward",
+    [
+      toU64(epochId),
+      toU32(index),
+      toU64(boostPoints),
+      toU32(proof.length),
+      ...proof.map((node) => Buffer.from(node)),
+    ],
+    [
+      { pubkey: user, isSigner: true, isWritable: true },
+      { pubkey: rankingEpoch, isSigner: false, isWritable: true },
+      { pubkey: userAccount, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+export function ixDistributePendingRewards() {
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  return ix("distribute_pending_rewards", [], [
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+  ]);
+}
+
+export function ixConfigureStakingPool(params: {
+  admin: PublicKey;
+  rewardDurationSecs?: BNish;
+  unbondingPeriodSecs?: BNish;
+  warmupPeriodSecs?: BNish;
+  maxPositionAmount?: BNish;
+  maxUserEffectiveStake?: BNish;
+  maxUserShareBps?: BNish;
+}) {
+  const { admin, rewardDurationSecs, unbondingPeriodSecs, warmupPeriodSecs } =
+    params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const data = [
+    toOption(rewardDurationSecs, toI64),
+    toOption(unbondingPeriodSecs, toI64),
+    toOption(warmupPeriodSecs, toI64),
+    toOption(params.maxPositionAmount, toU64),
+    toOption(params.maxUserEffectiveStake, toU128),
+    toOption(params.maxUserShareBps, toU16),
+  ];
+  return ix("configure_staking_pool", data, [
+    { pubkey: admin, isSigner: true, isWritable: false },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+  ]);
+}
+
+export function ixClaimAll(params: {
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  /** Position ids, or `{ id, seedOwner }` for positions received by transfer. */
+  positionIds: (number | { id: number; seedOwner: PublicKey })[];
+}) {
+  const { owner, treasuryXntVault, userXntAccount, positionIds } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const positions = positionIds.map((entry) => {
+    const [pda] =
+      typeof entry === "number"
+        ? findPosition(owner, entry)
+        : findPosition(entry.seedOwner, entry.id);
+    return { pubkey: pda, isSigner: false, isWritable: true };
+  });
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  return ix("claim_all", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: userXntAccount, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...positions,
+  ]);
+}
+
+export function ixTransferPosition(params: {
+  owner: PublicKey;
+  newOwner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  positionId: number;
+  seedOwner?: PublicKey;
+}) {
+  const { owner, newOwner, treasuryXntVault, userXntAccount, positionId } =
+    params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [stakePosition] = findPosition(params.seedOwner ?? owner, positionId);
+  return ix("transfer_position", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: newOwner, isSigner: false, isWritable: false },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: userXntAccount, isSigner: false, isWritable: true },
+    { pubkey: stakePosition, isSigner: false, isWritable: true },
+    { pubkey: findUserAccount(owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: findUserAccount(newOwner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: findUserPositions(owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: findUserPositions(newOwner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixMigrateStakePosition(params: {
+  payer: PublicKey;
+  seedOwner: PublicKey;
+  positionId: number;
+}) {
+  const { payer, seedOwner, positionId } = params;
+  const [stakePosition] = findPosition(seedOwner, positionId);
+  return ix("migrate_stake_position", [], [
+    { pubkey: payer, isSigner: true, isWritable: true },
+    { pubkey: stakePosition, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixMigrateUserAccount(params: { payer: PublicKey; user: PublicKey }) {
+  const { payer, user } = params;
+  const [userAccount] = findUserAccount(user, PROGRAM_ID);
+  return ix("migrate_user_account", [], [
+    { pubkey: payer, isSigner: true, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixSetClaimDelegate(params: {
+  owner: PublicKey;
+  /** Pass PublicKey.default to revoke. */
+  delegate: PublicKey;
+}) {
+  const { owner, delegate } = params;
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  return ix("set_claim_delegate", [delegate.toBuffer()], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+function keeperPositionKeys(params: {
+  authority: PublicKey;
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  ownerXntAccount: PublicKey;
+  positionId: number;
+  seedOwner?: PublicKey;
+}) {
+  const { authority, owner, treasuryXntVault, ownerXntAccount, positionId } =
+    params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  const [stakePosition] = findPosition(params.seedOwner ?? owner, positionId);
+  return [
+    { pubkey: authority, isSigner: true, isWritable: true },
+    { pubkey: owner, isSigner: false, isWritable: false },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: ownerXntAccount, isSigner: false, isWritable: true },
+    { pubkey: stakePosition, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ];
+}
+
+/** Claim on behalf of `owner`; signed by the owner or their claim delegate. */
+export function ixDelegatedClaim(params: Parameters<typeof keeperPositionKeys>[0]) {
+  return ix("delegated_claim", [], keeperPositionKeys(params));
+}
+
+/**
+ * Moves a warming-up position to its current ramp value. Any wallet may send
+ * it as `authority`; rewards go to the owner.
+ */
+export function ixAdvanceWarmup(params: Parameters<typeof keeperPositionKeys>[0]) {
+  return ix("advance_warmup", [], keeperPositionKeys(params));
+}
+
+/** Re-applies the owner's current staking boosts to a position. */
+export function ixRefreshPosition(params: Parameters<typeof keeperPositionKeys>[0]) {
+  return ix("refresh_position", [], keeperPositionKeys(params));
+}
+
+/**
+ * Grows the staking pool to the current layout. Pools from before solvency
+ * tracking need the admin as payer and the stakers' total pending rewards,
+ * summed off-chain, as `unclaimedRewards`.
+ */
+export function ixMigrateStakingPool(params: {
+  payer: PublicKey;
+  unclaimedRewards?: BNish;
+}) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  return ix("migrate_staking_pool", [toU64(params.unclaimedRewards ?? 0)], [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+/** Moves a single-byte-id boost config to its u16 PDA. */
+export function ixMigrateBoostConfig(params: { admin: PublicKey; boostId: number }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [legacyBoostConfig] = findLegacyBoostConfig(params.boostId, PROGRAM_ID);
+  const [boostConfig] = findBoostConfig(params.boostId, PROGRAM_ID);
+  return ix("migrate_boost_config", [toU8(params.boostId)], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: legacyBoostConfig, isSigner: false, isWritable: true },
+    { pubkey: boostConfig, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixCloseBoostConfig(params: { admin: PublicKey; boostId: number }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [boostConfig] = findBoostConfig(params.boostId, PROGRAM_ID);
+  return ix("close_boost_config", [], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: boostConfig, isSigner: false, isWritable: true },
+  ]);
+}
+
+export function ixMigrateGlobalConfig(params: { payer: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  return ix("migrate_global_config", [], [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+/** 0: Default, 1: Multiplicative, 2: Additive, 3: HighestOnly */
+export type StackingMode = 0 | 1 | 2 | 3;
+
+export function ixInitializeLeaderboard(params: { admin: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [leaderboard] = findLeaderboard(PROGRAM_ID);
+  return ix("initialize_leaderboard", [], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: leaderboard, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixFinalizeLeaderboard() {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [leaderboard] = findLeaderboard(PROGRAM_ID);
+  return ix("finalize_leaderboard", [], [
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: leaderboard, isSigner: false, isWritable: true },
+  ]);
+}
+
+/**
+ * Shifts daily point boundaries by `offsetSecs` from UTC (±14h), starting at
+ * the next day boundary.
+ */
+export function ixSetDayOffset(params: { admin: PublicKey; offsetSecs: BNish }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  return ix("set_day_offset", [toI64(params.offsetSecs)], [
+    { pubkey: params.admin, isSigner: true, isWritable: false },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+  ]);
+}
+
+/** Rolls an idle user over to the current day; any wallet can send it. */
+export function ixResetDailyPoints(params: { payer: PublicKey; user: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [userAccount] = findUserAccount(params.user, PROGRAM_ID);
+  return ix("reset_daily_points", [], [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: params.user, isSigner: false, isWritable: false },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixConfigureBoostStacking(params: {
+  admin: PublicKey;
+  /** One mode per boost kind, indexed like `kind` in ixUpsertBoostConfig (8 slots). */
+  modes?: StackingMode[];
+  maxMultiplierBps?: BNish;
+}) {
+  const { admin, modes, maxMultiplierBps } = params;
+  if (modes !== undefined && modes.length !== 8) {
+    throw new Error("modes must have 8 entries");
+  }
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const data = [
+    modes === undefined
+      ? Buffer.from([0])
+      : Buffer.concat([Buffer.from([1]), Buffer.from(modes)]),
+    toOption(maxMultiplierBps, toU32),
+  ];
+  return ix("configure_boost_stacking", data, [
+    { pubkey: admin, isSigner: true, isWritable: false },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+  ]);
+}
+
+/**
+ * Read-only: simulate and decode the return data as
+ * `{ user: Pubkey, slot: u64, userPower: u128, totalPower: u128 }`.
+ */
+/**
+ * Recomputes `owner`'s total effective stake from every position they hold;
+ * users who staked before checkpoints existed start from zero until this runs.
+ * Every position in the owner's `UserPositions` index must be listed, and the
+ * totals never go down.
+ */
+export function ixSyncUserStake(params: {
+  owner: PublicKey;
+  /** Position ids, or `{ id, seedOwner }` for positions received by transfer. */
+  positionIds: (number | { id: number; seedOwner: PublicKey })[];
+}) {
+  const { owner, positionIds } = params;
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  const [userPositions] = findUserPositions(owner, PROGRAM_ID);
+  const positions = positionIds.map((entry) => {
+    const [pda] =
+      typeof entry === "number"
+        ? findPosition(owner, entry)
+        : findPosition(entry.seedOwner, entry.id);
+    return { pubkey: pda, isSigner: false, isWritable: false };
+  });
+  return ix("sync_user_stake", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: userPositions, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...positions,
+  ]);
+}
+
+/**
+ * Lists positions opened before the `UserPositions` index existed; the index
+ * grows to fit, paid by `owner`.
+ */
+export function ixIndexPositions(params: {
+  owner: PublicKey;
+  /** Position ids, or `{ id, seedOwner }` for positions received by transfer. */
+  positionIds: (number | { id: number; seedOwner: PublicKey })[];
+}) {
+  const { owner, positionIds } = params;
+  const [userPositions] = findUserPositions(owner, PROGRAM_ID);
+  const positions = positionIds.map((entry) => {
+    const [pda] =
+      typeof entry === "number"
+        ? findPosition(owner, entry)
+        : findPosition(entry.seedOwner, entry.id);
+    return { pubkey: pda, isSigner: false, isWritable: false };
+  });
+  return ix("index_positions", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: userPositions, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...positions,
+  ]);
+}
+
+export function ixGetVotingPower(params: { user: PublicKey; slot: BNish }) {
+  const { user, slot } = params;
+  const [userAccount] = findUserAccount(user, PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  return ix("get_voting_power", [toU64(slot)], [
+    { pubkey: user, isSigner: false, isWritable: false },
+    { pubkey: userAccount, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: false },
+  ]);
+}
+
+/** Read-only: simulate to get `{ balance, reserved, unreserved, deficit }`. */
+export function ixCheckTreasurySolvency(params: { treasuryXntVault: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  return ix("check_treasury_solvency", [], [
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: false },
+    { pubkey: params.treasuryXntVault, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixConfigureTreasuryPolicy(params: {
+  admin: PublicKey;
+  budgetPerPeriod: BNish;
+  periodSecs: BNish;
+  timelockSecs: BNish;
+}) {
+  const { admin, budgetPerPeriod, periodSecs, timelockSecs } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [treasuryPolicy] = findTreasuryPolicy(PROGRAM_ID);
+  return ix(
+    "configure_treasury_policy",
+    [toU64(budgetPerPeriod), toI64(periodSecs), toI64(timelockSecs)],
+    [
+      { pubkey: admin, isSigner: true, isWritable: true },
+      { pubkey: globalConfig, isSigner: false, isWritable: false },
+      { pubkey: treasuryPolicy, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+/**
+ * Queues a change that loosens the treasury policy; tightening changes can go
+ * through `ixConfigureTreasuryPolicy` directly.
+ */
+export function ixQueueTreasuryPolicy(params: {
+  admin: PublicKey;
+  budgetPerPeriod: BNish;
+  periodSecs: BNish;
+  timelockSecs: BNish;
+}) {
+  const { admin, budgetPerPeriod, periodSecs, timelockSecs } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [treasuryPolicy] = findTreasuryPolicy(PROGRAM_ID);
+  const [policyChange] = findTreasuryPolicyChange(PROGRAM_ID);
+  return ix(
+    "queue_treasury_policy",
+    [toU64(budgetPerPeriod), toI64(periodSecs), toI64(timelockSecs)],
+    [
+      { pubkey: admin, isSigner: true, isWritable: true },
+      { pubkey: globalConfig, isSigner: false, isWritable: false },
+      { pubkey: treasuryPolicy, isSigner: false, isWritable: false },
+      { pubkey: policyChange, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+export function ixApplyTreasuryPolicy(params: { admin: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [treasuryPolicy] = findTreasuryPolicy(PROGRAM_ID);
+  const [policyChange] = findTreasuryPolicyChange(PROGRAM_ID);
+  return ix("apply_treasury_policy", [], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: treasuryPolicy, isSigner: false, isWritable: true },
+    { pubkey: policyChange, isSigner: false, isWritable: true },
+  ]);
+}
+
+export function ixCancelTreasuryPolicy(params: { admin: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [policyChange] = findTreasuryPolicyChange(PROGRAM_ID);
+  return ix("cancel_treasury_policy", [], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: policyChange, isSigner: false, isWritable: true },
+  ]);
+}
+
+/** `withdrawalId` must be the policy's current `nextWithdrawalId`. */
+export function ixQueueTreasuryWithdrawal(params: {
+  admin: PublicKey;
+  withdrawalId: bigint;
+  destination: PublicKey;
+  amount: BNish;
+}) {
+  const { admin, withdrawalId, destination, amount } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [treasuryPolicy] = findTreasuryPolicy(PROGRAM_ID);
+  const [withdrawal] = findTreasuryWithdrawal(withdrawalId, PROGRAM_ID);
+  return ix("queue_treasury_withdrawal", [toU64(amount)], [
+    { pubkey: admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: treasuryPolicy, isSigner: false, isWritable: true },
+    { pubkey: withdrawal, isSigner: false, isWritable: true },
+    { pubkey: destination, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixExecuteTreasuryWithdrawal(params: {
+  admin: PublicKey;
+  withdrawalId: bigint;
+  treasuryXntVault: PublicKey;
+  destination: PublicKey;
+}) {
+  const { admin, withdrawalId, treasuryXntVault, destination } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [treasuryPolicy] = findTreasuryPolicy(PROGRAM_ID);
+  const [withdrawal] = findTreasuryWithdrawal(withdrawalId, PROGRAM_ID);
+  return ix("execute_treasury_withdrawal", [], [
+    { pubkey: admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: false },
+    { pubkey: treasuryPolicy, isSigner: false, isWritable: true },
+    { pubkey: withdrawal, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: destination, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixCancelTreasuryWithdrawal(params: {
+  admin: PublicKey;
+  withdrawalId: bigint;
+}) {
+  const { admin, withdrawalId } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [withdrawal] = findTreasuryWithdrawal(withdrawalId, PROGRAM_ID);
+  return ix("cancel_treasury_withdrawal", [], [
+    { pubkey: admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: withdrawal, isSigner: false, isWritable: true },
+  ]);
+}
+
+export function ixBeginUnbond(params: {
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  positionId: number;
+  seedOwner?: PublicKey;
+}) {
+  const { owner, treasuryXntVault, userXntAccount, positionId } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [stakePosition] = findPosition(params.seedOwner ?? owner, positionId);
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  return ix("begin_unbond", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: userXntAccount, isSigner: false, isWritable: true },
+    { pubkey: stakePosition, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixWithdrawUnbonded(params: {
+  owner: PublicKey;
+  stakingVault: PublicKey;
+  userGameAccount: PublicKey;
+  positionId: number;
+  seedOwner?: PublicKey;
+}) {
+  const { owner, stakingVault, userGameAccount, positionId } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [stakePosition] = findPosition(params.seedOwner ?? owner, positionId);
+  return ix("withdraw_unbonded", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: false },
+    { pubkey: stakingVault, isSigner: false, isWritable: true },
+    { pubkey: userGameAccount, isSigner: false, isWritable: true },
+    { pubkey: stakePosition, isSigner: false, isWritable: true },
+    { pubkey: findUserPositions(owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+/** Merges `sourcePositionId` into `targetPositionId` (both owned by `owner`). */
+export function ixMergePositions(params: {
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  targetPositionId: number;
+  sourcePositionId: number;
+}) {
+  const {
+    owner,
+    treasuryXntVault,
+    userXntAccount,
+    targetPositionId,
+    sourcePositionId,
+  } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [target] = findPosition(owner, targetPositionId);
+  const [source] = findPosition(owner, sourcePositionId);
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  const [userPositions] = findUserPositions(owner, PROGRAM_ID);
+  return ix("merge_positions", [], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: userXntAccount, isSigner: false, isWritable: true },
+    { pubkey: target, isSigner: false, isWritable: true },
+    { pubkey: source, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: userPositions, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+/** `newPositionId` must be the user's current `nextPositionId` (min 1). */
+export function ixSplitPosition(params: {
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  sourcePositionId: number;
+  newPositionId: number;
+  amount: BNish;
+}) {
+  const {
+    owner,
+    treasuryXntVault,
+    userXntAccount,
+    sourcePositionId,
+    newPositionId,
+    amount,
+  } = params;
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [source] = findPosition(owner, sourcePositionId);
+  const [newPosition] = findUserStakePosition(owner, newPositionId, PROGRAM_ID);
+  const [userAccount] = findUserAccount(owner, PROGRAM_ID);
+  const [userPositions] = findUserPositions(owner, PROGRAM_ID);
+  return ix("split_position", [toU64(amount)], [
+    { pubkey: owner, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: true },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: userXntAccount, isSigner: false, isWritable: true },
+    { pubkey: source, isSigner: false, isWritable: true },
+    { pubkey: userAccount, isSigner: false, isWritable: true },
+    { pubkey: newPosition, isSigner: false, isWritable: true },
+    { pubkey: userPositions, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ]);
+}
+
+export function ixInitializeLiquidVault(params: {
+  admin: PublicKey;
+  xntMint: PublicKey;
+}) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [liquidVault] = findLiquidVault(PROGRAM_ID);
+  const [shareMint] = findLiquidShareMint(PROGRAM_ID);
+  const [vaultXntAccount] = findLiquidXntVault(PROGRAM_ID);
+  return ix("initialize_liquid_vault", [], [
+    { pubkey: params.admin, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: liquidVault, isSigner: false, isWritable: true },
+    { pubkey: shareMint, isSigner: false, isWritable: true },
+    { pubkey: params.xntMint, isSigner: false, isWritable: false },
+    { pubkey: vaultXntAccount, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+  ]);
+}
+
+/** Accounts shared by the liquid vault instructions, in context order. */
+function liquidVaultKeys(params: { treasuryXntVault: PublicKey }) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  return [
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: true },
+    { pubkey: params.treasuryXntVault, isSigner: false, isWritable: true },
+    { pubkey: findLiquidVault(PROGRAM_ID)[0], isSigner: false, isWritable: true },
+  ];
+}
+
+/** The share mint and the vault's XNT account, which follow `liquidVaultKeys`. */
+function liquidVaultTokenKeys() {
+  return [
+    { pubkey: findLiquidShareMint(PROGRAM_ID)[0], isSigner: false, isWritable: true },
+    { pubkey: findLiquidXntVault(PROGRAM_ID)[0], isSigner: false, isWritable: true },
+  ];
+}
+
+function liquidVaultUserKeys(params: {
+  owner: PublicKey;
+  stakingVault: PublicKey;
+  treasuryXntVault: PublicKey;
+  userGameAccount: PublicKey;
+  userXntAccount: PublicKey;
+  userShareAccount: PublicKey;
+}) {
+  return [
+    { pubkey: params.owner, isSigner: true, isWritable: true },
+    ...liquidVaultKeys(params),
+    ...liquidVaultTokenKeys(),
+    { pubkey: params.stakingVault, isSigner: false, isWritable: true },
+    { pubkey: params.userGameAccount, isSigner: false, isWritable: true },
+    { pubkey: params.userXntAccount, isSigner: false, isWritable: true },
+    { pubkey: params.userShareAccount, isSigner: false, isWritable: true },
+  ];
+}
+
+/**
+ * Deposits GAME for sGAME. Shares carry the vault's harvested XNT, so the
+ * depositor also adds the matching pro-rata XNT, at most `maxXntIn`;
+ * `minSharesOut` bounds the price moving. The owner's user account is
+ * created if missing, since the stake caps count their positions. Only while
+ * the pool has no warmup period; see `ixBeginLiquidDeposit`.
+ */
+export function ixDepositLiquid(
+  params: Parameters<typeof liquidVaultUserKeys>[0] & {
+    amount: BNish;
+    minSharesOut: BNish;
+    maxXntIn: BNish;
+  }
+) {
+  return ix(
+    "deposit_liquid",
+    [toU64(params.amount), toU64(params.minSharesOut), toU64(params.maxXntIn)],
+    [
+      ...liquidVaultUserKeys(params),
+      { pubkey: findUserAccount(params.owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+/** `ticketId` is any id the owner has no open deposit ticket under. */
+export function ixBeginLiquidDeposit(params: {
+  owner: PublicKey;
+  stakingVault: PublicKey;
+  treasuryXntVault: PublicKey;
+  userGameAccount: PublicKey;
+  userShareAccount: PublicKey;
+  ticketId: number;
+  amount: BNish;
+}) {
+  const [ticket] = findLiquidDeposit(params.owner, params.ticketId, PROGRAM_ID);
+  return ix(
+    "begin_liquid_deposit",
+    [toU32(params.ticketId), toU64(params.amount)],
+    [
+      { pubkey: params.owner, isSigner: true, isWritable: true },
+      ...liquidVaultKeys(params),
+      ...liquidVaultTokenKeys(),
+      { pubkey: params.stakingVault, isSigner: false, isWritable: true },
+      { pubkey: params.userGameAccount, isSigner: false, isWritable: true },
+      { pubkey: params.userShareAccount, isSigner: false, isWritable: false },
+      { pubkey: findUserAccount(params.owner, PROGRAM_ID)[0], isSigner: false, isWritable: true },
+      { pubkey: ticket, isSigner: false, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+/**
+ * Mints the sGAME for a deposit ticket once it has warmed up, taking the
+ * matching pro-rata XNT (at most `maxXntIn`) like `ixDepositLiquid`.
+ */
+export function ixClaimLiquidDeposit(params: {
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  userShareAccount: PublicKey;
+  ticketId: number;
+  minSharesOut: BNish;
+  maxXntIn: BNish;
+}) {
+  const [ticket] = findLiquidDeposit(params.owner, params.ticketId, PROGRAM_ID);
+  return ix(
+    "claim_liquid_deposit",
+    [toU32(params.ticketId), toU64(params.minSharesOut), toU64(params.maxXntIn)],
+    [
+      { pubkey: params.owner, isSigner: true, isWritable: true },
+      ...liquidVaultKeys(params),
+      ...liquidVaultTokenKeys(),
+      { pubkey: params.userXntAccount, isSigner: false, isWritable: true },
+      { pubkey: params.userShareAccount, isSigner: false, isWritable: true },
+      { pubkey: ticket, isSigner: false, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+/**
+ * Pays out the shares' GAME and XNT. Only while the pool has no unbonding
+ * period; see `ixBeginLiquidUnbond`.
+ */
+export function ixRedeemLiquid(
+  params: Parameters<typeof liquidVaultUserKeys>[0] & {
+    shares: BNish;
+    minGameOut: BNish;
+  }
+) {
+  return ix(
+    "redeem_liquid",
+    [toU64(params.shares), toU64(params.minGameOut)],
+    [
+      ...liquidVaultUserKeys(params),
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+/**
+ * Pays the shares' XNT straight away and unbonds their GAME. `ticketId` is
+ * any id the owner has no open unbond ticket under.
+ */
+export function ixBeginLiquidUnbond(params: {
+  owner: PublicKey;
+  treasuryXntVault: PublicKey;
+  userXntAccount: PublicKey;
+  userShareAccount: PublicKey;
+  ticketId: number;
+  shares: BNish;
+  minGameOut: BNish;
+}) {
+  const [ticket] = findLiquidUnbond(params.owner, params.ticketId, PROGRAM_ID);
+  return ix(
+    "begin_liquid_unbond",
+    [toU32(params.ticketId), toU64(params.shares), toU64(params.minGameOut)],
+    [
+      { pubkey: params.owner, isSigner: true, isWritable: true },
+      ...liquidVaultKeys(params),
+      ...liquidVaultTokenKeys(),
+      { pubkey: params.userXntAccount, isSigner: false, isWritable: true },
+      { pubkey: params.userShareAccount, isSigner: false, isWritable: true },
+      { pubkey: ticket, isSigner: false, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ]
+  );
+}
+
+export function ixWithdrawLiquidUnbonded(params: {
+  owner: PublicKey;
+  stakingVault: PublicKey;
+  userGameAccount: PublicKey;
+  ticketId: number;
+}) {
+  const [globalConfig] = findGlobalConfig(PROGRAM_ID);
+  const [stakingPool] = findStakingPool(PROGRAM_ID);
+  const [ticket] = findLiquidUnbond(params.owner, params.ticketId, PROGRAM_ID);
+  return ix("withdraw_liquid_unbonded", [toU32(params.ticketId)], [
+    { pubkey: params.owner, isSigner: true, isWritable: true },
+    { pubkey: globalConfig, isSigner: false, isWritable: false },
+    { pubkey: stakingPool, isSigner: false, isWritable: false },
+    { pubkey: params.stakingVault, isSigner: false, isWritable: true },
+    { pubkey: params.userGameAccount, isSigner: false, isWritable: true },
+    { pubkey: ticket, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+  ]);
+}
+
+/**
+ * Moves the vault's pending rewards into its XNT account for share holders;
+ * any wallet may send it.
+ */
+export function ixHarvestLiquidVault(params: { treasuryXntVault: PublicKey }) {
+  const [vaultXntAccount] = findLiquidXntVault(PROGRAM_ID);
+  return ix("harvest_liquid_vault", [], [
+    ...liquidVaultKeys(params),
+    { pubkey: vaultXntAccount, isSigner: false, isWritable: true },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+  ]);
+}
@@ -0,0 +1,78 @@
+import { PublicKey } from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
+
+/** One ranking allocation; its leaf index is its position in the list. */
+export type RankingAllocation = {
+  user: PublicKey;
+  boostPoints: bigint;
+};
+
+export type RankingTree = {
+  root: Uint8Array;
+  /** Proof for each allocation, in allocation order. */
+  proofs: Uint8Array[][];
+};
+
+/** keccak256(index u32 LE || user || boostPoints u64 LE), like the program. */
+export function rankingLeaf(
+  index: number,
+  user: PublicKey,
+  boostPoints: bigint
+): Uint8Array {
+  const data = Buffer.alloc(4 + 32 + 8);
+  data.writeUInt32LE(index, 0);
+  user.toBuffer().copy(data, 4);
+  data.writeBigUInt64LE(boostPoints, 36);
+  return keccak_256(data);
+}
+
+function hashPair(a: Uint8Array, b: Uint8Array): Uint8Array {
+  const [left, right] =
+    Buffer.compare(Buffer.from(a), Buffer.from(b)) <= 0 ? [a, b] : [b, a];
+  const data = new Uint8Array(64);
+  data.set(left, 0);
+  data.set(right, 32);
+  return keccak_256(data);
+}
+
+/**
+ * Builds the merkle tree for `publish_ranking_epoch`. Inner nodes hash the
+ * sorted pair of children; an odd node out is carried up unchanged.
+ */
+export function buildRankingTree(
+  allocations: RankingAllocation[]
+): RankingTree {
+  if (allocations.length === 0) {
+    throw new Error("a ranking needs at least one allocation");
+  }
+  let level = allocations.map((a, i) => rankingLeaf(i, a.user, a.boostPoints));
+  const proofs: Uint8Array[][] = allocations.map(() => []);
+  const positions = allocations.map((_, i) => i);
+  while (level.length > 1) {
+    positions.forEach((position, leaf) => {
+      const sibling = position ^ 1;
+      if (sibling < level.length) {
+        proofs[leaf].push(level[sibling]);
+      }
+      positions[leaf] = position >> 1;
+    });
+    const next: Uint8Array[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(
+        i + 1 < level.length ? hashPair(level[i], level[i + 1]) : level[i]
+      );
+    }
+    level = next;
+  }
+  return { root: level[0], proofs };
+}
+
+/** Checks `proof` the way `claim_ranking_reward` does. */
+export function verifyRankingProof(
+  proof: Uint8Array[],
+  root: Uint8Array,
+  leaf: Uint8Array
+): boolean {
+  const computed = proof.reduce(hashPair, leaf);
+  return Buffer.from(computed).equals(Buffer.from(root));
+}
@@ -8,6 +8,19 @@ export const PDA_SEEDS = {
   userAccount: utf8("user-account"),
   userStake: utf8("user-stake"),
   boostConfig: utf8("boost-config"),
+  boostReceipt: utf8("boost-receipt"),
+  boostInventory: utf8("boost-inventory"),
+  treasuryPolicy: utf8("treasury-policy"),
+  treasuryWithdrawal: utf8("treasury-withdrawal"),
+  treasuryPolicyChange: utf8("treasury-policy-change"),
+  userPositions: utf8("user-positions"),
+  liquidVault: utf8("liquid-vault"),
+  liquidShareMint: utf8("sgame-mint"),
+  liquidXntVault: utf8("liquid-xnt"),
+  liquidUnbond: utf8("liquid-unbond"),
+  liquidDeposit: utf8("liquid-deposit"),
+  leaderboard: utf8("leaderboard"),
+  rankingEpoch: utf8("ranking-epoch"),
 };
 
 export function findGlobalConfig(programId: PublicKey) {
@@ -49,9 +62,129 @@ export function findUserStakePosition(
   );
 }
 
+export function findUserPositions(user: PublicKey, programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.userPositions, user.toBuffer()],
+    programId
+  );
+}
+
+/** Boost ids are u16, seeded little-endian. */
+function boostIdSeed(id: number) {
+  const buf = Buffer.alloc(2);
+  buf.writeUInt16LE(id);
+  return buf;
+}
+
 export function findBoostConfig(id: number, programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.boostConfig, boostIdSeed(id)],
+    programId
+  );
+}
+
+export function findBoostReceipt(
+  user: PublicKey,
+  boostId: number,
+  programId: PublicKey
+) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.boostReceipt, user.toBuffer(), boostIdSeed(boostId)],
+    programId
+  );
+}
+
+/** PDA of a boost config created while ids were a single byte. */
+export function findLegacyBoostConfig(id: number, programId: PublicKey) {
   return PublicKey.findProgramAddressSync(
     [PDA_SEEDS.boostConfig, Buffer.from([id])],
     programId
   );
 }
+
+export function findBoostInventory(user: PublicKey, programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.boostInventory, user.toBuffer()],
+    programId
+  );
+}
+
+export function findTreasuryPolicy(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.treasuryPolicy],
+    programId
+  );
+}
+
+export function findTreasuryPolicyChange(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.treasuryPolicyChange],
+    programId
+  );
+}
+
+export function findTreasuryWithdrawal(id: bigint, programId: PublicKey) {
+  const buf = Buffer.alloc(8);
+  buf.writeBigUInt64LE(id);
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.treasuryWithdrawal, buf],
+    programId
+  );
+}
+
+export function findLiquidVault(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync([PDA_SEEDS.liquidVault], programId);
+}
+
+export function findLeaderboard(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync([PDA_SEEDS.leaderboard], programId);
+}
+
+export function findRankingEpoch(epochId: number | bigint, programId: PublicKey) {
+  const buf = Buffer.alloc(8);
+  buf.writeBigUInt64LE(BigInt(epochId));
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.rankingEpoch, buf],
+    programId
+  );
+}
+
+export function findLiquidShareMint(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.liquidShareMint],
+    programId
+  );
+}
+
+export function findLiquidXntVault(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.liquidXntVault],
+    programId
+  );
+}
+
+export function findLiquidUnbond(
+  owner: PublicKey,
+  ticketId: number,
+  programId: PublicKey
+) {
+  const buf = Buffer.alloc(4);
+  buf.writeUInt32LE(ticketId);
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.liquidUnbond, owner.toBuffer(), buf],
+    programId
+  );
+}
+
+export function findLiquidDeposit(
+  owner: PublicKey,
+  ticketId: number,
+  programId: PublicKey
+) {
+  const buf = Buffer.alloc(4);
+  buf.writeUInt32LE(ticketId);
+  return PublicKey.findProgramAddressSync(
+    [PDA_SEEDS.liquidDeposit, owner.toBuffer(), buf],
+    programId
+  );
+}
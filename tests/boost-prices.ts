@@ -0,0 +1,49 @@
+import { expect } from "chai";
+import { PROGRAM_ID, ixActivateBoost, ixUpsertBoostConfig } from "../sdk/client";
+import { findUserAccount } from "../sdk/pdas";
+import { Arena, User, expectError, newUser, send, setupArena } from "./fixtures";
+
+describe("boost prices", () => {
+  let arena: Arena;
+  let user: User;
+
+  const upsert = (id: number, prices: { costBoostPoints?: bigint; priceGame?: bigint }) =>
+    send(arena, [
+      ixUpsertBoostConfig({
+        admin: arena.admin.publicKey,
+        id,
+        kind: 0,
+        valueBps: 11_000,
+        durationSeconds: 3_600n,
+        ...prices,
+      }),
+    ]);
+
+  before(async () => {
+    arena = await setupArena();
+    user = await newUser(arena);
+  });
+
+  it("rejects currencies without a price", async () => {
+    await upsert(901, { priceGame: 5n });
+    await expectError(
+      arena,
+      [ixActivateBoost({ user: user.publicKey, boostId: 901, payment: 0 })],
+      [user.keypair],
+      "PaymentNotAccepted"
+    );
+  });
+
+  it("activates a zero-priced boost for free", async () => {
+    await upsert(902, { priceGame: 0n });
+    await send(
+      arena,
+      [ixActivateBoost({ user: user.publicKey, boostId: 902, payment: 1 })],
+      [user.keypair]
+    );
+    const account = await arena.program.account.userAccount.fetch(
+      findUserAccount(user.publicKey, PROGRAM_ID)[0]
+    );
+    expect(account.activeBoosts.map((b: any) => b.boostId)).to.include(902);
+  });
+});
@@ -0,0 +1,73 @@
+import { expect } from "chai";
+import { ixClaimAll } from "../sdk/client";
+import {
+  Arena,
+  User,
+  expectError,
+  mine,
+  newUser,
+  send,
+  setupArena,
+  stake,
+  tokenBalance,
+} from "./fixtures";
+
+describe("claim_all", () => {
+  let arena: Arena;
+  let staker: User;
+
+  const claimAll = (positionIds: number[]) =>
+    ixClaimAll({
+      owner: staker.publicKey,
+      treasuryXntVault: arena.treasuryXntVault,
+      userXntAccount: staker.xnt,
+      positionIds,
+    });
+
+  before(async () => {
+    arena = await setupArena();
+    staker = await newUser(arena);
+    await mine(arena, staker, 3, 2);
+    await stake(arena, staker, 1_000, 1);
+    await stake(arena, staker, 2_000, 2);
+    // Another wallet's mining funds the stakers.
+    await mine(arena, await newUser(arena), 3, 1);
+  });
+
+  it("settles every listed position in one transfer", async () => {
+    const before = await tokenBalance(arena, staker.xnt);
+    await send(arena, [claimAll([1, 2])], [staker.keypair]);
+    const after = await tokenBalance(arena, staker.xnt);
+    expect(after > before).to.equal(true);
+
+    // Reward debts were reset, so an immediate second claim pays nothing.
+    await send(arena, [claimAll([1, 2])], [staker.keypair]);
+    expect(await tokenBalance(arena, staker.xnt)).to.equal(after);
+  });
+
+  it("rejects a position listed twice", async () => {
+    await expectError(arena, [claimAll([1, 1])], [staker.keypair], "DuplicatePosition");
+  });
+
+  it("rejects an empty list", async () => {
+    await expectError(arena, [claimAll([])], [staker.keypair], "NoPositionsProvided");
+  });
+
+  it("rejects positions owned by someone else", async () => {
+    const other = await newUser(arena);
+    await mine(arena, other, 3, 1);
+    await expectError(
+      arena,
+      [
+        ixClaimAll({
+          owner: other.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: other.xnt,
+          positionIds: [{ id: 1, seedOwner: staker.publicKey }],
+        }),
+      ],
+      [other.keypair],
+      "Unauthorized"
+    );
+  });
+});
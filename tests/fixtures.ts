@@ -0,0 +1,224 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { X1MiningArena } from "../target/types/x1_mining_arena";
+import {
+  ixConfigureStakingPool,
+  ixInitializeGlobal,
+  ixInitializeStakingPool,
+  ixInitializeTreasuryVault,
+  ixMineWithRig,
+  ixStake,
+} from "../sdk/client";
+
+/** Shared program state; the global config and staking pool are singletons. */
+export type Arena = {
+  provider: anchor.AnchorProvider;
+  program: Program<X1MiningArena>;
+  admin: Keypair;
+  xntMint: PublicKey;
+  gameMint: PublicKey;
+  treasuryXntVault: PublicKey;
+  stakingVault: PublicKey;
+};
+
+export type User = {
+  keypair: Keypair;
+  publicKey: PublicKey;
+  xnt: PublicKey;
+  game: PublicKey;
+};
+
+let arena: Promise<Arena> | undefined;
+
+/** Initializes the program once per validator and returns its accounts. */
+export function setupArena(): Promise<Arena> {
+  arena ??= initArena();
+  return arena;
+}
+
+async function initArena(): Promise<Arena> {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.x1MiningArena as Program<X1MiningArena>;
+  const admin = (provider.wallet as anchor.Wallet).payer;
+  const xntMint = await createMint(
+    provider.connection,
+    admin,
+    admin.publicKey,
+    null,
+    9
+  );
+  const gameMint = Keypair.generate();
+  const treasuryXntVault = Keypair.generate();
+  const stakingVault = Keypair.generate();
+
+  const base: Arena = {
+    provider,
+    program,
+    admin,
+    xntMint,
+    gameMint: gameMint.publicKey,
+    treasuryXntVault: treasuryXntVault.publicKey,
+    stakingVault: stakingVault.publicKey,
+  };
+  await send(
+    base,
+    [
+      ixInitializeGlobal({
+        admin: admin.publicKey,
+        payer: admin.publicKey,
+        xntMint,
+        gameMint: gameMint.publicKey,
+        halvingInterval: 0,
+        stakingShareBps: 0,
+      }),
+    ],
+    [gameMint]
+  );
+  await send(
+    base,
+    [
+      ixInitializeTreasuryVault({
+        admin: admin.publicKey,
+        xntMint,
+        treasuryXntVault: treasuryXntVault.publicKey,
+      }),
+    ],
+    [treasuryXntVault]
+  );
+  await send(
+    base,
+    [
+      ixInitializeStakingPool({
+        admin: admin.publicKey,
+        gameMint: gameMint.publicKey,
+        xntMint,
+        stakingVault: stakingVault.publicKey,
+      }),
+      // Credit rewards immediately so tests do not depend on wall time.
+      ixConfigureStakingPool({ admin: admin.publicKey, rewardDurationSecs: 0 }),
+    ],
+    [stakingVault]
+  );
+  return base;
+}
+
+export async function send(
+  arena: Arena,
+  ixs: TransactionInstruction[],
+  signers: Keypair[] = []
+) {
+  return arena.provider.sendAndConfirm(new Transaction().add(...ixs), signers);
+}
+
+/** Sends `ixs` and asserts the transaction fails with `code`. */
+export async function expectError(
+  arena: Arena,
+  ixs: TransactionInstruction[],
+  signers: Keypair[],
+  code: string
+) {
+  try {
+    await send(arena, ixs, signers);
+  } catch (err: any) {
+    const text = `${err}\n${(err.logs ?? []).join("\n")}`;
+    expect(text).to.include(code);
+    return;
+  }
+  expect.fail(`expected ${code}`);
+}
+
+/** Funds a fresh wallet with SOL and `xnt` XNT base units. */
+export async function newUser(arena: Arena, xnt = 100n * 10n ** 9n): Promise<User> {
+  const keypair = Keypair.generate();
+  const { connection } = arena.provider;
+  const sig = await connection.requestAirdrop(keypair.publicKey, 10 * LAMPORTS_PER_SOL);
+  await connection.confirmTransaction(sig, "confirmed");
+  const xntAta = await getOrCreateAssociatedTokenAccount(
+    connection,
+    arena.admin,
+    arena.xntMint,
+    keypair.publicKey
+  );
+  const gameAta = await getOrCreateAssociatedTokenAccount(
+    connection,
+    arena.admin,
+    arena.gameMint,
+    keypair.publicKey
+  );
+  if (xnt > 0n) {
+    await mintTo(connection, arena.admin, arena.xntMint, xntAta.address, arena.admin, xnt);
+  }
+  return {
+    keypair,
+    publicKey: keypair.publicKey,
+    xnt: xntAta.address,
+    game: gameAta.address,
+  };
+}
+
+export async function mine(arena: Arena, user: User, rigId = 3, runs = 1) {
+  for (let i = 0; i < runs; i++) {
+    await send(
+      arena,
+      [
+        ixMineWithRig({
+          payer: user.publicKey,
+          gameMint: arena.gameMint,
+          userGameAccount: user.game,
+          userXntAccount: user.xnt,
+          treasuryXntVault: arena.treasuryXntVault,
+          rigId,
+        }),
+      ],
+      [user.keypair]
+    );
+  }
+}
+
+export function stakeIx(
+  arena: Arena,
+  user: User,
+  amount: bigint | number,
+  positionId: number,
+  lockDays = 7
+) {
+  return ixStake({
+    owner: user.publicKey,
+    stakingVault: arena.stakingVault,
+    treasuryXntVault: arena.treasuryXntVault,
+    userGameAccount: user.game,
+    userXntAccount: user.xnt,
+    amount,
+    lockDays,
+    positionId,
+  });
+}
+
+export async function stake(
+  arena: Arena,
+  user: User,
+  amount: bigint | number,
+  positionId: number,
+  lockDays = 7
+) {
+  await send(arena, [stakeIx(arena, user, amount, positionId, lockDays)], [user.keypair]);
+}
+
+export async function tokenBalance(arena: Arena, account: PublicKey) {
+  const balance = await arena.provider.connection.getTokenAccountBalance(account);
+  return BigInt(balance.value.amount);
+}
@@ -0,0 +1,323 @@
+import { expect } from "chai";
+import { getOrCreateAssociatedTokenAccount } from "@solana/spl-token";
+import { PublicKey } from "@solana/web3.js";
+import {
+  PROGRAM_ID,
+  ixBeginLiquidDeposit,
+  ixBeginLiquidUnbond,
+  ixClaimLiquidDeposit,
+  ixConfigureStakingPool,
+  ixDepositLiquid,
+  ixHarvestLiquidVault,
+  ixInitializeLiquidVault,
+  ixRedeemLiquid,
+  ixWithdrawLiquidUnbonded,
+} from "../sdk/client";
+import {
+  findLiquidDeposit,
+  findLiquidShareMint,
+  findLiquidUnbond,
+  findLiquidVault,
+  findLiquidXntVault,
+  findUserAccount,
+} from "../sdk/pdas";
+import {
+  Arena,
+  User,
+  expectError,
+  mine,
+  newUser,
+  send,
+  setupArena,
+  stake,
+  tokenBalance,
+} from "./fixtures";
+
+const MINIMUM_LIQUID_SHARES = 1_000n;
+
+describe("liquid vault", () => {
+  let arena: Arena;
+  let holder: User;
+  let holderShares: PublicKey;
+
+  const vault = () => arena.program.account.liquidVault.fetch(findLiquidVault(PROGRAM_ID)[0]);
+  const vaultKeys = (user: User, shares: PublicKey) => ({
+    owner: user.publicKey,
+    stakingVault: arena.stakingVault,
+    treasuryXntVault: arena.treasuryXntVault,
+    userGameAccount: user.game,
+    userXntAccount: user.xnt,
+    userShareAccount: shares,
+  });
+
+  before(async () => {
+    arena = await setupArena();
+    await send(arena, [
+      ixInitializeLiquidVault({ admin: arena.admin.publicKey, xntMint: arena.xntMint }),
+    ]);
+    holder = await newUser(arena);
+    await mine(arena, holder, 3, 2);
+    holderShares = (
+      await getOrCreateAssociatedTokenAccount(
+        arena.provider.connection,
+        arena.admin,
+        findLiquidShareMint(PROGRAM_ID)[0],
+        holder.publicKey
+      )
+    ).address;
+  });
+
+  after(async () => {
+    await send(arena, [
+      ixConfigureStakingPool({
+        admin: arena.admin.publicKey,
+        unbondingPeriodSecs: 0,
+        warmupPeriodSecs: 0,
+        maxUserEffectiveStake: 0,
+      }),
+    ]);
+  });
+
+  it("locks the minimum shares on the first deposit and takes no XNT", async () => {
+    const xntBefore = await tokenBalance(arena, holder.xnt);
+    await send(
+      arena,
+      [
+        ixDepositLiquid({
+          ...vaultKeys(holder, holderShares),
+          amount: 10_000,
+          minSharesOut: 0,
+          maxXntIn: 0,
+        }),
+      ],
+      [holder.keypair]
+    );
+    expect(await tokenBalance(arena, holderShares)).to.equal(10_000n - MINIMUM_LIQUID_SHARES);
+    expect((await vault()).lockedShares.toString()).to.equal(MINIMUM_LIQUID_SHARES.toString());
+    expect(await tokenBalance(arena, holder.xnt)).to.equal(xntBefore);
+  });
+
+  it("rejects deposits below the requested shares", async () => {
+    await expectError(
+      arena,
+      [
+        ixDepositLiquid({
+          ...vaultKeys(holder, holderShares),
+          amount: 1_000,
+          minSharesOut: 1_001,
+          maxXntIn: 0,
+        }),
+      ],
+      [holder.keypair],
+      "SlippageExceeded"
+    );
+  });
+
+  it("holds harvested XNT for share holders without minting GAME", async () => {
+    // Another wallet's mining funds the stakers, the vault included.
+    await mine(arena, await newUser(arena), 3, 2);
+    const before = await vault();
+    const supplyBefore = (await arena.provider.connection.getTokenSupply(arena.gameMint)).value
+      .amount;
+    await send(arena, [ixHarvestLiquidVault({ treasuryXntVault: arena.treasuryXntVault })]);
+    const after = await vault();
+    expect(after.totalGame.toString()).to.equal(before.totalGame.toString());
+    expect(after.totalXnt.gt(before.totalXnt)).to.equal(true);
+    expect(after.totalXntHarvested.gt(before.totalXntHarvested)).to.equal(true);
+    expect(await tokenBalance(arena, findLiquidXntVault(PROGRAM_ID)[0])).to.equal(
+      BigInt(after.totalXnt.toString())
+    );
+    expect((await arena.provider.connection.getTokenSupply(arena.gameMint)).value.amount).to.equal(
+      supplyBefore
+    );
+  });
+
+  it("takes the pro-rata XNT from later depositors", async () => {
+    await expectError(
+      arena,
+      [
+        ixDepositLiquid({
+          ...vaultKeys(holder, holderShares),
+          amount: 5_000,
+          minSharesOut: 0,
+          maxXntIn: 0,
+        }),
+      ],
+      [holder.keypair],
+      "SlippageExceeded"
+    );
+    const xntBefore = await tokenBalance(arena, holder.xnt);
+    const totalXntBefore = BigInt((await vault()).totalXnt.toString());
+    await send(
+      arena,
+      [
+        ixDepositLiquid({
+          ...vaultKeys(holder, holderShares),
+          amount: 5_000,
+          minSharesOut: 0,
+          maxXntIn: xntBefore,
+        }),
+      ],
+      [holder.keypair]
+    );
+    const paid = xntBefore - (await tokenBalance(arena, holder.xnt));
+    expect(paid > 0n).to.equal(true);
+    expect(BigInt((await vault()).totalXnt.toString())).to.equal(totalXntBefore + paid);
+  });
+
+  it("pays the shares' XNT on redemption", async () => {
+    const xntBefore = await tokenBalance(arena, holder.xnt);
+    await send(
+      arena,
+      [ixRedeemLiquid({ ...vaultKeys(holder, holderShares), shares: 1_000, minGameOut: 0 })],
+      [holder.keypair]
+    );
+    expect((await tokenBalance(arena, holder.xnt)) > xntBefore).to.equal(true);
+  });
+
+  it("counts the depositor's positions against the user cap", async () => {
+    const whale = await newUser(arena);
+    await mine(arena, whale, 3, 2);
+    await stake(arena, whale, 1_000, 1);
+    const account = await arena.program.account.userAccount.fetch(
+      findUserAccount(whale.publicKey, PROGRAM_ID)[0]
+    );
+    const target = BigInt(account.totalEffectiveStake.add(account.warmingStake).toString());
+    const whaleShares = (
+      await getOrCreateAssociatedTokenAccount(
+        arena.provider.connection,
+        arena.admin,
+        findLiquidShareMint(PROGRAM_ID)[0],
+        whale.publicKey
+      )
+    ).address;
+
+    // The deposit fits the cap on its own, but not on top of the position.
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, maxUserEffectiveStake: target }),
+    ]);
+    await expectError(
+      arena,
+      [
+        ixDepositLiquid({
+          ...vaultKeys(whale, whaleShares),
+          amount: 1_001,
+          minSharesOut: 0,
+          maxXntIn: await tokenBalance(arena, whale.xnt),
+        }),
+      ],
+      [whale.keypair],
+      "UserStakeCapExceeded"
+    );
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, maxUserEffectiveStake: 0 }),
+    ]);
+  });
+
+  it("warms deposits up in their own ticket when the pool has a warmup period", async () => {
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, warmupPeriodSecs: 3_600 }),
+    ]);
+    await expectError(
+      arena,
+      [
+        ixDepositLiquid({
+          ...vaultKeys(holder, holderShares),
+          amount: 5_000,
+          minSharesOut: 0,
+          maxXntIn: 0,
+        }),
+      ],
+      [holder.keypair],
+      "WarmupRequired"
+    );
+
+    const before = await vault();
+    const sharesBefore = await tokenBalance(arena, holderShares);
+    await send(
+      arena,
+      [ixBeginLiquidDeposit({ ...vaultKeys(holder, holderShares), ticketId: 1, amount: 5_000 })],
+      [holder.keypair]
+    );
+    const after = await vault();
+    // The deposit mints nothing and has no claim on the vault's GAME yet.
+    expect(await tokenBalance(arena, holderShares)).to.equal(sharesBefore);
+    expect(after.pendingGame.toNumber()).to.equal(5_000);
+    expect(after.totalGame.gte(before.totalGame)).to.equal(true);
+    const ticket = await arena.program.account.liquidDeposit.fetch(
+      findLiquidDeposit(holder.publicKey, 1, PROGRAM_ID)[0]
+    );
+    expect(ticket.gameAmount.toNumber()).to.equal(5_000);
+
+    await expectError(
+      arena,
+      [
+        ixClaimLiquidDeposit({
+          owner: holder.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: holder.xnt,
+          userShareAccount: holderShares,
+          ticketId: 1,
+          minSharesOut: 0,
+          maxXntIn: 0,
+        }),
+      ],
+      [holder.keypair],
+      "WarmupInProgress"
+    );
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, warmupPeriodSecs: 0 }),
+    ]);
+  });
+
+  it("routes redemptions through unbonding when the pool has a period", async () => {
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, unbondingPeriodSecs: 3_600 }),
+    ]);
+    await expectError(
+      arena,
+      [ixRedeemLiquid({ ...vaultKeys(holder, holderShares), shares: 100, minGameOut: 0 })],
+      [holder.keypair],
+      "UnbondingRequired"
+    );
+
+    const sharesBefore = await tokenBalance(arena, holderShares);
+    const xntBefore = await tokenBalance(arena, holder.xnt);
+    await send(
+      arena,
+      [
+        ixBeginLiquidUnbond({
+          owner: holder.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: holder.xnt,
+          userShareAccount: holderShares,
+          ticketId: 1,
+          shares: 100,
+          minGameOut: 0,
+        }),
+      ],
+      [holder.keypair]
+    );
+    expect(await tokenBalance(arena, holderShares)).to.equal(sharesBefore - 100n);
+    // The XNT is paid straight away; only the GAME unbonds.
+    expect((await tokenBalance(arena, holder.xnt)) > xntBefore).to.equal(true);
+    const ticket = await arena.program.account.liquidUnbond.fetch(
+      findLiquidUnbond(holder.publicKey, 1, PROGRAM_ID)[0]
+    );
+    expect(ticket.gameAmount.toNumber()).to.be.greaterThan(0);
+
+    await expectError(
+      arena,
+      [
+        ixWithdrawLiquidUnbonded({
+          owner: holder.publicKey,
+          stakingVault: arena.stakingVault,
+          userGameAccount: holder.game,
+          ticketId: 1,
+        }),
+      ],
+      [holder.keypair],
+      "UnbondingInProgress"
+    );
+  });
+});
@@ -0,0 +1,46 @@
+import { expect } from "chai";
+import { PROGRAM_ID, ixMergePositions } from "../sdk/client";
+import { findUserStakePosition } from "../sdk/pdas";
+import { Arena, User, mine, newUser, send, setupArena, stake } from "./fixtures";
+
+describe("merge_positions", () => {
+  let arena: Arena;
+  let staker: User;
+
+  const position = (id: number) =>
+    arena.program.account.userStakePosition.fetch(
+      findUserStakePosition(staker.publicKey, id, PROGRAM_ID)[0]
+    );
+
+  before(async () => {
+    arena = await setupArena();
+    staker = await newUser(arena);
+    await mine(arena, staker, 3, 2);
+  });
+
+  it("weights the lock multiplier by amount", async () => {
+    await stake(arena, staker, 900, 1, 7);
+    await stake(arena, staker, 100, 2, 30);
+    const longLock = (await position(2)).lockUntilTs.toNumber();
+
+    await send(
+      arena,
+      [
+        ixMergePositions({
+          owner: staker.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: staker.xnt,
+          targetPositionId: 1,
+          sourcePositionId: 2,
+        }),
+      ],
+      [staker.keypair]
+    );
+
+    const merged = await position(1);
+    expect(merged.amountStaked.toNumber()).to.equal(1_000);
+    expect(merged.lockUntilTs.toNumber()).to.equal(longLock);
+    // (900 * 10_500 + 100 * 12_000) / 1_000
+    expect(merged.lockMultiplierBps).to.equal(10_650);
+  });
+});
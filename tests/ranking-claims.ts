@@ -0,0 +1,135 @@
+import { expect } from "chai";
+import {
+  PROGRAM_ID,
+  ixClaimRankingReward,
+  ixCloseRankingEpoch,
+  ixPublishRankingEpoch,
+} from "../sdk/client";
+import {
+  buildRankingTree,
+  rankingLeaf,
+  verifyRankingProof,
+} from "../sdk/merkle";
+import { findUserAccount } from "../sdk/pdas";
+import {
+  Arena,
+  User,
+  expectError,
+  newUser,
+  send,
+  setupArena,
+} from "./fixtures";
+
+describe("ranking claims", () => {
+  let arena: Arena;
+  let users: User[];
+  // Epoch ids must increase across the whole validator run.
+  const epochId = BigInt(Date.now());
+  const points = [300n, 200n, 100n];
+
+  before(async () => {
+    arena = await setupArena();
+    users = [await newUser(arena), await newUser(arena), await newUser(arena)];
+  });
+
+  const tree = () =>
+    buildRankingTree(
+      users.map((u, i) => ({ user: u.publicKey, boostPoints: points[i] }))
+    );
+
+  const claim = (
+    user: User,
+    index: number,
+    boostPoints: bigint,
+    proof: Uint8Array[]
+  ) =>
+    ixClaimRankingReward({
+      user: user.publicKey,
+      epochId,
+      index,
+      boostPoints,
+      proof,
+    });
+
+  it("builds proofs that verify locally", () => {
+    const { root, proofs } = tree();
+    users.forEach((u, i) => {
+      const leaf = rankingLeaf(i, u.publicKey, points[i]);
+      expect(verifyRankingProof(proofs[i], root, leaf)).to.be.true;
+    });
+    const wrongIndex = rankingLeaf(1, users[0].publicKey, points[0]);
+    expect(verifyRankingProof(proofs[0], root, wrongIndex)).to.be.false;
+  });
+
+  it("credits a valid claim once", async () => {
+    const { root, proofs } = tree();
+    await send(arena, [
+      ixPublishRankingEpoch({
+        admin: arena.admin.publicKey,
+        epochId,
+        merkleRoot: root,
+        leafCount: users.length,
+        totalPoints: 600n,
+      }),
+    ]);
+    await send(
+      arena,
+      [claim(users[0], 0, points[0], proofs[0])],
+      [users[0].keypair]
+    );
+    const account = await arena.program.account.userAccount.fetch(
+      findUserAccount(users[0].publicKey, PROGRAM_ID)[0]
+    );
+    expect(account.boostPoints.toString()).to.equal("300");
+
+    await expectError(
+      arena,
+      [claim(users[0], 0, points[0], proofs[0])],
+      [users[0].keypair],
+      "AlreadyClaimed"
+    );
+  });
+
+  it("rejects a wrong amount or index", async () => {
+    const { proofs } = tree();
+    await expectError(
+      arena,
+      [claim(users[1], 1, 250n, proofs[1])],
+      [users[1].keypair],
+      "InvalidMerkleProof"
+    );
+    await expectError(
+      arena,
+      [claim(users[1], 2, points[1], proofs[1])],
+      [users[1].keypair],
+      "InvalidMerkleProof"
+    );
+  });
+
+  it("closes the epoch for good", async () => {
+    const { root, proofs } = tree();
+    await send(arena, [
+      ixCloseRankingEpoch({ admin: arena.admin.publicKey, epochId }),
+    ]);
+    await expectError(
+      arena,
+      [claim(users[2], 2, points[2], proofs[2])],
+      [users[2].keypair],
+      "AccountNotInitialized"
+    );
+    await expectError(
+      arena,
+      [
+        ixPublishRankingEpoch({
+          admin: arena.admin.publicKey,
+          epochId,
+          merkleRoot: root,
+          leafCount: users.length,
+          totalPoints: 600n,
+        }),
+      ],
+      [],
+      "RankingEpochTooOld"
+    );
+  });
+});
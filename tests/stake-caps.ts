@@ -0,0 +1,62 @@
+import { expect } from "chai";
+import { PROGRAM_ID, ixConfigureStakingPool } from "../sdk/client";
+import { findUserStakePosition } from "../sdk/pdas";
+import {
+  Arena,
+  User,
+  expectError,
+  mine,
+  newUser,
+  send,
+  setupArena,
+  stake,
+  stakeIx,
+} from "./fixtures";
+
+describe("stake caps", () => {
+  let arena: Arena;
+  let staker: User;
+
+  before(async () => {
+    arena = await setupArena();
+    staker = await newUser(arena);
+    await mine(arena, staker, 3, 2);
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, warmupPeriodSecs: 3_600 }),
+    ]);
+  });
+
+  after(async () => {
+    await send(arena, [
+      ixConfigureStakingPool({
+        admin: arena.admin.publicKey,
+        warmupPeriodSecs: 0,
+        maxUserEffectiveStake: 0,
+      }),
+    ]);
+  });
+
+  it("count stake that is still warming up", async () => {
+    await stake(arena, staker, 1_000, 1);
+    const position = await arena.program.account.userStakePosition.fetch(
+      findUserStakePosition(staker.publicKey, 1, PROGRAM_ID)[0]
+    );
+    const target = position.effectiveStake.add(position.warmingStake);
+    expect(position.warmingStake.gtn(0)).to.equal(true);
+
+    // The first position has barely warmed up, but its target already
+    // fills most of the cap.
+    await send(arena, [
+      ixConfigureStakingPool({
+        admin: arena.admin.publicKey,
+        maxUserEffectiveStake: target.addn(1),
+      }),
+    ]);
+    await expectError(
+      arena,
+      [stakeIx(arena, staker, 1_000, 2, 7)],
+      [staker.keypair],
+      "UserStakeCapExceeded"
+    );
+  });
+});
@@ -0,0 +1,102 @@
+import { expect } from "chai";
+import {
+  PROGRAM_ID,
+  ixClaim,
+  ixRefreshPosition,
+  ixTransferPosition,
+} from "../sdk/client";
+import { findUserStakePosition } from "../sdk/pdas";
+import {
+  Arena,
+  User,
+  expectError,
+  mine,
+  newUser,
+  send,
+  setupArena,
+  stake,
+  tokenBalance,
+} from "./fixtures";
+
+describe("transfer_position", () => {
+  let arena: Arena;
+  let sender: User;
+  let receiver: User;
+
+  before(async () => {
+    arena = await setupArena();
+    sender = await newUser(arena);
+    receiver = await newUser(arena);
+    await mine(arena, sender, 3, 1);
+    await stake(arena, sender, 1_000, 1);
+    await send(
+      arena,
+      [
+        ixTransferPosition({
+          owner: sender.publicKey,
+          newOwner: receiver.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: sender.xnt,
+          positionId: 1,
+        }),
+      ],
+      [sender.keypair]
+    );
+    await mine(arena, await newUser(arena), 3, 1);
+  });
+
+  it("lets the new owner claim through the original seeds", async () => {
+    const before = await tokenBalance(arena, receiver.xnt);
+    await send(
+      arena,
+      [
+        ixClaim({
+          owner: receiver.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: receiver.xnt,
+          positionId: 1,
+          seedOwner: sender.publicKey,
+        }),
+      ],
+      [receiver.keypair]
+    );
+    expect((await tokenBalance(arena, receiver.xnt)) > before).to.equal(true);
+  });
+
+  it("stops the previous owner from claiming", async () => {
+    await expectError(
+      arena,
+      [
+        ixClaim({
+          owner: sender.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          userXntAccount: sender.xnt,
+          positionId: 1,
+        }),
+      ],
+      [sender.keypair],
+      "Unauthorized"
+    );
+  });
+
+  it("refreshes the position under its new owner", async () => {
+    await send(
+      arena,
+      [
+        ixRefreshPosition({
+          authority: receiver.publicKey,
+          owner: receiver.publicKey,
+          treasuryXntVault: arena.treasuryXntVault,
+          ownerXntAccount: receiver.xnt,
+          positionId: 1,
+          seedOwner: sender.publicKey,
+        }),
+      ],
+      [receiver.keypair]
+    );
+    const position = await arena.program.account.userStakePosition.fetch(
+      findUserStakePosition(sender.publicKey, 1, PROGRAM_ID)[0]
+    );
+    expect(position.owner.toBase58()).to.equal(receiver.publicKey.toBase58());
+  });
+});
@@ -0,0 +1,86 @@
+import { expect } from "chai";
+import {
+  PROGRAM_ID,
+  ixApplyTreasuryPolicy,
+  ixCancelTreasuryPolicy,
+  ixConfigureTreasuryPolicy,
+  ixQueueTreasuryPolicy,
+} from "../sdk/client";
+import { findTreasuryPolicy, findTreasuryPolicyChange } from "../sdk/pdas";
+import { Arena, expectError, send, setupArena } from "./fixtures";
+
+const DAY = 86_400;
+
+describe("treasury policy", () => {
+  let arena: Arena;
+
+  const policy = (budgetPerPeriod: number, periodSecs: number, timelockSecs: number) => ({
+    admin: arena.admin.publicKey,
+    budgetPerPeriod,
+    periodSecs,
+    timelockSecs,
+  });
+  const fetchPolicy = () =>
+    arena.program.account.treasuryPolicy.fetch(findTreasuryPolicy(PROGRAM_ID)[0]);
+
+  before(async () => {
+    arena = await setupArena();
+  });
+
+  it("rejects a timelock below the minimum", async () => {
+    await expectError(
+      arena,
+      [ixConfigureTreasuryPolicy(policy(1_000, DAY, 60))],
+      [],
+      "TimelockTooShort"
+    );
+  });
+
+  it("configures a fresh policy directly", async () => {
+    await send(arena, [ixConfigureTreasuryPolicy(policy(1_000, DAY, 2 * DAY))]);
+    expect((await fetchPolicy()).budgetPerPeriod.toNumber()).to.equal(1_000);
+  });
+
+  it("applies tightening changes immediately", async () => {
+    await send(arena, [ixConfigureTreasuryPolicy(policy(500, 2 * DAY, 3 * DAY))]);
+    const current = await fetchPolicy();
+    expect(current.budgetPerPeriod.toNumber()).to.equal(500);
+    expect(current.timelockSecs.toNumber()).to.equal(3 * DAY);
+  });
+
+  it("refuses to loosen the policy without queueing", async () => {
+    for (const loosened of [
+      policy(501, 2 * DAY, 3 * DAY),
+      policy(500, DAY, 3 * DAY),
+      policy(500, 2 * DAY, 2 * DAY),
+    ]) {
+      await expectError(
+        arena,
+        [ixConfigureTreasuryPolicy(loosened)],
+        [],
+        "PolicyChangeNeedsQueue"
+      );
+    }
+  });
+
+  it("holds a queued loosening behind the current timelock", async () => {
+    await send(arena, [ixQueueTreasuryPolicy(policy(5_000, DAY, DAY))]);
+    const change = await arena.program.account.treasuryPolicyChange.fetch(
+      findTreasuryPolicyChange(PROGRAM_ID)[0]
+    );
+    expect(change.executableAtTs.sub(change.queuedAtTs).toNumber()).to.equal(3 * DAY);
+
+    await expectError(
+      arena,
+      [ixApplyTreasuryPolicy({ admin: arena.admin.publicKey })],
+      [],
+      "TimelockActive"
+    );
+    expect((await fetchPolicy()).budgetPerPeriod.toNumber()).to.equal(500);
+
+    await send(arena, [ixCancelTreasuryPolicy({ admin: arena.admin.publicKey })]);
+    expect(
+      await arena.provider.connection.getAccountInfo(findTreasuryPolicyChange(PROGRAM_ID)[0])
+    ).to.equal(null);
+  });
+});
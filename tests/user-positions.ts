@@ -0,0 +1,79 @@
+import { expect } from "chai";
+import { PROGRAM_ID, ixIndexPositions } from "../sdk/client";
+import { findUserPositions } from "../sdk/pdas";
+import {
+  Arena,
+  User,
+  expectError,
+  mine,
+  newUser,
+  send,
+  setupArena,
+  stake,
+} from "./fixtures";
+
+describe("user positions index", () => {
+  let arena: Arena;
+  let staker: User;
+
+  const index = (user: User) =>
+    arena.program.account.userPositions.fetch(findUserPositions(user.publicKey, PROGRAM_ID)[0]);
+
+  before(async () => {
+    arena = await setupArena();
+    staker = await newUser(arena);
+    await mine(arena, staker, 3, 2);
+  });
+
+  it("grows past the original 32-entry size", async () => {
+    for (let id = 1; id <= 34; id++) {
+      await stake(arena, staker, 10, id);
+    }
+    const positions = (await index(staker)).positions;
+    expect(positions.map((p) => p.positionId)).to.have.members(
+      Array.from({ length: 34 }, (_, i) => i + 1)
+    );
+    const info = await arena.provider.connection.getAccountInfo(
+      findUserPositions(staker.publicKey, PROGRAM_ID)[0]
+    );
+    expect(info!.data.length).to.equal(8 + 32 + 4 + 34 * 52);
+  });
+
+  it("refreshes listed positions without duplicating them", async () => {
+    await send(arena, [ixIndexPositions({ owner: staker.publicKey, positionIds: [1, 2] })], [
+      staker.keypair,
+    ]);
+    expect((await index(staker)).positions).to.have.length(34);
+  });
+
+  it("lists positions for a wallet without an index", async () => {
+    const other = await newUser(arena);
+    await mine(arena, other, 3, 1);
+    await stake(arena, other, 10, 1);
+    await send(arena, [ixIndexPositions({ owner: other.publicKey, positionIds: [1] })], [
+      other.keypair,
+    ]);
+    expect((await index(other)).positions.map((p) => p.positionId)).to.deep.equal([1]);
+  });
+
+  it("rejects duplicates and other wallets' positions", async () => {
+    await expectError(
+      arena,
+      [ixIndexPositions({ owner: staker.publicKey, positionIds: [1, 1] })],
+      [staker.keypair],
+      "DuplicatePosition"
+    );
+    const other = await newUser(arena);
+    await expectError(
+      arena,
+      [
+        ixIndexPositions({
+          owner: other.publicKey,
+          positionIds: [{ id: 1, seedOwner: staker.publicKey }],
+        }),
+      ],
+      [other.keypair],
+      "Unauthorized"
+    );
+  });
+});
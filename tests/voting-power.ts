@@ -0,0 +1,78 @@
+import { expect } from "chai";
+import { PROGRAM_ID, ixSyncUserStake } from "../sdk/client";
+import { findUserAccount, findUserStakePosition } from "../sdk/pdas";
+import {
+  Arena,
+  User,
+  expectError,
+  mine,
+  newUser,
+  send,
+  setupArena,
+  stake,
+} from "./fixtures";
+
+describe("sync_user_stake", () => {
+  let arena: Arena;
+  let staker: User;
+
+  const userAccount = (user: User) =>
+    arena.program.account.userAccount.fetch(findUserAccount(user.publicKey, PROGRAM_ID)[0]);
+  const position = (user: User, id: number) =>
+    arena.program.account.userStakePosition.fetch(
+      findUserStakePosition(user.publicKey, id, PROGRAM_ID)[0]
+    );
+
+  before(async () => {
+    arena = await setupArena();
+    staker = await newUser(arena);
+    await mine(arena, staker, 3, 2);
+    await stake(arena, staker, 1_000, 1);
+    await stake(arena, staker, 2_000, 2);
+  });
+
+  it("sets the total to the sum of the listed positions", async () => {
+    await send(arena, [ixSyncUserStake({ owner: staker.publicKey, positionIds: [1, 2] })], [
+      staker.keypair,
+    ]);
+    const expected =
+      BigInt((await position(staker, 1)).effectiveStake.toString()) +
+      BigInt((await position(staker, 2)).effectiveStake.toString());
+    expect(BigInt((await userAccount(staker)).totalEffectiveStake.toString())).to.equal(
+      expected
+    );
+  });
+
+  it("rejects a sync that leaves an indexed position out", async () => {
+    const before = (await userAccount(staker)).totalEffectiveStake.toString();
+    await expectError(
+      arena,
+      [ixSyncUserStake({ owner: staker.publicKey, positionIds: [2] })],
+      [staker.keypair],
+      "MissingPosition"
+    );
+    expect((await userAccount(staker)).totalEffectiveStake.toString()).to.equal(before);
+  });
+
+  it("rejects duplicates and other wallets' positions", async () => {
+    await expectError(
+      arena,
+      [ixSyncUserStake({ owner: staker.publicKey, positionIds: [1, 1] })],
+      [staker.keypair],
+      "DuplicatePosition"
+    );
+    const other = await newUser(arena);
+    await mine(arena, other, 3, 1);
+    await expectError(
+      arena,
+      [
+        ixSyncUserStake({
+          owner: other.publicKey,
+          positionIds: [{ id: 1, seedOwner: staker.publicKey }],
+        }),
+      ],
+      [other.keypair],
+      "Unauthorized"
+    );
+  });
+});
@@ -0,0 +1,75 @@
+import { expect } from "chai";
+import { PROGRAM_ID, ixAdvanceWarmup, ixConfigureStakingPool } from "../sdk/client";
+import { findStakingPool, findUserStakePosition } from "../sdk/pdas";
+import { Arena, User, expectError, mine, newUser, send, setupArena, stake } from "./fixtures";
+
+describe("advance_warmup", () => {
+  let arena: Arena;
+  let staker: User;
+  let keeper: User;
+
+  const position = (id: number) =>
+    arena.program.account.userStakePosition.fetch(
+      findUserStakePosition(staker.publicKey, id, PROGRAM_ID)[0]
+    );
+  const pool = () => arena.program.account.stakingPool.fetch(findStakingPool(PROGRAM_ID)[0]);
+  const advance = (id: number) =>
+    ixAdvanceWarmup({
+      authority: keeper.publicKey,
+      owner: staker.publicKey,
+      treasuryXntVault: arena.treasuryXntVault,
+      ownerXntAccount: staker.xnt,
+      positionId: id,
+    });
+  const big = (value: { toString(): string }) => BigInt(value.toString());
+
+  before(async () => {
+    arena = await setupArena();
+    staker = await newUser(arena);
+    keeper = await newUser(arena, 0n);
+    await mine(arena, staker, 3, 2);
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, warmupPeriodSecs: 30 }),
+    ]);
+    await stake(arena, staker, 1_000, 1);
+  });
+
+  after(async () => {
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, warmupPeriodSecs: 0 }),
+    ]);
+  });
+
+  it("lets any wallet move warming stake and the pool total", async () => {
+    const staked = await position(1);
+    const start = staked.warmupStartTs.toNumber();
+    const end = staked.warmupEndTs.toNumber();
+    expect(end - start).to.equal(30);
+    const target = big(staked.effectiveStake) + big(staked.warmingStake);
+    const startTotal = big((await pool()).totalEffectiveStake);
+
+    const sig = await send(arena, [advance(1)], [keeper.keypair]);
+    const tx = await arena.provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const now = tx!.blockTime!;
+    const expected =
+      now >= end ? target : (target * BigInt(Math.max(now - start, 0))) / BigInt(end - start);
+
+    const advanced = await position(1);
+    expect(big(advanced.effectiveStake)).to.equal(expected);
+    expect(big(advanced.warmingStake)).to.equal(target - expected);
+    expect(big((await pool()).totalEffectiveStake)).to.equal(
+      startTotal + expected - big(staked.effectiveStake)
+    );
+  });
+
+  it("rejects positions that are not warming up", async () => {
+    await send(arena, [
+      ixConfigureStakingPool({ admin: arena.admin.publicKey, warmupPeriodSecs: 0 }),
+    ]);
+    await stake(arena, staker, 1_000, 2);
+    await expectError(arena, [advance(2)], [keeper.keypair], "NotWarmingUp");
+  });
+});